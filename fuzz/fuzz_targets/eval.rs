@@ -0,0 +1,31 @@
+#![no_main]
+
+use cake_interpreter::{
+    evaluator::{Config, Evaluator, Limits},
+    lexer::Lexer,
+    object::environment::Environment,
+    parser::Parser,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|source: &str| {
+    let mut parser = Parser::new(Lexer::new(source));
+    let program = parser.parse();
+    if !parser.errors().is_empty() {
+        return;
+    }
+    // A low call limit keeps deep recursion within the fuzzer's stack, and
+    // the step budget stops programs that never end, such as endless tail
+    // calls. Inputs must not touch the file system.
+    let config = Config {
+        max_depth: 64,
+        limits: Limits {
+            max_steps: Some(100_000),
+            max_memory: Some(64 * 1024 * 1024),
+            ..Limits::default()
+        },
+        allow_io: false,
+        ..Config::default()
+    };
+    Evaluator::with_config(config).eval(&program, &Environment::new());
+});
@@ -0,0 +1,15 @@
+#![no_main]
+
+use cake_interpreter::{lexer::Lexer, token::Token};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|source: &str| {
+    let mut lexer = Lexer::new(source);
+    // Every token consumes at least one char.
+    for _ in 0..=source.chars().count() {
+        if lexer.next_token() == Token::EOF {
+            return;
+        }
+    }
+    panic!("lexer did not reach EOF");
+});
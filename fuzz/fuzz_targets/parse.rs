@@ -0,0 +1,16 @@
+#![no_main]
+
+use cake_interpreter::{ast::same_code::SameCode, formatter, lexer::Lexer, parser::Parser};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|source: &str| {
+    let mut parser = Parser::new(Lexer::new(source));
+    let program = parser.parse();
+    if parser.errors().is_empty() {
+        // Whatever parses can be printed and parsed back unchanged.
+        let printed = formatter::format(&program);
+        let mut reparser = Parser::new(Lexer::new(&printed));
+        // Only the spans may differ.
+        assert!(reparser.parse().same_code(&program), "{}", printed);
+    }
+});
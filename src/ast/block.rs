@@ -1,4 +1,4 @@
-use super::{ident::Ident, Node, Statement};
+use super::{types::Param, Node, Statement};
 
 impl Node for Vec<Statement> {
     fn literal(&self) -> String {
@@ -7,19 +7,19 @@ impl Node for Vec<Statement> {
             res.push_str(&statement.literal());
             res.push_str("; ");
         }
-        res.push_str("}");
+        res.push('}');
         res
     }
 }
 
-impl Node for Vec<Ident> {
+impl Node for Vec<Param> {
     fn literal(&self) -> String {
         let mut res = String::from("(");
 
-        let names = self.iter().map(|x| x.name.clone()).collect::<Vec<String>>();
+        let names = self.iter().map(|x| x.literal()).collect::<Vec<String>>();
         res.push_str(names.join(",").as_str());
 
-        res.push_str(")");
+        res.push(')');
         res
     }
 }
@@ -0,0 +1,16 @@
+use super::Node;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Comment {
+    // Text after the `//`, without trailing whitespace.
+    pub text: String,
+    // Whether the comment ends the line of the code before it rather than
+    // standing on its own line.
+    pub trailing: bool,
+}
+
+impl Node for Comment {
+    fn literal(&self) -> String {
+        format!("Comment({})", self.text.trim())
+    }
+}
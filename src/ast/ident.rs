@@ -1,8 +1,20 @@
+use crate::token::Span;
+
 use super::Node;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Ident {
     pub name: String,
+    pub span: Span,
+    // Unique among the identifiers of a program, and small: tables about
+    // them, like the resolver's slots, are indexed by it.
+    pub id: usize,
+}
+
+impl Ident {
+    pub fn new(name: String, span: Span, id: usize) -> Self {
+        Self { name, span, id }
+    }
 }
 
 impl Node for Ident {
@@ -1,3 +1,5 @@
+use crate::token::Span;
+
 use super::{Expr, Node, Statement};
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -5,6 +7,8 @@ pub struct IfExpression {
     pub condition: Expr,
     pub then: Box<Statement>,
     pub else_: Option<Box<Statement>>,
+    // The `if` keyword.
+    pub span: Span,
 }
 
 impl Node for IfExpression {
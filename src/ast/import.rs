@@ -0,0 +1,17 @@
+use crate::token::Span;
+
+use super::{ident::Ident, Node};
+
+// `import "path" as alias;`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Import {
+    pub path: String,
+    pub alias: Ident,
+    pub span: Span,
+}
+
+impl Node for Import {
+    fn literal(&self) -> String {
+        format!("Import({:?}, {})", self.path, self.alias.literal())
+    }
+}
@@ -0,0 +1,17 @@
+use super::{Expr, Node};
+
+// A piece of an interpolated string, `"text ${expr} text"`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Fragment {
+    Text(String),
+    Expr(Expr),
+}
+
+impl Node for Fragment {
+    fn literal(&self) -> String {
+        match self {
+            Fragment::Text(text) => format!("{:?}", text),
+            Fragment::Expr(expr) => expr.literal(),
+        }
+    }
+}
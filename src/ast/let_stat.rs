@@ -1,13 +1,44 @@
-use super::{ident::Ident, Expr, Node};
+use super::{ident::Ident, pattern::Pattern, types::TypeExpr, Expr, Node};
+use crate::token::Span;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Let {
-    pub ident: Ident,
+    // A plain name is a `Pattern::Binding`, even `_`; `[...]` and `{...}`
+    // destructure the value.
+    pub pattern: Pattern,
+    pub ty: Option<TypeExpr>,
     pub expr: Expr,
+    // `export let`: the binding is part of the module's interface.
+    pub export: bool,
+    // The `let` keyword.
+    pub span: Span,
+}
+
+impl Let {
+    // The name bound, unless the value is destructured.
+    pub fn ident(&self) -> Option<&Ident> {
+        match &self.pattern {
+            Pattern::Binding(ident) => Some(ident),
+            _ => None,
+        }
+    }
 }
 
 impl Node for Let {
     fn literal(&self) -> String {
-        format!("Let({}, {})", self.ident.literal(), self.expr.literal())
+        let literal = match &self.ty {
+            Some(ty) => format!(
+                "Let({}: {}, {})",
+                self.pattern.literal(),
+                ty,
+                self.expr.literal()
+            ),
+            None => format!("Let({}, {})", self.pattern.literal(), self.expr.literal()),
+        };
+        if self.export {
+            format!("Export({})", literal)
+        } else {
+            literal
+        }
     }
 }
@@ -0,0 +1,23 @@
+use super::{pattern::Pattern, Expr, Node, Statement};
+
+// `pattern if guard => body`. The body is a block or a single expression.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Arm {
+    pub pattern: Pattern,
+    pub guard: Option<Expr>,
+    pub body: Box<Statement>,
+}
+
+impl Node for Arm {
+    fn literal(&self) -> String {
+        match &self.guard {
+            Some(guard) => format!(
+                "Arm({} if {}, {})",
+                self.pattern.literal(),
+                guard.literal(),
+                self.body.literal()
+            ),
+            None => format!("Arm({}, {})", self.pattern.literal(), self.body.literal()),
+        }
+    }
+}
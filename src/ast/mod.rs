@@ -1,11 +1,22 @@
 use operators::{InfixOperator, PrefixOperator};
 
+use crate::token::Span;
+
 pub mod block;
+pub mod comment;
 pub mod ident;
 pub mod if_else;
+pub mod import;
+pub mod interpolated;
 pub mod let_stat;
+pub mod match_expr;
 pub mod operators;
+pub mod pattern;
 pub mod return_stat;
+pub mod same_code;
+pub mod throw_stat;
+pub mod try_catch;
+pub mod types;
 
 pub trait Node {
     fn literal(&self) -> String;
@@ -16,11 +27,51 @@ pub enum Expr {
     Ident(ident::Ident),
     Int(i64),
     Bool(bool),
+    String(String),
     Illegal(String),
-    Prefix(PrefixOperator, Box<Expr>),
-    Infix(Box<Expr>, InfixOperator, Box<Expr>),
-    Function(Vec<ident::Ident>, Box<Statement>),
-    Call(Box<Expr>, Vec<Expr>),
+    // Operator expressions carry the span of their operator, calls and
+    // indexing the span from the callee or indexed value to the closing
+    // delimiter.
+    Prefix(PrefixOperator, Box<Expr>, Span),
+    Infix(Box<Expr>, InfixOperator, Box<Expr>, Span),
+    // Parameters, return type and body.
+    Function(Vec<types::Param>, Option<types::TypeExpr>, Box<Statement>),
+    Call(Box<Expr>, Vec<Expr>, Span),
+    Array(Vec<Expr>),
+    Index(Box<Expr>, Box<Expr>, Span),
+    // Postfix `?`: unwraps an `ok` value or returns the `err` from the
+    // enclosing function.
+    Propagate(Box<Expr>, Span),
+    // `value.name`, with the span from the value to the name.
+    Member(Box<Expr>, ident::Ident, Span),
+    // `"text ${expr} text"`: never two texts in a row, nor empty ones.
+    Interpolated(Vec<interpolated::Fragment>, Span),
+    // `{key: value, ...}`, in source order, with the span of the `{`.
+    Hash(Vec<(Expr, Expr)>, Span),
+    // `if (condition) { ... } else { ... }`: the value of the branch taken,
+    // or `null` when there is none.
+    If(Box<if_else::IfExpression>),
+    // The value matched and the arms, with the span of the `match` keyword.
+    Match(Box<Expr>, Vec<match_expr::Arm>, Span),
+}
+
+impl Expr {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Expr::Ident(ident) => Some(ident.span),
+            Expr::If(if_expr) => Some(if_expr.span),
+            Expr::Prefix(_, _, span)
+            | Expr::Infix(_, _, _, span)
+            | Expr::Call(_, _, span)
+            | Expr::Index(_, _, span)
+            | Expr::Propagate(_, span)
+            | Expr::Member(_, _, span)
+            | Expr::Interpolated(_, span)
+            | Expr::Hash(_, span)
+            | Expr::Match(_, _, span) => Some(*span),
+            _ => None,
+        }
+    }
 }
 
 impl Node for Expr {
@@ -29,30 +80,95 @@ impl Node for Expr {
             Expr::Ident(ident) => ident.literal(),
             Expr::Illegal(s) => s.clone(),
             Expr::Int(i) => i.to_string(),
-            Expr::Prefix(op, expr) => format!("({:?}{})", op, expr.literal()),
-            Expr::Infix(left, op, right) => {
+            Expr::String(s) => format!("{:?}", s),
+            Expr::Prefix(op, expr, _) => format!("({:?}{})", op, expr.literal()),
+            Expr::Infix(left, op, right, _) => {
                 format!("({} {:?} {})", left.literal(), op, right.literal())
             }
             Expr::Bool(x) => x.to_string(),
-            Expr::Function(parameters, body) => {
-                format!("Func{} {}", parameters.literal(), body.literal())
-            }
-            Expr::Call(func, params) => {
+            Expr::Function(parameters, ret, body) => match ret {
+                Some(ret) => format!("Func{} -> {} {}", parameters.literal(), ret, body.literal()),
+                None => format!("Func{} {}", parameters.literal(), body.literal()),
+            },
+            Expr::Call(func, params, _) => {
                 let params = params.iter().map(|x| x.literal()).collect::<Vec<String>>();
                 format!("{}({})", func.literal(), params.join(","))
             }
+            Expr::Array(elements) => {
+                let elements = elements
+                    .iter()
+                    .map(|x| x.literal())
+                    .collect::<Vec<String>>();
+                format!("[{}]", elements.join(","))
+            }
+            Expr::Index(left, index, _) => format!("({}[{}])", left.literal(), index.literal()),
+            Expr::Propagate(expr, _) => format!("({}?)", expr.literal()),
+            Expr::Member(expr, name, _) => format!("({}.{})", expr.literal(), name.literal()),
+            Expr::Interpolated(fragments, _) => {
+                let fragments = fragments
+                    .iter()
+                    .map(|x| x.literal())
+                    .collect::<Vec<String>>();
+                format!("Interpolated({})", fragments.join(", "))
+            }
+            Expr::Hash(entries, _) => {
+                let entries = entries
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key.literal(), value.literal()))
+                    .collect::<Vec<String>>();
+                format!("{{{}}}", entries.join(","))
+            }
+            Expr::If(if_expr) => if_expr.literal(),
+            Expr::Match(subject, arms, _) => {
+                let arms = arms.iter().map(|x| x.literal()).collect::<Vec<String>>();
+                format!("Match({}, [{}])", subject.literal(), arms.join(", "))
+            }
         }
     }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[allow(clippy::enum_variant_names)]
 pub enum Statement {
     Let(let_stat::Let),
     Return(return_stat::Return),
     Expression(Expr),
     Illegal(String),
     BlockStatement(Vec<Statement>),
-    IfExpression(if_else::IfExpression),
+    Throw(throw_stat::Throw),
+    Try(try_catch::Try),
+    Import(import::Import),
+    // Comments are kept in the tree so that the formatter can print them
+    // back; they are skipped everywhere else.
+    Comment(comment::Comment),
+}
+
+impl Statement {
+    // Whether the statement always returns or throws, so that nothing after
+    // it in the same block runs.
+    pub fn always_exits(&self) -> bool {
+        match self {
+            Statement::Return(_) | Statement::Throw(_) => true,
+            Statement::BlockStatement(block) => block.iter().any(Statement::always_exits),
+            Statement::Expression(Expr::If(if_expr)) => match &if_expr.else_ {
+                Some(else_) => if_expr.then.always_exits() && else_.always_exits(),
+                None => false,
+            },
+            Statement::Try(try_stat) => {
+                let handled = match &try_stat.catch {
+                    Some(catch) => catch.body.always_exits(),
+                    None => true,
+                };
+                (try_stat.body.always_exits() && handled)
+                    || try_stat.finally.as_ref().is_some_and(|f| f.always_exits())
+            }
+            Statement::Let(_)
+            | Statement::Import(_)
+            | Statement::Expression(_)
+            | Statement::Comment(_)
+            | Statement::Illegal(_) => false,
+        }
+    }
 }
 
 impl Node for Statement {
@@ -63,19 +179,22 @@ impl Node for Statement {
             Statement::Expression(expr) => expr.literal(),
             Statement::Illegal(s) => s.clone(),
             Statement::BlockStatement(block) => block.literal(),
-            Statement::IfExpression(cond) => cond.literal(),
+            Statement::Throw(throw_stat) => throw_stat.literal(),
+            Statement::Try(try_stat) => try_stat.literal(),
+            Statement::Import(import) => import.literal(),
+            Statement::Comment(comment) => comment.literal(),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub struct Program {
     pub statements: Vec<Statement>,
 }
 
 impl Node for Program {
     fn literal(&self) -> String {
-        if self.statements.len() == 0 {
+        if self.statements.is_empty() {
             String::from("Program {}")
         } else {
             let mut res = String::from("Program {\n");
@@ -84,7 +203,7 @@ impl Node for Program {
                 res.push_str(&statement.literal());
                 res.push_str(";\n");
             }
-            res.push_str("}");
+            res.push('}');
             res
         }
     }
@@ -0,0 +1,100 @@
+use super::{ident::Ident, Expr, Node};
+
+// What a `match` arm or a `let` takes a value apart with.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Pattern {
+    // `_`: matches anything, binds nothing.
+    Wildcard,
+    // Matches anything and binds it to the name.
+    Binding(Ident),
+    Int(i64),
+    Bool(bool),
+    String(String),
+    // Matches arrays of exactly as many elements, or at least as many with a
+    // rest, which binds the others as an array.
+    Array(Vec<Pattern>, Option<Ident>),
+    // Matches hashes with all of the keys, and possibly others. Keys are
+    // int, bool or string literals; a bare `name` is short for
+    // `"name": name`. The rest binds the other entries as a hash.
+    Hash(Vec<(Expr, Pattern)>, Option<Ident>),
+    // `pattern = default`, for an element or an entry: the default is matched
+    // instead when the value has nothing there.
+    Default(Box<Pattern>, Expr),
+}
+
+impl Pattern {
+    // Whether the pattern matches every value.
+    pub fn is_irrefutable(&self) -> bool {
+        matches!(self, Pattern::Wildcard | Pattern::Binding(_))
+    }
+
+    // The names the pattern binds, from left to right.
+    pub fn bindings(&self) -> Vec<&Ident> {
+        match self {
+            Pattern::Binding(ident) => vec![ident],
+            Pattern::Array(elements, rest) => elements
+                .iter()
+                .flat_map(Pattern::bindings)
+                .chain(rest)
+                .collect(),
+            Pattern::Hash(entries, rest) => entries
+                .iter()
+                .flat_map(|(_, p)| p.bindings())
+                .chain(rest)
+                .collect(),
+            Pattern::Default(pattern, _) => pattern.bindings(),
+            Pattern::Wildcard | Pattern::Int(_) | Pattern::Bool(_) | Pattern::String(_) => {
+                vec![]
+            }
+        }
+    }
+
+    // The default expressions in the pattern, from left to right.
+    pub fn defaults(&self) -> Vec<&Expr> {
+        match self {
+            Pattern::Array(elements, _) => elements.iter().flat_map(Pattern::defaults).collect(),
+            Pattern::Hash(entries, _) => entries.iter().flat_map(|(_, p)| p.defaults()).collect(),
+            Pattern::Default(pattern, default) => {
+                let mut res = pattern.defaults();
+                res.push(default);
+                res
+            }
+            Pattern::Wildcard
+            | Pattern::Binding(_)
+            | Pattern::Int(_)
+            | Pattern::Bool(_)
+            | Pattern::String(_) => vec![],
+        }
+    }
+}
+
+impl Node for Pattern {
+    fn literal(&self) -> String {
+        match self {
+            Pattern::Wildcard => String::from("_"),
+            Pattern::Binding(ident) => ident.literal(),
+            Pattern::Int(i) => i.to_string(),
+            Pattern::Bool(b) => b.to_string(),
+            Pattern::String(s) => format!("{:?}", s),
+            Pattern::Array(elements, rest) => {
+                let elements = elements
+                    .iter()
+                    .map(|x| x.literal())
+                    .chain(rest.iter().map(|rest| format!("...{}", rest.literal())))
+                    .collect::<Vec<String>>();
+                format!("[{}]", elements.join(","))
+            }
+            Pattern::Hash(entries, rest) => {
+                let entries = entries
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key.literal(), value.literal()))
+                    .chain(rest.iter().map(|rest| format!("...{}", rest.literal())))
+                    .collect::<Vec<String>>();
+                format!("{{{}}}", entries.join(","))
+            }
+            Pattern::Default(pattern, default) => {
+                format!("{} = {}", pattern.literal(), default.literal())
+            }
+        }
+    }
+}
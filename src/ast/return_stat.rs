@@ -1,12 +1,19 @@
+use crate::token::Span;
+
 use super::{Expr, Node};
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Return {
-    pub return_expr: Expr,
+    // `None` for a bare `return`, which returns `null`.
+    pub return_expr: Option<Expr>,
+    pub span: Span,
 }
 
 impl Node for Return {
     fn literal(&self) -> String {
-        format!("Return({})", self.return_expr.literal())
+        match &self.return_expr {
+            Some(expr) => format!("Return({})", expr.literal()),
+            None => String::from("Return"),
+        }
     }
 }
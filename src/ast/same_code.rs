@@ -0,0 +1,251 @@
+use super::{
+    ident::Ident,
+    if_else::IfExpression,
+    import::Import,
+    interpolated::Fragment,
+    let_stat::Let,
+    match_expr::Arm,
+    pattern::Pattern,
+    return_stat::Return,
+    throw_stat::Throw,
+    try_catch::{Catch, Try},
+    types::Param,
+    Expr, Program, Statement,
+};
+
+// Equality of the code a tree stands for, leaving out the spans that say
+// where it was parsed: the same code parsed from differently laid out
+// sources, or built without a source, compares equal. Fields are listed in
+// full so that a new one can't be forgotten.
+pub trait SameCode {
+    fn same_code(&self, other: &Self) -> bool;
+}
+
+impl<T: SameCode> SameCode for Box<T> {
+    fn same_code(&self, other: &Self) -> bool {
+        (**self).same_code(other)
+    }
+}
+
+impl<T: SameCode> SameCode for Option<T> {
+    fn same_code(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Some(a), Some(b)) => a.same_code(b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T: SameCode> SameCode for Vec<T> {
+    fn same_code(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().zip(other).all(|(a, b)| a.same_code(b))
+    }
+}
+
+impl<A: SameCode, B: SameCode> SameCode for (A, B) {
+    fn same_code(&self, other: &Self) -> bool {
+        self.0.same_code(&other.0) && self.1.same_code(&other.1)
+    }
+}
+
+impl SameCode for Program {
+    fn same_code(&self, other: &Self) -> bool {
+        self.statements.same_code(&other.statements)
+    }
+}
+
+impl SameCode for Ident {
+    fn same_code(&self, other: &Self) -> bool {
+        let Ident {
+            name,
+            span: _,
+            id: _,
+        } = self;
+        *name == other.name
+    }
+}
+
+impl SameCode for Param {
+    fn same_code(&self, other: &Self) -> bool {
+        let Param { ident, ty } = self;
+        ident.same_code(&other.ident) && *ty == other.ty
+    }
+}
+
+impl SameCode for Statement {
+    fn same_code(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Statement::Let(a), Statement::Let(b)) => a.same_code(b),
+            (Statement::Return(a), Statement::Return(b)) => a.same_code(b),
+            (Statement::Expression(a), Statement::Expression(b)) => a.same_code(b),
+            (Statement::Illegal(a), Statement::Illegal(b)) => a == b,
+            (Statement::BlockStatement(a), Statement::BlockStatement(b)) => a.same_code(b),
+            (Statement::Throw(a), Statement::Throw(b)) => a.same_code(b),
+            (Statement::Try(a), Statement::Try(b)) => a.same_code(b),
+            (Statement::Import(a), Statement::Import(b)) => a.same_code(b),
+            (Statement::Comment(a), Statement::Comment(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl SameCode for Let {
+    fn same_code(&self, other: &Self) -> bool {
+        let Let {
+            pattern,
+            ty,
+            expr,
+            export,
+            span: _,
+        } = self;
+        pattern.same_code(&other.pattern)
+            && *ty == other.ty
+            && expr.same_code(&other.expr)
+            && *export == other.export
+    }
+}
+
+impl SameCode for Return {
+    fn same_code(&self, other: &Self) -> bool {
+        let Return {
+            return_expr,
+            span: _,
+        } = self;
+        return_expr.same_code(&other.return_expr)
+    }
+}
+
+impl SameCode for Throw {
+    fn same_code(&self, other: &Self) -> bool {
+        let Throw { expr, span: _ } = self;
+        expr.same_code(&other.expr)
+    }
+}
+
+impl SameCode for Try {
+    fn same_code(&self, other: &Self) -> bool {
+        let Try {
+            body,
+            catch,
+            finally,
+            span: _,
+        } = self;
+        body.same_code(&other.body)
+            && catch.same_code(&other.catch)
+            && finally.same_code(&other.finally)
+    }
+}
+
+impl SameCode for Catch {
+    fn same_code(&self, other: &Self) -> bool {
+        let Catch { ident, body } = self;
+        ident.same_code(&other.ident) && body.same_code(&other.body)
+    }
+}
+
+impl SameCode for Import {
+    fn same_code(&self, other: &Self) -> bool {
+        let Import {
+            path,
+            alias,
+            span: _,
+        } = self;
+        *path == other.path && alias.same_code(&other.alias)
+    }
+}
+
+impl SameCode for IfExpression {
+    fn same_code(&self, other: &Self) -> bool {
+        let IfExpression {
+            condition,
+            then,
+            else_,
+            span: _,
+        } = self;
+        condition.same_code(&other.condition)
+            && then.same_code(&other.then)
+            && else_.same_code(&other.else_)
+    }
+}
+
+impl SameCode for Arm {
+    fn same_code(&self, other: &Self) -> bool {
+        let Arm {
+            pattern,
+            guard,
+            body,
+        } = self;
+        pattern.same_code(&other.pattern)
+            && guard.same_code(&other.guard)
+            && body.same_code(&other.body)
+    }
+}
+
+impl SameCode for Fragment {
+    fn same_code(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Fragment::Text(a), Fragment::Text(b)) => a == b,
+            (Fragment::Expr(a), Fragment::Expr(b)) => a.same_code(b),
+            _ => false,
+        }
+    }
+}
+
+impl SameCode for Pattern {
+    fn same_code(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Pattern::Wildcard, Pattern::Wildcard) => true,
+            (Pattern::Binding(a), Pattern::Binding(b)) => a.same_code(b),
+            (Pattern::Int(a), Pattern::Int(b)) => a == b,
+            (Pattern::Bool(a), Pattern::Bool(b)) => a == b,
+            (Pattern::String(a), Pattern::String(b)) => a == b,
+            (Pattern::Array(a, rest_a), Pattern::Array(b, rest_b)) => {
+                a.same_code(b) && rest_a.same_code(rest_b)
+            }
+            (Pattern::Hash(a, rest_a), Pattern::Hash(b, rest_b)) => {
+                a.same_code(b) && rest_a.same_code(rest_b)
+            }
+            (Pattern::Default(a, default_a), Pattern::Default(b, default_b)) => {
+                a.same_code(b) && default_a.same_code(default_b)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl SameCode for Expr {
+    fn same_code(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Expr::Ident(a), Expr::Ident(b)) => a.same_code(b),
+            (Expr::Int(a), Expr::Int(b)) => a == b,
+            (Expr::Bool(a), Expr::Bool(b)) => a == b,
+            (Expr::String(a), Expr::String(b)) | (Expr::Illegal(a), Expr::Illegal(b)) => a == b,
+            (Expr::Prefix(op_a, a, _), Expr::Prefix(op_b, b, _)) => op_a == op_b && a.same_code(b),
+            (Expr::Infix(left_a, op_a, right_a, _), Expr::Infix(left_b, op_b, right_b, _)) => {
+                op_a == op_b && left_a.same_code(left_b) && right_a.same_code(right_b)
+            }
+            (Expr::Function(params_a, ret_a, body_a), Expr::Function(params_b, ret_b, body_b)) => {
+                params_a.same_code(params_b) && ret_a == ret_b && body_a.same_code(body_b)
+            }
+            (Expr::Call(func_a, args_a, _), Expr::Call(func_b, args_b, _)) => {
+                func_a.same_code(func_b) && args_a.same_code(args_b)
+            }
+            (Expr::Array(a), Expr::Array(b)) => a.same_code(b),
+            (Expr::Index(left_a, index_a, _), Expr::Index(left_b, index_b, _)) => {
+                left_a.same_code(left_b) && index_a.same_code(index_b)
+            }
+            (Expr::Propagate(a, _), Expr::Propagate(b, _)) => a.same_code(b),
+            (Expr::Member(a, name_a, _), Expr::Member(b, name_b, _)) => {
+                a.same_code(b) && name_a.same_code(name_b)
+            }
+            (Expr::Interpolated(a, _), Expr::Interpolated(b, _)) => a.same_code(b),
+            (Expr::Hash(a, _), Expr::Hash(b, _)) => a.same_code(b),
+            (Expr::If(a), Expr::If(b)) => a.same_code(b),
+            (Expr::Match(subject_a, arms_a, _), Expr::Match(subject_b, arms_b, _)) => {
+                subject_a.same_code(subject_b) && arms_a.same_code(arms_b)
+            }
+            _ => false,
+        }
+    }
+}
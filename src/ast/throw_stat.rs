@@ -0,0 +1,15 @@
+use crate::token::Span;
+
+use super::{Expr, Node};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Throw {
+    pub expr: Expr,
+    pub span: Span,
+}
+
+impl Node for Throw {
+    fn literal(&self) -> String {
+        format!("Throw({})", self.expr.literal())
+    }
+}
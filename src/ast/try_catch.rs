@@ -0,0 +1,36 @@
+use crate::token::Span;
+
+use super::{ident::Ident, Node, Statement};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Catch {
+    pub ident: Ident,
+    pub body: Box<Statement>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Try {
+    pub body: Box<Statement>,
+    pub catch: Option<Catch>,
+    pub finally: Option<Box<Statement>>,
+    // The `try` keyword.
+    pub span: Span,
+}
+
+impl Node for Try {
+    fn literal(&self) -> String {
+        let mut res = format!("Try({}", self.body.literal());
+        if let Some(catch) = &self.catch {
+            res.push_str(&format!(
+                ", Catch({}, {})",
+                catch.ident.literal(),
+                catch.body.literal()
+            ));
+        }
+        if let Some(finally) = &self.finally {
+            res.push_str(&format!(", Finally({})", finally.literal()));
+        }
+        res.push(')');
+        res
+    }
+}
@@ -0,0 +1,73 @@
+use std::fmt::Display;
+
+use super::{ident::Ident, Node};
+
+// A type annotation, as written in the source.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum TypeExpr {
+    Int,
+    Bool,
+    Str,
+    Null,
+    Result,
+    Error,
+    // Opts out of checking.
+    Any,
+    Array(Box<TypeExpr>),
+    Func(Vec<TypeExpr>, Box<TypeExpr>),
+}
+
+// Names of the types that are spelled as a single word.
+pub const TYPE_NAMES: &[(&str, TypeExpr)] = &[
+    ("int", TypeExpr::Int),
+    ("bool", TypeExpr::Bool),
+    ("str", TypeExpr::Str),
+    ("null", TypeExpr::Null),
+    ("result", TypeExpr::Result),
+    ("error", TypeExpr::Error),
+    ("any", TypeExpr::Any),
+];
+
+impl TypeExpr {
+    pub fn from_name(name: &str) -> Option<Self> {
+        TYPE_NAMES
+            .iter()
+            .find(|(type_name, _)| *type_name == name)
+            .map(|(_, ty)| ty.clone())
+    }
+}
+
+impl Display for TypeExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypeExpr::Array(element) => write!(f, "[{}]", element),
+            TypeExpr::Func(params, ret) => {
+                let params: Vec<String> = params.iter().map(|p| p.to_string()).collect();
+                write!(f, "func({}) -> {}", params.join(", "), ret)
+            }
+            ty => {
+                let name = TYPE_NAMES
+                    .iter()
+                    .find(|(_, named)| named == ty)
+                    .map_or("?", |(name, _)| name);
+                write!(f, "{}", name)
+            }
+        }
+    }
+}
+
+// A function parameter and its annotation, if it has one.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Param {
+    pub ident: Ident,
+    pub ty: Option<TypeExpr>,
+}
+
+impl Node for Param {
+    fn literal(&self) -> String {
+        match &self.ty {
+            Some(ty) => format!("{}: {}", self.ident.literal(), ty),
+            None => self.ident.literal(),
+        }
+    }
+}
@@ -0,0 +1,806 @@
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{
+    ast::{
+        ident::Ident,
+        if_else::IfExpression,
+        interpolated::Fragment,
+        let_stat::Let,
+        match_expr::Arm,
+        operators::{InfixOperator, PrefixOperator},
+        pattern::Pattern,
+        types::{Param, TypeExpr},
+        Expr, Program, Statement,
+    },
+    token::Span,
+};
+
+mod test;
+
+// Hindley-Milner inference made gradual: unannotated code gets type
+// variables, solved by unification, and `Any` fits everything. Code that is
+// only known at runtime, such as mixed arrays or branches of different types,
+// becomes `Any` instead of an error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Type {
+    Int,
+    Bool,
+    Str,
+    Null,
+    Result,
+    Error,
+    Any,
+    Array(Box<Type>),
+    Func(Vec<Type>, Box<Type>),
+    Var(usize),
+}
+
+impl Type {
+    pub fn from_annotation(ty: &TypeExpr) -> Self {
+        match ty {
+            TypeExpr::Int => Type::Int,
+            TypeExpr::Bool => Type::Bool,
+            TypeExpr::Str => Type::Str,
+            TypeExpr::Null => Type::Null,
+            TypeExpr::Result => Type::Result,
+            TypeExpr::Error => Type::Error,
+            TypeExpr::Any => Type::Any,
+            TypeExpr::Array(element) => Type::Array(Box::new(Type::from_annotation(element))),
+            TypeExpr::Func(params, ret) => Type::Func(
+                params.iter().map(Type::from_annotation).collect(),
+                Box::new(Type::from_annotation(ret)),
+            ),
+        }
+    }
+}
+
+// Types print as they are written in annotations; unsolved variables as `?`.
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Int => write!(f, "int"),
+            Type::Bool => write!(f, "bool"),
+            Type::Str => write!(f, "str"),
+            Type::Null => write!(f, "null"),
+            Type::Result => write!(f, "result"),
+            Type::Error => write!(f, "error"),
+            Type::Any => write!(f, "any"),
+            Type::Array(element) => write!(f, "[{}]", element),
+            Type::Func(params, ret) => {
+                let params: Vec<String> = params.iter().map(|p| p.to_string()).collect();
+                write!(f, "func({}) -> {}", params.join(", "), ret)
+            }
+            Type::Var(_) => write!(f, "?"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl Display for TypeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+// A type with variables that each use of the name instantiates afresh.
+#[derive(Debug, Clone)]
+struct Scheme {
+    vars: Vec<usize>,
+    ty: Type,
+}
+
+impl Scheme {
+    fn mono(ty: Type) -> Self {
+        Self { vars: vec![], ty }
+    }
+}
+
+// What checking a program found out about it.
+#[derive(Debug, Clone)]
+pub struct Inference {
+    // The type of the value the program ends with.
+    pub ty: Type,
+    // The type of each name it binds, by the offset of its identifier.
+    pub bindings: HashMap<usize, Type>,
+    pub errors: Vec<TypeError>,
+}
+
+pub fn check(program: &Program) -> Vec<TypeError> {
+    infer(program, HashMap::new()).errors
+}
+
+// Checks `program` without running it, given the types of the names already
+// bound.
+pub fn infer(program: &Program, globals: HashMap<String, Type>) -> Inference {
+    let globals = globals
+        .into_iter()
+        .map(|(name, ty)| (name, Scheme::mono(ty)))
+        .collect();
+    let mut checker = Checker {
+        vars: Vec::new(),
+        scopes: vec![globals],
+        returns: Vec::new(),
+        bindings: HashMap::new(),
+        errors: Vec::new(),
+    };
+    let ty = checker.block(&program.statements, Span::default());
+    checker.errors.sort_by_key(|err| err.span.start);
+    let bindings = checker
+        .bindings
+        .iter()
+        .map(|(start, ty)| (*start, checker.resolve(ty)))
+        .collect();
+    Inference {
+        ty: checker.resolve(&ty),
+        bindings,
+        errors: checker.errors,
+    }
+}
+
+struct Checker {
+    // What each type variable was solved to, if it was.
+    vars: Vec<Option<Type>>,
+    // Functions, `catch` blocks and `match` arms open a scope, other blocks
+    // share the one around them, as they do when evaluated.
+    scopes: Vec<HashMap<String, Scheme>>,
+    // Return type of each function being checked, innermost last.
+    returns: Vec<Type>,
+    // The type each name was last bound with, by the offset of its
+    // identifier.
+    bindings: HashMap<usize, Type>,
+    errors: Vec<TypeError>,
+}
+
+impl Checker {
+    fn error(&mut self, message: String, span: Span) {
+        self.errors.push(TypeError { message, span });
+    }
+
+    fn fresh(&mut self) -> Type {
+        self.vars.push(None);
+        Type::Var(self.vars.len() - 1)
+    }
+
+    // Follows solved variables at the top of `ty`.
+    fn prune(&self, ty: &Type) -> Type {
+        let mut ty = ty.clone();
+        while let Type::Var(var) = ty {
+            match &self.vars[var] {
+                Some(solved) => ty = solved.clone(),
+                None => break,
+            }
+        }
+        ty
+    }
+
+    // `ty` with every solved variable replaced, for messages.
+    fn resolve(&self, ty: &Type) -> Type {
+        match self.prune(ty) {
+            Type::Array(element) => Type::Array(Box::new(self.resolve(&element))),
+            Type::Func(params, ret) => Type::Func(
+                params.iter().map(|p| self.resolve(p)).collect(),
+                Box::new(self.resolve(&ret)),
+            ),
+            ty => ty,
+        }
+    }
+
+    fn occurs(&self, var: usize, ty: &Type) -> bool {
+        match self.prune(ty) {
+            Type::Var(other) => other == var,
+            Type::Array(element) => self.occurs(var, &element),
+            Type::Func(params, ret) => {
+                params.iter().any(|p| self.occurs(var, p)) || self.occurs(var, &ret)
+            }
+            _ => false,
+        }
+    }
+
+    fn unify(&mut self, a: &Type, b: &Type) -> bool {
+        match (self.prune(a), self.prune(b)) {
+            (Type::Any, _) | (_, Type::Any) => true,
+            (Type::Var(a), Type::Var(b)) if a == b => true,
+            (Type::Var(var), ty) | (ty, Type::Var(var)) => {
+                // A recursive type cannot be written down: leave it open.
+                if !self.occurs(var, &ty) {
+                    self.vars[var] = Some(ty);
+                }
+                true
+            }
+            (Type::Array(a), Type::Array(b)) => self.unify(&a, &b),
+            (Type::Func(a_params, a_ret), Type::Func(b_params, b_ret)) => {
+                a_params.len() == b_params.len()
+                    && a_params
+                        .iter()
+                        .zip(&b_params)
+                        .all(|(a, b)| self.unify(a, b))
+                    && self.unify(&a_ret, &b_ret)
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    // Reports a mismatch between what was expected at `span` and what was
+    // found.
+    fn expect(&mut self, expected: &Type, found: &Type, span: Span) {
+        if !self.unify(expected, found) {
+            let message = format!(
+                "type mismatch: expected {}, got {}",
+                self.resolve(expected),
+                self.resolve(found)
+            );
+            self.error(message, span);
+        }
+    }
+
+    // Infers `expr` and checks it against `expected`.
+    fn check_expr(&mut self, expr: &Expr, expected: &Type, span: Span) {
+        let found = self.infer(expr, span);
+        self.expect(expected, &found, expr.span().unwrap_or(span));
+    }
+
+    fn free_vars(&self, ty: &Type, out: &mut Vec<usize>) {
+        match self.prune(ty) {
+            Type::Var(var) if !out.contains(&var) => out.push(var),
+            Type::Array(element) => self.free_vars(&element, out),
+            Type::Func(params, ret) => {
+                params.iter().for_each(|p| self.free_vars(p, out));
+                self.free_vars(&ret, out);
+            }
+            _ => {}
+        }
+    }
+
+    // Quantifies the variables of `ty` that no enclosing binding mentions.
+    fn generalize(&self, ty: Type) -> Scheme {
+        let mut vars = Vec::new();
+        self.free_vars(&ty, &mut vars);
+        let mut bound = Vec::new();
+        for scheme in self.scopes.iter().flat_map(|scope| scope.values()) {
+            let mut scheme_vars = Vec::new();
+            self.free_vars(&scheme.ty, &mut scheme_vars);
+            bound.extend(
+                scheme_vars
+                    .into_iter()
+                    .filter(|var| !scheme.vars.contains(var)),
+            );
+        }
+        vars.retain(|var| !bound.contains(var));
+        Scheme { vars, ty }
+    }
+
+    fn instantiate(&mut self, scheme: &Scheme) -> Type {
+        let fresh: Vec<(usize, Type)> =
+            scheme.vars.iter().map(|var| (*var, self.fresh())).collect();
+        self.substitute(&scheme.ty, &fresh)
+    }
+
+    fn substitute(&self, ty: &Type, fresh: &[(usize, Type)]) -> Type {
+        match self.prune(ty) {
+            Type::Var(var) => fresh
+                .iter()
+                .find(|(old, _)| *old == var)
+                .map_or(Type::Var(var), |(_, new)| new.clone()),
+            Type::Array(element) => Type::Array(Box::new(self.substitute(&element, fresh))),
+            Type::Func(params, ret) => Type::Func(
+                params.iter().map(|p| self.substitute(p, fresh)).collect(),
+                Box::new(self.substitute(&ret, fresh)),
+            ),
+            ty => ty,
+        }
+    }
+
+    fn bind(&mut self, ident: &Ident, scheme: Scheme) {
+        self.bindings.insert(ident.span.start, scheme.ty.clone());
+        let scope = self
+            .scopes
+            .last_mut()
+            .expect("the global scope is never left");
+        scope.insert(ident.name.clone(), scheme);
+    }
+
+    fn lookup(&mut self, name: &str) -> Type {
+        let scheme = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .cloned();
+        match scheme {
+            Some(scheme) => self.instantiate(&scheme),
+            None => builtin(name).unwrap_or(Type::Any),
+        }
+    }
+
+    // Checks a list of statements and returns the type of the value it
+    // evaluates to: that of its last statement.
+    fn block(&mut self, statements: &[Statement], span: Span) -> Type {
+        let mut value = Type::Null;
+        for statement in statements {
+            if let Statement::Comment(_) = statement {
+                continue;
+            }
+            value = self.statement(statement, span);
+        }
+        value
+    }
+
+    fn statement(&mut self, statement: &Statement, span: Span) -> Type {
+        match statement {
+            Statement::Let(let_stat) => {
+                let Some(ident) = let_stat.ident() else {
+                    self.destructure(let_stat);
+                    return Type::Null;
+                };
+                let span = ident.span;
+                let name = &ident.name;
+                match &let_stat.ty {
+                    Some(ty) => {
+                        let ty = Type::from_annotation(ty);
+                        // Annotated functions can call themselves.
+                        self.bind(ident, Scheme::mono(ty.clone()));
+                        self.check_expr(&let_stat.expr, &ty, span);
+                    }
+                    None if matches!(let_stat.expr, Expr::Function(..)) => {
+                        let ty = self.fresh();
+                        self.bind(ident, Scheme::mono(ty.clone()));
+                        let found = self.infer(&let_stat.expr, span);
+                        self.unify(&ty, &found);
+                        // The function's own binding mentions all its
+                        // variables, and must not keep them from being
+                        // generalized.
+                        if let Some(scope) = self.scopes.last_mut() {
+                            scope.remove(name);
+                        }
+                        let scheme = self.generalize(ty);
+                        self.bind(ident, scheme);
+                    }
+                    None => {
+                        let ty = self.infer(&let_stat.expr, span);
+                        self.bind(ident, Scheme::mono(ty));
+                    }
+                }
+                Type::Null
+            }
+            Statement::Return(return_stat) => {
+                let found = match &return_stat.return_expr {
+                    Some(expr) => self.infer(expr, return_stat.span),
+                    None => Type::Null,
+                };
+                if let Some(expected) = self.returns.last().cloned() {
+                    let span = return_stat
+                        .return_expr
+                        .as_ref()
+                        .and_then(Expr::span)
+                        .unwrap_or(return_stat.span);
+                    self.expect(&expected, &found, span);
+                }
+                Type::Any
+            }
+            Statement::Throw(throw_stat) => {
+                self.infer(&throw_stat.expr, throw_stat.span);
+                Type::Any
+            }
+            Statement::Expression(expr) => self.infer(expr, span),
+            Statement::BlockStatement(block) => self.block(block, span),
+            Statement::Try(try_stat) => {
+                self.statement(&try_stat.body, try_stat.span);
+                if let Some(catch) = &try_stat.catch {
+                    self.scopes.push(HashMap::new());
+                    self.bind(&catch.ident, Scheme::mono(Type::Error));
+                    self.statement(&catch.body, try_stat.span);
+                    self.scopes.pop();
+                }
+                if let Some(finally) = &try_stat.finally {
+                    self.statement(finally, try_stat.span);
+                }
+                Type::Any
+            }
+            // Modules are checked on their own, and what they export is not
+            // known here.
+            Statement::Import(import) => {
+                self.bind(&import.alias, Scheme::mono(Type::Any));
+                Type::Null
+            }
+            Statement::Comment(_) | Statement::Illegal(_) => Type::Any,
+        }
+    }
+
+    fn infer_if(&mut self, if_expr: &IfExpression) -> Type {
+        // Any value can be a condition.
+        self.infer(&if_expr.condition, if_expr.span);
+        let then = self.statement(&if_expr.then, if_expr.span);
+        match &if_expr.else_ {
+            Some(else_) => {
+                let otherwise = self.statement(else_, if_expr.span);
+                self.join(&if_expr.then, then, else_, otherwise)
+            }
+            None => Type::Any,
+        }
+    }
+
+    // The value of an `if` with both branches: a branch that always leaves
+    // gives none, and branches of different types give `Any`.
+    fn join(&self, then: &Statement, a: Type, otherwise: &Statement, b: Type) -> Type {
+        if then.always_exits() {
+            return b;
+        }
+        if otherwise.always_exits() {
+            return a;
+        }
+        let (a, b) = (self.resolve(&a), self.resolve(&b));
+        if a == b {
+            a
+        } else {
+            Type::Any
+        }
+    }
+
+    // `span` locates expressions that have no location of their own.
+    fn infer(&mut self, expr: &Expr, span: Span) -> Type {
+        match expr {
+            Expr::Ident(ident) => self.lookup(&ident.name),
+            Expr::Int(_) => Type::Int,
+            Expr::Bool(_) => Type::Bool,
+            Expr::String(_) => Type::Str,
+            Expr::Illegal(_) => Type::Any,
+            Expr::Array(elements) => {
+                let types: Vec<Type> = elements.iter().map(|e| self.infer(e, span)).collect();
+                let types: Vec<Type> = types.iter().map(|ty| self.resolve(ty)).collect();
+                match types.first() {
+                    None => Type::Array(Box::new(self.fresh())),
+                    Some(first) if types.iter().all(|ty| ty == first) => {
+                        Type::Array(Box::new(first.clone()))
+                    }
+                    Some(_) => Type::Array(Box::new(Type::Any)),
+                }
+            }
+            Expr::Prefix(op, right, span) => match op {
+                PrefixOperator::Bang => {
+                    self.infer(right, *span);
+                    Type::Bool
+                }
+                PrefixOperator::Minus | PrefixOperator::Plus => {
+                    self.check_expr(right, &Type::Int, *span);
+                    Type::Int
+                }
+            },
+            Expr::Infix(left, op, right, span) => self.infer_infix(left, op, right, *span),
+            Expr::Function(params, ret, body) => self.infer_function(params, ret, body, span),
+            Expr::Call(func, args, span) => self.infer_call(func, args, *span),
+            Expr::Index(left, index, span) => {
+                let left = self.infer(left, *span);
+                match self.prune(&left) {
+                    Type::Array(element) => {
+                        self.check_expr(index, &Type::Int, *span);
+                        *element
+                    }
+                    Type::Str => {
+                        self.check_expr(index, &Type::Int, *span);
+                        Type::Str
+                    }
+                    Type::Error => {
+                        self.check_expr(index, &Type::Str, *span);
+                        match index.as_ref() {
+                            Expr::String(key) => error_field(key),
+                            _ => Type::Any,
+                        }
+                    }
+                    Type::Var(_) | Type::Any => {
+                        self.infer(index, *span);
+                        Type::Any
+                    }
+                    other => {
+                        self.infer(index, *span);
+                        self.error(format!("index operator not supported: {}", other), *span);
+                        Type::Any
+                    }
+                }
+            }
+            Expr::Propagate(expr, span) => {
+                self.check_expr(expr, &Type::Result, *span);
+                Type::Any
+            }
+            Expr::Member(expr, name, span) => {
+                let value = self.infer(expr, *span);
+                match self.prune(&value) {
+                    Type::Error => error_field(&name.name),
+                    _ => Type::Any,
+                }
+            }
+            // Any value can be embedded.
+            Expr::Interpolated(fragments, span) => {
+                for fragment in fragments {
+                    if let Fragment::Expr(expr) = fragment {
+                        self.infer(expr, *span);
+                    }
+                }
+                Type::Str
+            }
+            // Hashes are not typed yet: any key, any value.
+            Expr::Hash(entries, _) => {
+                for (key, value) in entries {
+                    self.infer(key, span);
+                    self.infer(value, span);
+                }
+                Type::Any
+            }
+            Expr::If(if_expr) => self.infer_if(if_expr),
+            Expr::Match(subject, arms, span) => self.infer_match(subject, arms, *span),
+        }
+    }
+
+    // The names of a destructuring `let` get the types of the parts they
+    // match, as in a `match`.
+    fn destructure(&mut self, let_stat: &Let) {
+        let span = let_stat.span;
+        let ty = match &let_stat.ty {
+            Some(ty) => {
+                let ty = Type::from_annotation(ty);
+                self.check_expr(&let_stat.expr, &ty, span);
+                ty
+            }
+            None => self.infer(&let_stat.expr, span),
+        };
+        self.pattern(&let_stat.pattern, &ty, span);
+    }
+
+    // The value of a `match` is that of its arms, as for the branches of an
+    // `if`. Arms are free to expect values of different types when the
+    // subject's is not known: they do not constrain it.
+    fn infer_match(&mut self, subject: &Expr, arms: &[Arm], span: Span) -> Type {
+        let subject = match self.infer(subject, span) {
+            ty if matches!(self.prune(&ty), Type::Var(_)) => Type::Any,
+            ty => ty,
+        };
+        let mut value: Option<Type> = None;
+        for arm in arms {
+            // Each arm binds its names in a scope of its own.
+            self.scopes.push(HashMap::new());
+            self.pattern(&arm.pattern, &subject, span);
+            if let Some(guard) = &arm.guard {
+                self.infer(guard, span);
+            }
+            let ty = self.statement(&arm.body, span);
+            self.scopes.pop();
+            if arm.body.always_exits() {
+                continue;
+            }
+            let ty = self.resolve(&ty);
+            value = match value {
+                Some(value) if value != ty => Some(Type::Any),
+                _ => Some(ty),
+            };
+        }
+        value.unwrap_or(Type::Any)
+    }
+
+    // Binds the names of a pattern matched against a value of type `ty`,
+    // and reports the patterns that can never match it.
+    fn pattern(&mut self, pattern: &Pattern, ty: &Type, span: Span) {
+        let known = match self.prune(ty) {
+            Type::Var(_) | Type::Any => None,
+            ty => Some(ty),
+        };
+        let never = |expected: &str| {
+            known
+                .as_ref()
+                .map(|ty| format!("{} pattern never matches {}", expected, ty))
+        };
+        let message = match pattern {
+            Pattern::Wildcard => None,
+            Pattern::Binding(ident) => {
+                self.bind(ident, Scheme::mono(ty.clone()));
+                None
+            }
+            Pattern::Int(_) if known != Some(Type::Int) => never("int"),
+            Pattern::Bool(_) if known != Some(Type::Bool) => never("bool"),
+            Pattern::String(_) if known != Some(Type::Str) => never("str"),
+            Pattern::Int(_) | Pattern::Bool(_) | Pattern::String(_) => None,
+            // The default stands in for a part of type `ty`.
+            Pattern::Default(pattern, default) => {
+                let found = self.infer(default, span);
+                if known.is_some() {
+                    self.expect(ty, &found, default.span().unwrap_or(span));
+                }
+                self.pattern(pattern, ty, span);
+                None
+            }
+            Pattern::Array(elements, rest) => {
+                let element = match &known {
+                    Some(Type::Array(element)) => *element.clone(),
+                    _ => Type::Any,
+                };
+                for pattern in elements {
+                    self.pattern(pattern, &element, span);
+                }
+                if let Some(rest) = rest {
+                    let ty = Type::Array(Box::new(element));
+                    self.bind(rest, Scheme::mono(ty));
+                }
+                match &known {
+                    Some(Type::Array(_)) => None,
+                    _ => never("array"),
+                }
+            }
+            // Caught errors have fields too.
+            Pattern::Hash(entries, rest) => {
+                for (_, pattern) in entries {
+                    self.pattern(pattern, &Type::Any, span);
+                }
+                if let Some(rest) = rest {
+                    self.bind(rest, Scheme::mono(Type::Any));
+                }
+                match &known {
+                    Some(Type::Error) => None,
+                    _ => never("hash"),
+                }
+            }
+        };
+        if let Some(message) = message {
+            self.error(message, span);
+        }
+    }
+
+    fn infer_infix(&mut self, left: &Expr, op: &InfixOperator, right: &Expr, span: Span) -> Type {
+        match op {
+            InfixOperator::Plus => {
+                let left = self.infer(left, span);
+                let right_ty = self.infer(right, span);
+                match self.prune(&left) {
+                    // Numbers add, strings concatenate.
+                    ty @ (Type::Int | Type::Str) => {
+                        self.expect(&ty, &right_ty, right.span().unwrap_or(span));
+                        ty
+                    }
+                    Type::Var(_) | Type::Any => {
+                        self.unify(&left, &right_ty);
+                        left
+                    }
+                    other => {
+                        let message =
+                            format!("unknown operator: {} + {}", other, self.resolve(&right_ty));
+                        self.error(message, span);
+                        Type::Any
+                    }
+                }
+            }
+            InfixOperator::Minus | InfixOperator::Star | InfixOperator::Slash => {
+                self.check_expr(left, &Type::Int, span);
+                self.check_expr(right, &Type::Int, span);
+                Type::Int
+            }
+            InfixOperator::LessThan | InfixOperator::GreaterThan => {
+                self.check_expr(left, &Type::Int, span);
+                self.check_expr(right, &Type::Int, span);
+                Type::Bool
+            }
+            // Values of different types cannot be compared.
+            InfixOperator::Eq | InfixOperator::NotEq => {
+                let left = self.infer(left, span);
+                let right_ty = self.infer(right, span);
+                self.expect(&left, &right_ty, span);
+                Type::Bool
+            }
+        }
+    }
+
+    fn infer_function(
+        &mut self,
+        params: &[Param],
+        ret: &Option<TypeExpr>,
+        body: &Statement,
+        span: Span,
+    ) -> Type {
+        let param_types: Vec<Type> = params
+            .iter()
+            .map(|param| match &param.ty {
+                Some(ty) => Type::from_annotation(ty),
+                None => self.fresh(),
+            })
+            .collect();
+        let ret = match ret {
+            Some(ty) => Type::from_annotation(ty),
+            None => self.fresh(),
+        };
+        self.scopes.push(HashMap::new());
+        for (param, ty) in params.iter().zip(&param_types) {
+            self.bind(&param.ident, Scheme::mono(ty.clone()));
+        }
+        self.returns.push(ret.clone());
+        let value = self.statement(body, span);
+        // Falling off the end returns the value of the last statement.
+        if !body.always_exits() {
+            let span = last_span(body).unwrap_or(span);
+            self.expect(&ret, &value, span);
+        }
+        self.returns.pop();
+        self.scopes.pop();
+        Type::Func(param_types, Box::new(ret))
+    }
+
+    fn infer_call(&mut self, func: &Expr, args: &[Expr], span: Span) -> Type {
+        let func_ty = self.infer(func, span);
+        match self.prune(&func_ty) {
+            Type::Func(params, ret) => {
+                if params.len() != args.len() {
+                    let message = format!(
+                        "wrong number of arguments: expected {}, got {}",
+                        params.len(),
+                        args.len()
+                    );
+                    self.error(message, span);
+                    args.iter().for_each(|arg| {
+                        self.infer(arg, span);
+                    });
+                } else {
+                    for (arg, param) in args.iter().zip(&params) {
+                        self.check_expr(arg, param, span);
+                    }
+                }
+                *ret
+            }
+            Type::Var(_) => {
+                let args: Vec<Type> = args.iter().map(|arg| self.infer(arg, span)).collect();
+                let ret = self.fresh();
+                self.unify(&func_ty, &Type::Func(args, Box::new(ret.clone())));
+                ret
+            }
+            Type::Any => {
+                args.iter().for_each(|arg| {
+                    self.infer(arg, span);
+                });
+                Type::Any
+            }
+            other => {
+                args.iter().for_each(|arg| {
+                    self.infer(arg, span);
+                });
+                self.error(format!("not a function: {}", other), span);
+                Type::Any
+            }
+        }
+    }
+}
+
+// Types of the builtins that have a precise one.
+fn builtin(name: &str) -> Option<Type> {
+    let func = |params: Vec<Type>, ret: Type| Some(Type::Func(params, Box::new(ret)));
+    match name {
+        "ok" | "err" => func(vec![Type::Any], Type::Result),
+        "is_ok" | "is_err" => func(vec![Type::Result], Type::Bool),
+        "unwrap" | "unwrap_err" => func(vec![Type::Result], Type::Any),
+        "unwrap_or" => func(vec![Type::Result, Type::Any], Type::Any),
+        "gc" => func(Vec::new(), Type::Int),
+        _ => None,
+    }
+}
+
+// The type of a caught error's field, read as `e.name` or `e["name"]`.
+fn error_field(name: &str) -> Type {
+    match name {
+        "message" | "kind" => Type::Str,
+        "trace" => Type::Array(Box::new(Type::Str)),
+        _ => Type::Any,
+    }
+}
+
+// Where the value of a block comes from: its last statement.
+fn last_span(statement: &Statement) -> Option<Span> {
+    match statement {
+        Statement::BlockStatement(block) => block
+            .iter()
+            .rev()
+            .find(|s| !matches!(s, Statement::Comment(_)))
+            .and_then(last_span),
+        Statement::Expression(expr) => expr.span(),
+        Statement::Let(let_stat) => Some(let_stat.ident().map_or(let_stat.span, |i| i.span)),
+        Statement::Try(try_stat) => Some(try_stat.span),
+        _ => None,
+    }
+}
@@ -0,0 +1,144 @@
+#[cfg(test)]
+mod tests {
+    use crate::{checker::check, lexer, parser::Parser};
+
+    // The type errors of `input`, as `line:column message`.
+    fn errors(input: &str) -> Vec<String> {
+        let mut parser = Parser::new(lexer::Lexer::new(input));
+        let program = parser.parse();
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+        check(&program)
+            .iter()
+            .map(|err| format!("{} {}", err.span, err))
+            .collect()
+    }
+
+    #[test]
+    fn test_check_annotations() {
+        assert!(errors("let x: int = 5; let s: str = \"a\"; let a: [int] = [1, 2];").is_empty());
+        assert_eq!(
+            errors("let x: int = \"five\";"),
+            ["1:5 type mismatch: expected int, got str"]
+        );
+        assert_eq!(
+            errors("let a: [str] = [1];"),
+            ["1:5 type mismatch: expected [str], got [int]"]
+        );
+        assert_eq!(
+            errors("let f = func(a: int, b: str) -> bool { a; };"),
+            ["1:40 type mismatch: expected bool, got int"]
+        );
+        assert_eq!(
+            errors("let f = func(a: int) -> int { if (a > 0) { return true; }; a; };"),
+            ["1:44 type mismatch: expected int, got bool"]
+        );
+        // `any` accepts every value and fits everywhere.
+        assert!(errors("let x: any = 1; let y: str = x;").is_empty());
+    }
+
+    #[test]
+    fn test_check_inference() {
+        assert_eq!(
+            errors("let x = 1; let y = x + \"a\";"),
+            ["1:22 type mismatch: expected int, got str"]
+        );
+        assert_eq!(
+            errors("let f = func(a, b) { a - b; }; f(1, true);"),
+            ["1:32 type mismatch: expected int, got bool"]
+        );
+        assert_eq!(
+            errors("let f = func(a) { a; }; f(1, 2);"),
+            ["1:25 wrong number of arguments: expected 1, got 2"]
+        );
+        assert_eq!(errors("let x = 1; x(2);"), ["1:12 not a function: int"]);
+        assert_eq!(
+            errors("let x = true; -x;"),
+            ["1:16 type mismatch: expected int, got bool"]
+        );
+        assert_eq!(
+            errors("1 == \"1\";"),
+            ["1:3 type mismatch: expected int, got str"]
+        );
+        assert_eq!(
+            errors("let x: str = if (true) { 1 } else { 2 };"),
+            ["1:14 type mismatch: expected str, got int"]
+        );
+    }
+
+    #[test]
+    fn test_check_generalization() {
+        // Each use of a generic function gets its own types.
+        assert!(
+            errors("let id = func(x) { x; }; let a: int = id(1); let b: str = id(\"b\");")
+                .is_empty()
+        );
+        assert_eq!(
+            errors("let id = func(x) { x; }; let a: int = id(\"a\");"),
+            ["1:39 type mismatch: expected int, got str"]
+        );
+        // Recursive functions are checked against themselves.
+        assert_eq!(
+            errors("let f = func(n) { if (n < 1) { return 0; }; f(n - 1) + \"a\"; };"),
+            ["1:54 type mismatch: expected int, got str"]
+        );
+    }
+
+    #[test]
+    fn test_check_gradual() {
+        // Values whose type is only known at runtime are not errors.
+        assert!(errors(
+            "let a = [1, \"a\"]; let x = a[0] + 1; \
+             let f = func(c) { if (c) { 1; } else { \"one\"; } }; f(true) + 1; \
+             let r = ok(1); let v: int = unwrap(r); \
+             try { throw \"e\"; } catch (e) { let m: str = e[\"message\"]; let k: str = e.kind; };"
+        )
+        .is_empty());
+        assert_eq!(
+            errors("try { throw 1; } catch (e) { let n: int = e.kind; };"),
+            ["1:43 type mismatch: expected int, got str"]
+        );
+        assert_eq!(
+            errors("let r = 1; is_ok(r);"),
+            ["1:18 type mismatch: expected result, got int"]
+        );
+        assert_eq!(
+            errors("let f = func() { 1?; };"),
+            ["1:19 type mismatch: expected result, got int"]
+        );
+    }
+
+    #[test]
+    fn test_check_match() {
+        // Arms of the same type give it to the `match`, the names they bind
+        // get the types of the parts they match.
+        assert_eq!(
+            errors("let a: [int] = [1, 2]; let s: str = match (a) { [x, y] => x + y, _ => 0 };"),
+            ["1:37 type mismatch: expected str, got int"]
+        );
+        assert_eq!(
+            errors("let n = 1; match (n) { \"one\" => 1, [x] => x, _ => 0 };"),
+            [
+                "1:12 str pattern never matches int",
+                "1:12 array pattern never matches int"
+            ]
+        );
+        // Destructured names get the types of the parts they take.
+        assert_eq!(
+            errors("let [a, ...r]: [int] = [1, 2]; let s: str = a; let t: [str] = r;"),
+            [
+                "1:45 type mismatch: expected str, got int",
+                "1:63 type mismatch: expected [str], got [int]"
+            ]
+        );
+        assert_eq!(
+            errors("let [a = \"x\"] = [1];"),
+            ["1:1 type mismatch: expected int, got str"]
+        );
+        // Values of unknown type can be matched against anything.
+        assert!(errors(
+            "let f = func(v) { match (v) { 0 => 1, n if n > 1 => n, [a] => 2, {\"k\": k} => 3, _ => 4 } }; \
+             f([1]) + f(\"a\");"
+        )
+        .is_empty());
+    }
+}
@@ -0,0 +1,589 @@
+use std::{
+    env, fs,
+    io::{self, Read},
+    path::Path,
+    process,
+    str::FromStr,
+    thread,
+    time::Duration,
+};
+
+use serde_json::json;
+
+use crate::{
+    checker,
+    evaluator::{Config, Evaluator},
+    formatter, lexer,
+    linter::{
+        self,
+        config::{self as lint_config, LintConfig},
+        Level,
+    },
+    lsp,
+    object::{environment::Environment, Object},
+    optimize::{self, OptimizeConfig},
+    parser::{self, error::ParseError},
+    repl::{self, ReplConfig},
+    resolver,
+};
+
+mod test;
+
+const USAGE: &str = "\
+usage: cake-interpreter [OPTIONS] [FILE]
+       cake-interpreter fmt [--check] [FILES...]
+       cake-interpreter check [FILES...]
+       cake-interpreter lint [--format human|json] [--config FILE] [FILES...]
+       cake-interpreter lsp
+
+commands:
+    check             check the names and types of FILES, or stdin, without
+                      running them
+    fmt               format FILES in place, or stdin to stdout
+                      --check: list files that are not formatted, change nothing
+    lint              check FILES, or stdin, for suspicious code
+                      --format: `human` (default) or `json`
+                      --config: project file with a `lint` table of rule
+                      levels (default: the closest cake.json)
+    lsp               run a language server over stdin and stdout
+
+syntax:
+    A `;` can be left out where a statement is complete and a line ends
+    after it, a `}` or the end of the file follows it, or it ends with a
+    block of its own, like `let f = func() { 1 }`.
+    A line break does not end an unfinished statement: `let a =` or
+    `f(1,` goes on on the next line.
+    A line that starts with a binary operator continues the expression
+    before it: `a` then `+ b` on the next line is `a + b`.
+    A line that starts with `(` or `[` begins a new statement: it is not a
+    call or an index of the line before.
+    A `return` is complete on its own: `return` alone on a line returns
+    null, and the value it returns must start on the same line.
+
+options:
+    --max-depth N     maximum depth of nested calls
+    --max-steps N     stop after evaluating N expressions
+    --timeout MS      stop after MS milliseconds
+    --max-memory BYTES
+                      stop once strings and arrays have taken BYTES
+    --max-string BYTES
+                      maximum length of a string
+    --max-array N     maximum length of an array
+    --no-io           do not allow importing the io module or files
+    --gc-threshold N  create at least N environments between two collections
+                      of unreachable cycles
+    --no-gc           only collect cycles when the program calls gc()
+    --path DIR        also look for imported files in DIR; can be repeated,
+                      and comes before the directories in CAKE_PATH
+    --dump-optimized  print FILE, or stdin, as it runs after optimization
+    --no-fold         do not evaluate operators on literals ahead of time
+    --no-dead-branches
+                      keep the branches of `if`s on constant conditions
+    --no-double-negation
+                      keep `!!x` as written
+    --prompt TEXT     REPL prompt
+    --history FILE    REPL history file (default: ~/.cake_history)
+    --no-history      do not load or save the REPL history";
+
+// Host stack reserved per level of Cake recursion. A call of a function
+// like `func(n) { 1 + f(n + 1) }` takes about 32 KiB in a debug build and
+// 8 KiB in a release one; deeper expressions take more, and are stopped by
+// the evaluator's stack guard before they run out of it.
+const STACK_PER_FRAME: usize = 64 * 1024;
+const MIN_STACK_SIZE: usize = 8 * 1024 * 1024;
+// Left to what runs between two checks of the guard, like parsing an
+// imported module.
+const STACK_HEADROOM: usize = 2 * 1024 * 1024;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub enum Command {
+    // Runs `Options::file`, or the REPL without one.
+    #[default]
+    Run,
+    Check {
+        files: Vec<String>,
+    },
+    Fmt {
+        check: bool,
+        files: Vec<String>,
+    },
+    Lint {
+        format: LintFormat,
+        config: Option<String>,
+        files: Vec<String>,
+    },
+    Lsp,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LintFormat {
+    #[default]
+    Human,
+    Json,
+}
+
+#[derive(Debug, Default)]
+pub struct Options {
+    pub command: Command,
+    pub config: Config,
+    pub optimize: OptimizeConfig,
+    // Prints the optimized program instead of running it.
+    pub dump_optimized: bool,
+    pub repl: ReplConfig,
+    pub file: Option<String>,
+}
+
+pub fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options::default();
+    if args.first().map(String::as_str) == Some("fmt") {
+        let mut check = false;
+        let mut files = Vec::new();
+        for arg in &args[1..] {
+            match arg.as_str() {
+                "--check" => check = true,
+                "-h" | "--help" => return Err(String::from(USAGE)),
+                _ if arg.starts_with('-') => return Err(format!("unknown option: {}", arg)),
+                _ => files.push(arg.clone()),
+            }
+        }
+        options.command = Command::Fmt { check, files };
+        return Ok(options);
+    }
+    if args.first().map(String::as_str) == Some("check") {
+        let mut files = Vec::new();
+        for arg in &args[1..] {
+            match arg.as_str() {
+                "-h" | "--help" => return Err(String::from(USAGE)),
+                _ if arg.starts_with('-') => return Err(format!("unknown option: {}", arg)),
+                _ => files.push(arg.clone()),
+            }
+        }
+        options.command = Command::Check { files };
+        return Ok(options);
+    }
+    if args.first().map(String::as_str) == Some("lint") {
+        let mut format = LintFormat::default();
+        let mut config = None;
+        let mut files = Vec::new();
+        let mut args = args[1..].iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--format" => {
+                    format = match args.next().map(String::as_str) {
+                        Some("human") => LintFormat::Human,
+                        Some("json") => LintFormat::Json,
+                        Some(value) => {
+                            return Err(format!("invalid value for --format: {}", value))
+                        }
+                        None => return Err(String::from("--format expects a value")),
+                    };
+                }
+                "--config" => {
+                    let file = args
+                        .next()
+                        .ok_or_else(|| String::from("--config expects a value"))?;
+                    config = Some(file.clone());
+                }
+                "-h" | "--help" => return Err(String::from(USAGE)),
+                _ if arg.starts_with('-') => return Err(format!("unknown option: {}", arg)),
+                _ => files.push(arg.clone()),
+            }
+        }
+        options.command = Command::Lint {
+            format,
+            config,
+            files,
+        };
+        return Ok(options);
+    }
+    if args.first().map(String::as_str) == Some("lsp") {
+        if let Some(arg) = args.get(1) {
+            return Err(format!("unexpected argument: {}", arg));
+        }
+        options.command = Command::Lsp;
+        return Ok(options);
+    }
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--max-depth" => options.config.max_depth = value(arg, args.next())?,
+            "--max-steps" => {
+                options.config.limits.max_steps = Some(value(arg, args.next())?);
+            }
+            "--timeout" => {
+                let millis = value(arg, args.next())?;
+                options.config.limits.timeout = Some(Duration::from_millis(millis));
+            }
+            "--max-memory" => {
+                options.config.limits.max_memory = Some(value(arg, args.next())?);
+            }
+            "--max-string" => {
+                options.config.limits.max_string_len = Some(value(arg, args.next())?);
+            }
+            "--max-array" => {
+                options.config.limits.max_array_len = Some(value(arg, args.next())?);
+            }
+            "--no-io" => options.config.allow_io = false,
+            "--gc-threshold" => options.config.gc.threshold = value(arg, args.next())?,
+            "--no-gc" => options.config.gc.automatic = false,
+            "--path" => {
+                let dir = args
+                    .next()
+                    .ok_or_else(|| String::from("--path expects a value"))?;
+                options.config.search_path.push(dir.into());
+            }
+            "--prompt" => {
+                options.repl.prompt = args
+                    .next()
+                    .ok_or_else(|| String::from("--prompt expects a value"))?
+                    .clone();
+            }
+            "--history" => {
+                let file = args
+                    .next()
+                    .ok_or_else(|| String::from("--history expects a value"))?;
+                options.repl.history_file = Some(file.into());
+            }
+            "--no-history" => options.repl.history_file = None,
+            "--dump-optimized" => options.dump_optimized = true,
+            "--no-fold" => options.optimize.fold = false,
+            "--no-dead-branches" => options.optimize.dead_branches = false,
+            "--no-double-negation" => options.optimize.double_negation = false,
+            "-h" | "--help" => return Err(String::from(USAGE)),
+            _ if arg.starts_with('-') => return Err(format!("unknown option: {}", arg)),
+            _ if options.file.is_none() => options.file = Some(arg.clone()),
+            _ => return Err(String::from(USAGE)),
+        }
+    }
+    Ok(options)
+}
+
+// Parses the value given to `flag`.
+fn value<T: FromStr>(flag: &str, value: Option<&String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{} expects a value", flag))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {}: {}", flag, value))
+}
+
+pub fn run(args: &[String]) -> i32 {
+    let mut options = match parse_args(args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}", err);
+            return 2;
+        }
+    };
+
+    match &options.command {
+        Command::Check { files } => return run_check(files),
+        Command::Fmt { check, files } => return run_fmt(*check, files),
+        Command::Lint {
+            format,
+            config,
+            files,
+        } => return run_lint(*format, config.as_deref(), files),
+        Command::Lsp => return lsp::serve(io::stdin().lock(), io::stdout().lock()),
+        Command::Run => {}
+    }
+    if let Some(paths) = env::var_os("CAKE_PATH") {
+        options.config.search_path.extend(env::split_paths(&paths));
+    }
+    if options.dump_optimized {
+        return dump_optimized(options.file.as_deref(), &options.optimize);
+    }
+
+    let stack_size = reserve_stack(&mut options.config);
+    let handle = thread::Builder::new()
+        .stack_size(stack_size)
+        .spawn(move || match options.file {
+            Some(file) => run_file(&file, options.config, &options.optimize),
+            None => {
+                repl::repl(options.config, options.repl);
+                0
+            }
+        });
+    match handle.map(|h| h.join()) {
+        Ok(Ok(code)) => code,
+        _ => {
+            eprintln!("interpreter thread failed");
+            process::abort()
+        }
+    }
+}
+
+// The stack to run the evaluator with, which `config` is told to stay in.
+fn reserve_stack(config: &mut Config) -> usize {
+    let stack_size = MIN_STACK_SIZE.max(config.max_depth.saturating_mul(STACK_PER_FRAME));
+    config.max_stack = stack_size - STACK_HEADROOM;
+    stack_size
+}
+
+fn run_file(file: &str, config: Config, optimize: &OptimizeConfig) -> i32 {
+    match fs::read_to_string(file) {
+        Ok(source) => run_source(file, &source, config, optimize),
+        Err(err) => {
+            eprintln!("{}: {}", file, err);
+            1
+        }
+    }
+}
+
+fn run_source(file: &str, source: &str, config: Config, optimize: &OptimizeConfig) -> i32 {
+    let mut parser = parser::Parser::new(lexer::Lexer::new(source));
+    let program = parser.parse();
+    if !parser.errors().is_empty() {
+        report_parse_errors(file, parser.errors());
+        return 1;
+    }
+    let program = optimize::optimize(program, optimize);
+    let env = Environment::new();
+    match Evaluator::with_config(config).eval_file(&program, &env, Path::new(file)) {
+        Object::Error(err) => {
+            eprintln!("{}", err);
+            1
+        }
+        _ => 0,
+    }
+}
+
+// Prints `file`, or stdin without one, formatted after the `optimize` passes.
+fn dump_optimized(file: Option<&str>, optimize: &OptimizeConfig) -> i32 {
+    let (name, source) = match file {
+        Some(file) => (file, fs::read_to_string(file)),
+        None => {
+            let mut source = String::new();
+            let read = io::stdin().read_to_string(&mut source).map(|_| source);
+            ("<stdin>", read)
+        }
+    };
+    let source = match source {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("{}: {}", name, err);
+            return 1;
+        }
+    };
+    let mut parser = parser::Parser::new(lexer::Lexer::new(&source));
+    let program = parser.parse();
+    if !parser.errors().is_empty() {
+        report_parse_errors(name, parser.errors());
+        return 1;
+    }
+    print!(
+        "{}",
+        formatter::format(&optimize::optimize(program, optimize))
+    );
+    0
+}
+
+// Formats `files` in place, or stdin to stdout when there are none. With
+// `check` nothing is written, and the files that would change are listed.
+fn run_fmt(check: bool, files: &[String]) -> i32 {
+    if files.is_empty() {
+        let mut source = String::new();
+        if let Err(err) = io::stdin().read_to_string(&mut source) {
+            eprintln!("<stdin>: {}", err);
+            return 1;
+        }
+        return match format_source(&source) {
+            Ok(formatted) if check => {
+                if formatted == source {
+                    0
+                } else {
+                    println!("<stdin>");
+                    1
+                }
+            }
+            Ok(formatted) => {
+                print!("{}", formatted);
+                0
+            }
+            Err(errors) => {
+                report_parse_errors("<stdin>", &errors);
+                1
+            }
+        };
+    }
+
+    let mut code = 0;
+    for file in files {
+        let source = match fs::read_to_string(file) {
+            Ok(source) => source,
+            Err(err) => {
+                eprintln!("{}: {}", file, err);
+                code = 1;
+                continue;
+            }
+        };
+        let formatted = match format_source(&source) {
+            Ok(formatted) => formatted,
+            Err(errors) => {
+                report_parse_errors(file, &errors);
+                code = 1;
+                continue;
+            }
+        };
+        if formatted == source {
+            continue;
+        }
+        if check {
+            println!("{}", file);
+            code = 1;
+        } else if let Err(err) = fs::write(file, formatted) {
+            eprintln!("{}: {}", file, err);
+            code = 1;
+        }
+    }
+    code
+}
+
+// Checks the names and types of `files`, or stdin when there are none. Fails
+// when a file cannot be read or parsed, uses an undefined name or has a type
+// error. The other findings of the resolver are left to `lint`.
+fn run_check(files: &[String]) -> i32 {
+    let mut sources = Vec::new();
+    let mut code = 0;
+    if files.is_empty() {
+        let mut source = String::new();
+        match io::stdin().read_to_string(&mut source) {
+            Ok(_) => sources.push((String::from("<stdin>"), source)),
+            Err(err) => {
+                eprintln!("<stdin>: {}", err);
+                return 1;
+            }
+        }
+    }
+    for file in files {
+        match fs::read_to_string(file) {
+            Ok(source) => sources.push((file.clone(), source)),
+            Err(err) => {
+                eprintln!("{}: {}", file, err);
+                code = 1;
+            }
+        }
+    }
+
+    for (file, source) in &sources {
+        let mut parser = parser::Parser::new(lexer::Lexer::new(source));
+        let program = parser.parse();
+        if !parser.errors().is_empty() {
+            report_parse_errors(file, parser.errors());
+            code = 1;
+            continue;
+        }
+        let resolution = resolver::resolve(&program);
+        for diagnostic in resolution.diagnostics.iter().filter(|d| d.is_error()) {
+            println!("{}:{}: error: {}", file, diagnostic.span, diagnostic);
+            code = 1;
+        }
+        for err in checker::check(&program) {
+            println!("{}:{}: type error: {}", file, err.span, err);
+            code = 1;
+        }
+    }
+    code
+}
+
+// Lints `files`, or stdin when there are none. Fails when a file cannot be
+// read or parsed, or when a rule set to `error` fires.
+fn run_lint(format: LintFormat, config: Option<&str>, files: &[String]) -> i32 {
+    let config = match config {
+        Some(path) => LintConfig::load(Path::new(path)),
+        None => env::current_dir()
+            .ok()
+            .and_then(|dir| lint_config::find_project(&dir))
+            .map_or_else(|| Ok(LintConfig::default()), |path| LintConfig::load(&path)),
+    };
+    let config = match config {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            return 2;
+        }
+    };
+
+    let mut sources = Vec::new();
+    let mut code = 0;
+    if files.is_empty() {
+        let mut source = String::new();
+        match io::stdin().read_to_string(&mut source) {
+            Ok(_) => sources.push((String::from("<stdin>"), source)),
+            Err(err) => {
+                eprintln!("<stdin>: {}", err);
+                return 1;
+            }
+        }
+    }
+    for file in files {
+        match fs::read_to_string(file) {
+            Ok(source) => sources.push((file.clone(), source)),
+            Err(err) => {
+                eprintln!("{}: {}", file, err);
+                code = 1;
+            }
+        }
+    }
+
+    let mut findings = Vec::new();
+    for (file, source) in &sources {
+        let mut parser = parser::Parser::new(lexer::Lexer::new(source));
+        let program = parser.parse();
+        if !parser.errors().is_empty() {
+            code = 1;
+            match format {
+                LintFormat::Human => report_parse_errors(file, parser.errors()),
+                LintFormat::Json => findings.extend(parser.errors().iter().map(|err| {
+                    json!({
+                        "file": file,
+                        "line": err.span.line,
+                        "column": err.span.column,
+                        "severity": "error",
+                        "rule": null,
+                        "message": err.message,
+                    })
+                })),
+            }
+            continue;
+        }
+        for lint in linter::lint(&program, &config) {
+            if lint.level == Level::Error {
+                code = 1;
+            }
+            match format {
+                LintFormat::Human => {
+                    println!("{}:{}: {}: {}", file, lint.span, lint.level.name(), lint)
+                }
+                LintFormat::Json => findings.push(json!({
+                    "file": file,
+                    "line": lint.span.line,
+                    "column": lint.span.column,
+                    "severity": lint.level.name(),
+                    "rule": lint.rule.name(),
+                    "message": lint.message,
+                })),
+            }
+        }
+    }
+    if format == LintFormat::Json {
+        println!("{}", serde_json::Value::Array(findings));
+    }
+    code
+}
+
+// Source that fails to parse is left alone: printing a partial tree would
+// drop code.
+pub fn format_source(source: &str) -> Result<String, Vec<ParseError>> {
+    let mut parser = parser::Parser::new(lexer::Lexer::new(source));
+    let program = parser.parse();
+    if !parser.errors().is_empty() {
+        return Err(parser.errors().to_vec());
+    }
+    Ok(formatter::format(&program))
+}
+
+fn report_parse_errors(file: &str, errors: &[ParseError]) {
+    for err in errors {
+        eprintln!("{}:{}: parse error: {}", file, err.span, err);
+    }
+}
@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use crate::{
+        cli::{parse_args, reserve_stack},
+        evaluator::Evaluator,
+        lexer::Lexer,
+        object::{environment::Environment, error::ErrorKind, Object},
+        parser::Parser,
+    };
+
+    #[test]
+    fn test_parse_max_depth() {
+        let args = |args: &[&str]| {
+            let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+            parse_args(&args).map(|options| options.config.max_depth)
+        };
+        assert_eq!(args(&["--max-depth", "20"]), Ok(20));
+        assert_eq!(
+            args(&["--max-depth", "deep"]),
+            Err(String::from("invalid value for --max-depth: deep"))
+        );
+        assert_eq!(
+            args(&["--max-depth"]),
+            Err(String::from("--max-depth expects a value"))
+        );
+    }
+
+    // Deep recursion stops with an error on the stack the CLI runs
+    // programs with, however much each call nests.
+    #[test]
+    fn test_stack_size() {
+        let nested = format!("{}f(n + 1){}", "1 + (".repeat(100), ")".repeat(100));
+        let cases = [
+            (
+                String::from("let f = func(n) { 1 + f(n + 1) }; f(0);"),
+                "maximum call depth of 1000 exceeded",
+            ),
+            (
+                format!("let f = func(n) {{ {} }}; f(0);", nested),
+                "expressions nested too deeply for the stack of",
+            ),
+        ];
+        for (input, message) in cases {
+            let mut config = parse_args(&[]).unwrap().config;
+            let stack_size = reserve_stack(&mut config);
+            let result = thread::Builder::new()
+                .stack_size(stack_size)
+                .spawn(move || {
+                    let program = Parser::new(Lexer::new(&input)).parse();
+                    match Evaluator::with_config(config).eval(&program, &Environment::new()) {
+                        Object::Error(err) => Some((err.kind, err.message)),
+                        _ => None,
+                    }
+                })
+                .unwrap()
+                .join()
+                .unwrap();
+            let (kind, found) = result.unwrap();
+            assert_eq!(kind, ErrorKind::StackOverflow);
+            assert!(found.starts_with(message), "{}", found);
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use crate::object::{
+    error::{ErrorKind, RuntimeError},
+    Builtin, BuiltinFn, Object,
+};
+
+use super::Evaluator;
+
+const BUILTINS: &[(&str, BuiltinFn)] = &[
+    ("ok", ok),
+    ("err", err),
+    ("is_ok", is_ok),
+    ("is_err", is_err),
+    ("unwrap", unwrap),
+    ("unwrap_err", unwrap_err),
+    ("unwrap_or", unwrap_or),
+    ("gc", gc),
+];
+
+pub fn lookup(name: &str) -> Option<Object> {
+    BUILTINS
+        .iter()
+        .find(|(builtin, _)| *builtin == name)
+        .map(|(name, func)| Object::Builtin(Builtin { name, func: *func }))
+}
+
+pub fn names() -> impl Iterator<Item = &'static str> {
+    BUILTINS.iter().map(|(name, _)| *name)
+}
+
+pub(crate) fn error(kind: ErrorKind, message: String) -> Object {
+    Object::Error(RuntimeError::new(kind, message))
+}
+
+pub(crate) fn check_arity(name: &str, args: &[Object], expected: usize) -> Result<(), Object> {
+    if args.len() != expected {
+        return Err(error(
+            ErrorKind::WrongArity,
+            format!(
+                "wrong number of arguments to `{}`: expected {}, got {}",
+                name,
+                expected,
+                args.len()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn not_a_result(name: &str, value: &Object) -> Object {
+    error(
+        ErrorKind::TypeMismatch,
+        format!(
+            "argument to `{}` must be RESULT, got {}",
+            name,
+            value.type_name()
+        ),
+    )
+}
+
+fn ok(_: &mut Evaluator, mut args: Vec<Object>) -> Object {
+    if let Err(err) = check_arity("ok", &args, 1) {
+        return err;
+    }
+    Object::Ok(Box::new(args.remove(0)))
+}
+
+fn err(_: &mut Evaluator, mut args: Vec<Object>) -> Object {
+    if let Err(err) = check_arity("err", &args, 1) {
+        return err;
+    }
+    Object::Err(Box::new(args.remove(0)))
+}
+
+fn is_ok(_: &mut Evaluator, args: Vec<Object>) -> Object {
+    if let Err(err) = check_arity("is_ok", &args, 1) {
+        return err;
+    }
+    match &args[0] {
+        Object::Ok(_) => Object::Bool(true),
+        Object::Err(_) => Object::Bool(false),
+        other => not_a_result("is_ok", other),
+    }
+}
+
+fn is_err(_: &mut Evaluator, args: Vec<Object>) -> Object {
+    if let Err(err) = check_arity("is_err", &args, 1) {
+        return err;
+    }
+    match &args[0] {
+        Object::Ok(_) => Object::Bool(false),
+        Object::Err(_) => Object::Bool(true),
+        other => not_a_result("is_err", other),
+    }
+}
+
+fn unwrap(_: &mut Evaluator, mut args: Vec<Object>) -> Object {
+    if let Err(err) = check_arity("unwrap", &args, 1) {
+        return err;
+    }
+    match args.remove(0) {
+        Object::Ok(value) => *value,
+        Object::Err(value) => error(
+            ErrorKind::Thrown,
+            format!("called `unwrap` on err({})", value.repr()),
+        ),
+        other => not_a_result("unwrap", &other),
+    }
+}
+
+fn unwrap_err(_: &mut Evaluator, mut args: Vec<Object>) -> Object {
+    if let Err(err) = check_arity("unwrap_err", &args, 1) {
+        return err;
+    }
+    match args.remove(0) {
+        Object::Err(value) => *value,
+        Object::Ok(value) => error(
+            ErrorKind::Thrown,
+            format!("called `unwrap_err` on ok({})", value.repr()),
+        ),
+        other => not_a_result("unwrap_err", &other),
+    }
+}
+
+fn unwrap_or(_: &mut Evaluator, mut args: Vec<Object>) -> Object {
+    if let Err(err) = check_arity("unwrap_or", &args, 2) {
+        return err;
+    }
+    let default = args.pop().unwrap_or(Object::Null);
+    match args.remove(0) {
+        Object::Ok(value) => *value,
+        Object::Err(_) => default,
+        other => not_a_result("unwrap_or", &other),
+    }
+}
+
+// Collects the cycles that are no longer reachable, and returns the number of
+// environments freed.
+fn gc(evaluator: &mut Evaluator, args: Vec<Object>) -> Object {
+    if let Err(err) = check_arity("gc", &args, 0) {
+        return err;
+    }
+    Object::Int(evaluator.collect_garbage() as i64)
+}
@@ -0,0 +1,207 @@
+use std::{
+    mem,
+    time::{Duration, Instant},
+};
+
+use crate::object::{error::ErrorKind, HashKey, Object};
+
+use super::{error, Evaluator};
+
+// The timeout is only checked every so many steps, reading the clock is slow
+// next to a step.
+const CLOCK_INTERVAL: u64 = 1024;
+
+// Bounds on the resources one run of a program can use, for running code
+// that is not trusted. `None` is no limit. A run is a call to `eval`,
+// including the modules it imports.
+#[derive(Debug, Clone, Default)]
+pub struct Limits {
+    // Expressions evaluated.
+    pub max_steps: Option<u64>,
+    pub timeout: Option<Duration>,
+    // Bytes of strings, arrays and hashes created. Nothing is given back when
+    // a value is dropped, so this bounds the memory they use with room to
+    // spare.
+    pub max_memory: Option<usize>,
+    // In bytes.
+    pub max_string_len: Option<usize>,
+    pub max_array_len: Option<usize>,
+}
+
+// The limit that stopped a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Steps,
+    Timeout,
+    Memory,
+    StringLen,
+    ArrayLen,
+}
+
+// What the current or last run used.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Usage {
+    pub steps: u64,
+    // Bytes, as counted by `Limits::max_memory`.
+    pub memory: usize,
+    pub elapsed: Duration,
+    pub exceeded: Option<Limit>,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct Meter {
+    usage: Usage,
+    // Set while a run is in progress.
+    started: Option<Instant>,
+    // Where the host stack was when the run started.
+    stack_base: usize,
+}
+
+impl Evaluator {
+    // What the last run used, or the current one so far. A limit error
+    // returned by `eval` always comes with `exceeded` set.
+    pub fn usage(&self) -> Usage {
+        let mut usage = self.meter.usage.clone();
+        if let Some(started) = self.meter.started {
+            usage.elapsed = started.elapsed();
+        }
+        usage
+    }
+
+    pub fn exceeded(&self) -> Option<Limit> {
+        self.meter.usage.exceeded
+    }
+
+    // Starts metering a run, unless one is already in progress: returns
+    // whether it did, i.e. whether the caller should `stop` it.
+    pub(super) fn start(&mut self) -> bool {
+        if self.meter.started.is_some() {
+            return false;
+        }
+        self.meter = Meter {
+            usage: Usage::default(),
+            started: Some(Instant::now()),
+            stack_base: stack_address(),
+        };
+        true
+    }
+
+    pub(super) fn stop(&mut self) {
+        if let Some(started) = self.meter.started.take() {
+            self.meter.usage.elapsed = started.elapsed();
+        }
+    }
+
+    // Counts an expression about to be evaluated.
+    pub(super) fn step(&mut self) -> Result<(), Object> {
+        // Calls are bounded by `max_depth`, but the expressions nested in
+        // each of them take host stack too.
+        if self.meter.started.is_some()
+            && self.meter.stack_base.abs_diff(stack_address()) > self.config.max_stack
+        {
+            return Err(error(
+                ErrorKind::StackOverflow,
+                format!(
+                    "expressions nested too deeply for the stack of {} bytes",
+                    self.config.max_stack
+                ),
+            ));
+        }
+        self.meter.usage.steps += 1;
+        let steps = self.meter.usage.steps;
+        if let Some(max) = self.config.limits.max_steps {
+            if steps > max {
+                return Err(self.exceed(Limit::Steps, format!("step budget of {} exceeded", max)));
+            }
+        }
+        if let (Some(timeout), Some(started)) = (self.config.limits.timeout, self.meter.started) {
+            if steps.is_multiple_of(CLOCK_INTERVAL) && started.elapsed() > timeout {
+                return Err(
+                    self.exceed(Limit::Timeout, format!("timeout of {:?} exceeded", timeout))
+                );
+            }
+        }
+        Ok(())
+    }
+
+    // Checks a value that was just created against the size limits and
+    // counts it against the memory cap. The elements of a new array are
+    // values that were counted when they were created, or parts of the
+    // builtin arguments they come from.
+    pub(super) fn track(&mut self, value: Object) -> Object {
+        let checked = match &value {
+            Object::String(s) => self.check_string(s.len()),
+            Object::Array(elements) => self.check_array(elements.len()),
+            Object::Hash(entries) => self.check_array(entries.len()),
+            _ => return value,
+        };
+        let size = size(&value);
+        let res = checked.and_then(|_| self.check_memory(size));
+        self.meter.usage.memory = self.meter.usage.memory.saturating_add(size);
+        res.err().unwrap_or(value)
+    }
+
+    // For builtins about to create an array of `len` elements: fails before
+    // it is allocated when it would be too large.
+    pub(crate) fn reserve(&mut self, len: usize) -> Result<(), Object> {
+        self.check_array(len)?;
+        self.check_memory(len.saturating_mul(mem::size_of::<Object>()))
+    }
+
+    // For builtins about to create a string of `len` bytes, the same way.
+    pub(crate) fn reserve_string(&mut self, len: usize) -> Result<(), Object> {
+        self.check_string(len)?;
+        self.check_memory(len)
+    }
+
+    fn check_string(&mut self, len: usize) -> Result<(), Object> {
+        match self.config.limits.max_string_len {
+            Some(max) if len > max => Err(self.exceed(
+                Limit::StringLen,
+                format!("string of {} bytes exceeds the maximum of {}", len, max),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_array(&mut self, len: usize) -> Result<(), Object> {
+        match self.config.limits.max_array_len {
+            Some(max) if len > max => Err(self.exceed(
+                Limit::ArrayLen,
+                format!("array of {} elements exceeds the maximum of {}", len, max),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_memory(&mut self, size: usize) -> Result<(), Object> {
+        match self.config.limits.max_memory {
+            Some(max) if self.meter.usage.memory.saturating_add(size) > max => Err(self.exceed(
+                Limit::Memory,
+                format!("memory cap of {} bytes exceeded", max),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn exceed(&mut self, limit: Limit, message: String) -> Object {
+        self.meter.usage.exceeded = Some(limit);
+        error(ErrorKind::LimitExceeded, message)
+    }
+}
+
+// Roughly where the top of the host stack is.
+#[inline(never)]
+fn stack_address() -> usize {
+    let marker = 0u8;
+    std::hint::black_box(&marker) as *const u8 as usize
+}
+
+fn size(value: &Object) -> usize {
+    match value {
+        Object::String(s) => s.len(),
+        Object::Array(elements) => elements.len() * mem::size_of::<Object>(),
+        Object::Hash(entries) => entries.len() * mem::size_of::<(HashKey, Object)>(),
+        _ => 0,
+    }
+}
@@ -0,0 +1,755 @@
+use std::{collections::BTreeMap, path::PathBuf, rc::Rc};
+
+use crate::{
+    ast::{
+        ident::Ident,
+        if_else::IfExpression,
+        interpolated::Fragment,
+        operators::{InfixOperator, PrefixOperator},
+        pattern::Pattern,
+        try_catch::Try,
+        Expr, Program, Statement,
+    },
+    object::{
+        environment::{Env, Environment},
+        error::{ErrorKind, Frame, RuntimeError},
+        gc::{GcConfig, GcStats, Heap},
+        Function, HashKey, Object,
+    },
+    resolver::{self, Slot, Slots},
+    token::Span,
+};
+
+pub mod builtins;
+mod limits;
+mod modules;
+mod patterns;
+mod test;
+
+pub use limits::{Limit, Limits, Usage};
+
+pub const DEFAULT_MAX_DEPTH: usize = 1000;
+// Fits in the 2 MiB that Rust gives threads other than the main one.
+pub const DEFAULT_MAX_STACK: usize = 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    // Maximum number of nested Cake calls. Tail calls reuse their frame and
+    // do not count. Every level costs a few KB of host stack, so large
+    // limits need the evaluator to run on a thread with a big enough stack.
+    pub max_depth: usize,
+    // Bytes of host stack a run can use before it stops with a stack
+    // overflow, which must leave some of the thread's stack to spare.
+    pub max_stack: usize,
+    // Directories `import` looks in, after that of the importing module.
+    // Paths found nowhere are read relative to the working directory.
+    pub search_path: Vec<PathBuf>,
+    pub limits: Limits,
+    // Whether the `io` module and files can be imported.
+    pub allow_io: bool,
+    pub gc: GcConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_stack: DEFAULT_MAX_STACK,
+            search_path: Vec::new(),
+            limits: Limits::default(),
+            allow_io: true,
+            gc: GcConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Evaluator {
+    config: Config,
+    // Cake functions currently being evaluated, outermost first. A `return`
+    // can only be turned into a tail call when there is a frame to reuse.
+    frames: Vec<Frame>,
+    // Number of enclosing `try` statements in the current frame. Calls made
+    // inside them must return here for their errors to be caught, so they
+    // are never turned into tail calls.
+    try_depth: usize,
+    modules: modules::Loader,
+    // Where the builtin being run was called from, for the functions it
+    // calls back.
+    call_site: Span,
+    meter: limits::Meter,
+    heap: Heap,
+    // Where the locals of the code being run live: the resolution of the
+    // program it comes from.
+    slots: Rc<Slots>,
+}
+
+fn error(kind: ErrorKind, message: String) -> Object {
+    Object::Error(RuntimeError::new(kind, message))
+}
+
+impl Evaluator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_config(config: Config) -> Self {
+        Self {
+            config,
+            frames: Vec::new(),
+            try_depth: 0,
+            modules: modules::Loader::default(),
+            call_site: Span::default(),
+            meter: limits::Meter::default(),
+            heap: Heap::default(),
+            slots: Rc::default(),
+        }
+    }
+
+    // Attaches the span and the current call stack to an error that has not
+    // been located yet; errors coming from deeper expressions keep theirs.
+    fn locate(&self, mut obj: Object, span: Span) -> Object {
+        if let Object::Error(err) = &mut obj {
+            if err.span.is_none() {
+                err.locate(span, &self.frames);
+            }
+        }
+        obj
+    }
+
+    // Frees the environments, and what they hold, that are only kept alive
+    // by cycles. Returns the number of environments freed.
+    pub fn collect_garbage(&mut self) -> usize {
+        self.heap.collect()
+    }
+
+    pub fn gc_stats(&self) -> GcStats {
+        self.heap.stats()
+    }
+
+    pub fn eval(&mut self, program: &Program, env: &Env) -> Object {
+        self.heap.track_root(env);
+        // Modules are evaluated as part of the run that imports them.
+        let run = self.start();
+        let result = self.eval_program(program, env);
+        if run {
+            self.stop();
+        }
+        result
+    }
+
+    // Resolves `program` once, then runs it with its slots.
+    fn eval_program(&mut self, program: &Program, env: &Env) -> Object {
+        let slots = Rc::new(resolver::resolve(program).slots);
+        let outer = std::mem::replace(&mut self.slots, slots);
+        let mut result = Object::Null;
+        for statement in code(&program.statements) {
+            result = self.eval_statement(statement, env, false);
+            match result {
+                Object::Return(value) => {
+                    result = *value;
+                    break;
+                }
+                Object::Error(_) => break,
+                _ => {}
+            }
+        }
+        self.slots = outer;
+        result
+    }
+
+    // `tail` is true when the statement's value is the value of the enclosing
+    // function call, i.e. nothing is left to do in the current frame after it.
+    fn eval_statement(&mut self, statement: &Statement, env: &Env, tail: bool) -> Object {
+        match statement {
+            Statement::Let(let_stat) => {
+                let value = match (let_stat.ident(), &let_stat.expr) {
+                    (Some(ident), Expr::Function(params, _, body)) => {
+                        Object::Function(Rc::new(Function {
+                            name: Some(ident.name.clone()),
+                            params: params.clone(),
+                            body: *body.clone(),
+                            env: env.clone(),
+                            slots: self.slots.clone(),
+                        }))
+                    }
+                    (_, expr) => self.eval_expression(expr, env),
+                };
+                if value.is_abrupt() {
+                    return value;
+                }
+                match &let_stat.pattern {
+                    Pattern::Binding(ident) => {
+                        self.bind(env, ident, value);
+                        Object::Null
+                    }
+                    pattern => self.destructure(pattern, value, let_stat.span, env),
+                }
+            }
+            Statement::Return(return_stat) => {
+                let value = match &return_stat.return_expr {
+                    None => Object::Null,
+                    Some(expr) if !self.frames.is_empty() && self.try_depth == 0 => {
+                        self.eval_tail_expression(expr, env)
+                    }
+                    Some(expr) => self.eval_expression(expr, env),
+                };
+                match value {
+                    Object::Error(_) | Object::Return(_) | Object::TailCall(_, _, _) => value,
+                    value => Object::Return(Box::new(value)),
+                }
+            }
+            Statement::Expression(expr) => {
+                if tail {
+                    self.eval_tail_expression(expr, env)
+                } else {
+                    self.eval_expression(expr, env)
+                }
+            }
+            Statement::Illegal(s) => error(ErrorKind::Illegal, format!("illegal statement: {}", s)),
+            Statement::BlockStatement(block) => self.eval_block(block, env, tail),
+            Statement::Throw(throw_stat) => match self.eval_expression(&throw_stat.expr, env) {
+                Object::ErrorValue(err) => Object::Error((*err).clone()),
+                value if value.is_abrupt() => value,
+                value => self.locate(error(ErrorKind::Thrown, value.to_string()), throw_stat.span),
+            },
+            Statement::Try(try_stat) => self.eval_try(try_stat, env, tail),
+            Statement::Import(import) => match self.import(&import.path) {
+                Ok(module) => {
+                    self.bind(env, &import.alias, Object::Module(module));
+                    Object::Null
+                }
+                Err(err) => self.locate(err, import.span),
+            },
+            Statement::Comment(_) => Object::Null,
+        }
+    }
+
+    fn eval_try(&mut self, try_stat: &Try, env: &Env, tail: bool) -> Object {
+        self.try_depth += 1;
+        let mut result = self.eval_statement(&try_stat.body, env, false);
+        self.try_depth -= 1;
+        // Limits stop the run: neither `catch` nor `finally` get to run.
+        if is_limit(&result) {
+            return result;
+        }
+
+        if let (Object::Error(err), Some(catch)) = (&result, &try_stat.catch) {
+            // The error is bound for the handler only.
+            let handler = Environment::new_enclosed(env.clone());
+            self.heap.track(&handler, &self.config.gc);
+            let err = Object::ErrorValue(Rc::new(err.clone()));
+            self.bind(&handler, &catch.ident, err);
+            // The `finally` block still has to run after the handler.
+            let guarded = try_stat.finally.is_some();
+            if guarded {
+                self.try_depth += 1;
+            }
+            result = self.eval_statement(&catch.body, &handler, tail && !guarded);
+            if guarded {
+                self.try_depth -= 1;
+            }
+            if is_limit(&result) {
+                return result;
+            }
+        }
+
+        if let Some(finally) = &try_stat.finally {
+            let finally = self.eval_statement(finally, env, false);
+            if let Object::Return(_) | Object::Error(_) | Object::TailCall(_, _, _) = finally {
+                return finally;
+            }
+        }
+        result
+    }
+
+    fn eval_block(&mut self, block: &[Statement], env: &Env, tail: bool) -> Object {
+        let block: Vec<&Statement> = code(block).collect();
+        let mut result = Object::Null;
+        for (i, statement) in block.iter().enumerate() {
+            let is_last = i + 1 == block.len();
+            result = self.eval_statement(statement, env, tail && is_last);
+            if let Object::Return(_) | Object::Error(_) | Object::TailCall(_, _, _) = result {
+                return result;
+            }
+        }
+        result
+    }
+
+    fn eval_if(&mut self, if_expr: &IfExpression, env: &Env, tail: bool) -> Object {
+        let condition = self.eval_expression(&if_expr.condition, env);
+        if condition.is_abrupt() {
+            return condition;
+        }
+        if condition.is_truthy() {
+            self.eval_statement(&if_expr.then, env, tail)
+        } else if let Some(else_) = &if_expr.else_ {
+            self.eval_statement(else_, env, tail)
+        } else {
+            Object::Null
+        }
+    }
+
+    // Evaluates an expression in tail position: a call is not performed but
+    // returned as an `Object::TailCall` for `apply_function` to loop on.
+    fn eval_tail_expression(&mut self, expr: &Expr, env: &Env) -> Object {
+        match expr {
+            Expr::If(if_expr) => return self.eval_if(if_expr, env, true),
+            Expr::Match(subject, arms, span) => {
+                return self.eval_match(subject, arms, *span, env, true)
+            }
+            _ => {}
+        }
+        if let Expr::Call(func, args, span) = expr {
+            let func = self.eval_expression(func, env);
+            if func.is_abrupt() {
+                return func;
+            }
+            let args = match self.eval_expressions(args, env) {
+                Ok(args) => args,
+                Err(err) => return err,
+            };
+            return match func {
+                Object::Function(func) => Object::TailCall(func, args, *span),
+                other => self.apply_function(other, args, *span),
+            };
+        }
+        self.eval_expression(expr, env)
+    }
+
+    pub fn eval_expression(&mut self, expr: &Expr, env: &Env) -> Object {
+        if let Err(err) = self.step() {
+            return match expr.span() {
+                Some(span) => self.locate(err, span),
+                None => err,
+            };
+        }
+        match expr {
+            Expr::Ident(ident) => {
+                let value = match self.slot(ident) {
+                    Some(slot) => env.borrow().get_slot(slot),
+                    None => env
+                        .borrow()
+                        .get(&ident.name)
+                        .or_else(|| builtins::lookup(&ident.name)),
+                };
+                match value {
+                    Some(value) => value,
+                    None => self.locate(
+                        error(
+                            ErrorKind::UnknownIdentifier,
+                            format!("identifier not found: {}", ident.name),
+                        ),
+                        ident.span,
+                    ),
+                }
+            }
+            Expr::Int(i) => Object::Int(*i),
+            Expr::Bool(b) => Object::Bool(*b),
+            Expr::String(s) => self.track(Object::String(s.clone())),
+            Expr::Array(elements) => match self.eval_expressions(elements, env) {
+                Ok(elements) => self.track(Object::Array(Rc::new(elements))),
+                Err(err) => err,
+            },
+            Expr::Index(left, index, span) => {
+                let left = self.eval_expression(left, env);
+                if left.is_abrupt() {
+                    return left;
+                }
+                let index = self.eval_expression(index, env);
+                if index.is_abrupt() {
+                    return index;
+                }
+                self.locate(eval_index(left, index), *span)
+            }
+            Expr::Illegal(s) => error(ErrorKind::Illegal, format!("illegal expression: {}", s)),
+            Expr::Prefix(op, right, span) => {
+                let right = self.eval_expression(right, env);
+                if right.is_abrupt() {
+                    return right;
+                }
+                self.locate(eval_prefix(op, right), *span)
+            }
+            Expr::Infix(left, op, right, span) => {
+                let left = self.eval_expression(left, env);
+                if left.is_abrupt() {
+                    return left;
+                }
+                let right = self.eval_expression(right, env);
+                if right.is_abrupt() {
+                    return right;
+                }
+                let value = self.track(eval_infix(op, left, right));
+                self.locate(value, *span)
+            }
+            Expr::Function(params, _, body) => Object::Function(Rc::new(Function {
+                name: None,
+                params: params.clone(),
+                body: *body.clone(),
+                env: env.clone(),
+                slots: self.slots.clone(),
+            })),
+            Expr::Propagate(expr, span) => match self.eval_expression(expr, env) {
+                Object::Ok(value) => *value,
+                Object::Err(value) => Object::Return(Box::new(Object::Err(value))),
+                value if value.is_abrupt() => value,
+                value => self.locate(
+                    error(
+                        ErrorKind::TypeMismatch,
+                        format!("`?` expects a RESULT, got {}", value.type_name()),
+                    ),
+                    *span,
+                ),
+            },
+            Expr::Call(func, args, span) => {
+                let func = self.eval_expression(func, env);
+                if func.is_abrupt() {
+                    return func;
+                }
+                let args = match self.eval_expressions(args, env) {
+                    Ok(args) => args,
+                    Err(err) => return err,
+                };
+                self.apply_function(func, args, *span)
+            }
+            Expr::Member(expr, name, span) => {
+                let value = self.eval_expression(expr, env);
+                if value.is_abrupt() {
+                    return value;
+                }
+                self.locate(eval_member(value, &name.name), *span)
+            }
+            Expr::Interpolated(fragments, span) => {
+                let mut res = String::new();
+                for fragment in fragments {
+                    match fragment {
+                        Fragment::Text(text) => res.push_str(text),
+                        Fragment::Expr(expr) => {
+                            let value = self.eval_expression(expr, env);
+                            if value.is_abrupt() {
+                                return value;
+                            }
+                            let value = value.to_string();
+                            if let Err(err) = self.reserve_string(res.len() + value.len()) {
+                                return self.locate(err, *span);
+                            }
+                            res.push_str(&value);
+                        }
+                    }
+                }
+                let value = self.track(Object::String(res));
+                self.locate(value, *span)
+            }
+            Expr::Hash(entries, span) => self.eval_hash(entries, *span, env),
+            Expr::If(if_expr) => self.eval_if(if_expr, env, false),
+            Expr::Match(subject, arms, span) => self.eval_match(subject, arms, *span, env, false),
+        }
+    }
+
+    fn eval_hash(&mut self, entries: &[(Expr, Expr)], span: Span, env: &Env) -> Object {
+        let mut hash = BTreeMap::new();
+        for (key, value) in entries {
+            let key_value = self.eval_expression(key, env);
+            if key_value.is_abrupt() {
+                return key_value;
+            }
+            let Some(hash_key) = HashKey::from_object(&key_value) else {
+                let err = error(
+                    ErrorKind::TypeMismatch,
+                    format!("unusable as hash key: {}", key_value.type_name()),
+                );
+                // Literal keys have no span of their own.
+                return self.locate(err, key.span().unwrap_or(span));
+            };
+            let value = self.eval_expression(value, env);
+            if value.is_abrupt() {
+                return value;
+            }
+            hash.insert(hash_key, value);
+        }
+        self.track(Object::Hash(Rc::new(hash)))
+    }
+
+    fn eval_expressions(&mut self, exprs: &[Expr], env: &Env) -> Result<Vec<Object>, Object> {
+        let mut res = Vec::with_capacity(exprs.len());
+        for expr in exprs {
+            let value = self.eval_expression(expr, env);
+            if value.is_abrupt() {
+                return Err(value);
+            }
+            res.push(value);
+        }
+        Ok(res)
+    }
+
+    // Calls `func` from a builtin, as if from where the builtin was called.
+    pub fn call(&mut self, func: Object, args: Vec<Object>) -> Object {
+        self.apply_function(func, args, self.call_site)
+    }
+
+    // Trampoline: tail calls coming back from the body replace the current
+    // function and arguments, so a chain of tail calls runs in constant stack.
+    pub fn apply_function(&mut self, func: Object, args: Vec<Object>, call_site: Span) -> Object {
+        let mut func = match func {
+            Object::Function(func) => func,
+            Object::Builtin(builtin) => {
+                let outer = std::mem::replace(&mut self.call_site, call_site);
+                let res = (builtin.func)(self, args);
+                self.call_site = outer;
+                let res = self.track(res);
+                return self.locate(res, call_site);
+            }
+            other => {
+                return self.locate(
+                    error(
+                        ErrorKind::NotCallable,
+                        format!("not a function: {}", other.type_name()),
+                    ),
+                    call_site,
+                )
+            }
+        };
+        let mut args = args;
+        let mut call_site = call_site;
+
+        if self.frames.len() >= self.config.max_depth {
+            return self.locate(
+                error(
+                    ErrorKind::StackOverflow,
+                    format!("maximum call depth of {} exceeded", self.config.max_depth),
+                ),
+                call_site,
+            );
+        }
+
+        let try_depth = std::mem::replace(&mut self.try_depth, 0);
+        let slots = self.slots.clone();
+        let mut pushed = false;
+        let result = loop {
+            if func.params.len() != args.len() {
+                break self.locate(
+                    error(
+                        ErrorKind::WrongArity,
+                        format!(
+                            "wrong number of arguments: expected {}, got {}",
+                            func.params.len(),
+                            args.len()
+                        ),
+                    ),
+                    call_site,
+                );
+            }
+
+            let frame = Frame {
+                function: function_name(&func),
+                call_site,
+            };
+            match self.frames.last_mut() {
+                Some(top) if pushed => *top = frame,
+                _ => {
+                    self.frames.push(frame);
+                    pushed = true;
+                }
+            }
+
+            self.slots = func.slots.clone();
+            let env = Environment::new_enclosed(func.env.clone());
+            self.heap.track(&env, &self.config.gc);
+            for (param, arg) in func.params.iter().zip(args) {
+                self.bind(&env, &param.ident, arg);
+            }
+
+            match self.eval_statement(&func.body, &env, true) {
+                Object::TailCall(next, next_args, next_call_site) => {
+                    func = next;
+                    args = next_args;
+                    call_site = next_call_site;
+                }
+                Object::Return(value) => break *value,
+                other => break other,
+            }
+        };
+        if pushed {
+            self.frames.pop();
+        }
+        self.try_depth = try_depth;
+        self.slots = slots;
+        result
+    }
+
+    fn slot(&self, ident: &Ident) -> Option<Slot> {
+        self.slots.get(ident.id).copied().flatten()
+    }
+
+    // Binds a name in the current frame: in its slot when it has one.
+    fn bind(&self, env: &Env, ident: &Ident, value: Object) {
+        match self.slot(ident) {
+            Some(slot) => env.borrow_mut().set_slot(slot.index, value),
+            None => env.borrow_mut().set(&ident.name, value),
+        }
+    }
+}
+
+fn is_limit(value: &Object) -> bool {
+    matches!(value, Object::Error(err) if err.kind == ErrorKind::LimitExceeded)
+}
+
+fn function_name(func: &Function) -> String {
+    func.name
+        .clone()
+        .unwrap_or_else(|| String::from("<anonymous>"))
+}
+
+// The statements that do something, i.e. all but comments.
+fn code(statements: &[Statement]) -> impl Iterator<Item = &Statement> {
+    statements
+        .iter()
+        .filter(|statement| !matches!(statement, Statement::Comment(_)))
+}
+
+pub(crate) fn eval_prefix(op: &PrefixOperator, right: Object) -> Object {
+    match (op, right) {
+        (PrefixOperator::Bang, right) => Object::Bool(!right.is_truthy()),
+        (PrefixOperator::Minus, Object::Int(i)) => Object::Int(i.wrapping_neg()),
+        (PrefixOperator::Plus, Object::Int(i)) => Object::Int(i),
+        (op, right) => error(
+            ErrorKind::UnknownOperator,
+            format!("unknown operator: {:?}{}", op, right.type_name()),
+        ),
+    }
+}
+
+pub(crate) fn eval_infix(op: &InfixOperator, left: Object, right: Object) -> Object {
+    match (left, right) {
+        (Object::Int(l), Object::Int(r)) => match op {
+            InfixOperator::Plus => Object::Int(l.wrapping_add(r)),
+            InfixOperator::Minus => Object::Int(l.wrapping_sub(r)),
+            InfixOperator::Star => Object::Int(l.wrapping_mul(r)),
+            InfixOperator::Slash => {
+                if r == 0 {
+                    error(ErrorKind::DivisionByZero, String::from("division by zero"))
+                } else {
+                    Object::Int(l.wrapping_div(r))
+                }
+            }
+            InfixOperator::Eq => Object::Bool(l == r),
+            InfixOperator::NotEq => Object::Bool(l != r),
+            InfixOperator::LessThan => Object::Bool(l < r),
+            InfixOperator::GreaterThan => Object::Bool(l > r),
+        },
+        (Object::String(l), Object::String(r)) => match op {
+            InfixOperator::Plus => Object::String(l + &r),
+            InfixOperator::Eq => Object::Bool(l == r),
+            InfixOperator::NotEq => Object::Bool(l != r),
+            op => error(
+                ErrorKind::UnknownOperator,
+                format!("unknown operator: STRING {:?} STRING", op),
+            ),
+        },
+        (Object::Bool(l), Object::Bool(r)) => match op {
+            InfixOperator::Eq => Object::Bool(l == r),
+            InfixOperator::NotEq => Object::Bool(l != r),
+            op => error(
+                ErrorKind::UnknownOperator,
+                format!("unknown operator: BOOLEAN {:?} BOOLEAN", op),
+            ),
+        },
+        (left, right) => error(
+            ErrorKind::TypeMismatch,
+            format!(
+                "type mismatch: {} {:?} {}",
+                left.type_name(),
+                op,
+                right.type_name()
+            ),
+        ),
+    }
+}
+
+fn eval_member(value: Object, name: &str) -> Object {
+    match value {
+        Object::Module(module) => match module.exports.iter().find(|(n, _)| n == name) {
+            Some((_, value)) => value.clone(),
+            None => error(
+                ErrorKind::UnknownIdentifier,
+                format!("{} is not exported by {}", name, module.path),
+            ),
+        },
+        // `e.kind` reads like `e["kind"]`.
+        value @ Object::ErrorValue(_) => match field(&value, &HashKey::String(name.to_string())) {
+            Some(value) => value,
+            None => error(
+                ErrorKind::UnknownIdentifier,
+                format!("{} is not a field of ERROR", name),
+            ),
+        },
+        value => error(
+            ErrorKind::TypeMismatch,
+            format!(
+                "member access not supported: {}.{}",
+                value.type_name(),
+                name
+            ),
+        ),
+    }
+}
+
+// The value under `key` in a hash, or in the fields of a caught error.
+fn field(value: &Object, key: &HashKey) -> Option<Object> {
+    match (value, key) {
+        (Object::Hash(entries), key) => entries.get(key).cloned(),
+        (Object::ErrorValue(err), HashKey::String(key)) => match key.as_str() {
+            "message" => Some(Object::String(err.message.clone())),
+            "kind" => Some(Object::String(format!("{:?}", err.kind))),
+            "trace" => Some(Object::Array(Rc::new(
+                err.trace
+                    .iter()
+                    .map(|frame| Object::String(frame.to_string()))
+                    .collect(),
+            ))),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn eval_index(left: Object, index: Object) -> Object {
+    match (left, index) {
+        (Object::Array(elements), Object::Int(i)) => {
+            match usize::try_from(i).ok().and_then(|i| elements.get(i)) {
+                Some(element) => element.clone(),
+                None => error(
+                    ErrorKind::IndexOutOfBounds,
+                    format!("index {} out of bounds for length {}", i, elements.len()),
+                ),
+            }
+        }
+        (Object::String(s), Object::Int(i)) => {
+            match usize::try_from(i).ok().and_then(|i| s.chars().nth(i)) {
+                Some(c) => Object::String(c.to_string()),
+                None => error(
+                    ErrorKind::IndexOutOfBounds,
+                    format!("index {} out of bounds for length {}", i, s.chars().count()),
+                ),
+            }
+        }
+        (Object::Hash(entries), index) => match HashKey::from_object(&index) {
+            Some(key) => entries.get(&key).cloned().unwrap_or(Object::Null),
+            None => error(
+                ErrorKind::TypeMismatch,
+                format!("unusable as hash key: {}", index.type_name()),
+            ),
+        },
+        (left @ Object::ErrorValue(_), Object::String(key)) => {
+            field(&left, &HashKey::String(key)).unwrap_or(Object::Null)
+        }
+        (left, index) => error(
+            ErrorKind::TypeMismatch,
+            format!(
+                "index operator not supported: {}[{}]",
+                left.type_name(),
+                index.type_name()
+            ),
+        ),
+    }
+}
@@ -0,0 +1,167 @@
+use std::{
+    collections::HashMap,
+    fs, iter,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    ast::{Program, Statement},
+    lexer::Lexer,
+    object::{
+        environment::{Env, Environment},
+        error::{ErrorKind, RuntimeError},
+        Module, Object,
+    },
+    parser::Parser,
+    stdlib,
+};
+
+use super::{error, Evaluator};
+
+// The modules an evaluator has imported, each evaluated once however many
+// times it is imported.
+#[derive(Debug, Default)]
+pub struct Loader {
+    // By canonical path.
+    cache: HashMap<PathBuf, Rc<Module>>,
+    // Files being evaluated, innermost last, with the path they were found
+    // at: importing one of them again is a cycle.
+    loading: Vec<(PathBuf, String)>,
+}
+
+impl Evaluator {
+    pub(super) fn import(&mut self, path: &str) -> Result<Rc<Module>, Object> {
+        // The standard library shadows files of the same name.
+        if let Some(module) = stdlib::module(path) {
+            if path == "io" && !self.config.allow_io {
+                return Err(error(
+                    ErrorKind::Import,
+                    String::from("the io module is disabled"),
+                ));
+            }
+            return Ok(module);
+        }
+        // Reading a file is I/O too: refused before looking for it, so
+        // that which files exist does not show either.
+        if !self.config.allow_io {
+            return Err(error(
+                ErrorKind::Import,
+                format!("file imports are disabled: {}", path),
+            ));
+        }
+        let Some(file) = self.find_module(path) else {
+            return Err(error(
+                ErrorKind::Import,
+                format!("module not found: {}", path),
+            ));
+        };
+        let shown = file.display().to_string();
+        let key = fs::canonicalize(&file).unwrap_or(file);
+        if let Some(module) = self.modules.cache.get(&key) {
+            return Ok(module.clone());
+        }
+        if let Some(start) = self.modules.loading.iter().position(|(k, _)| *k == key) {
+            let mut cycle: Vec<&str> = self.modules.loading[start..]
+                .iter()
+                .map(|(_, shown)| shown.as_str())
+                .collect();
+            cycle.push(&shown);
+            return Err(error(
+                ErrorKind::Import,
+                format!("import cycle: {}", cycle.join(" -> ")),
+            ));
+        }
+
+        let source = fs::read_to_string(&key)
+            .map_err(|err| error(ErrorKind::Import, format!("cannot read {}: {}", shown, err)))?;
+        let mut parser = Parser::new(Lexer::new(&source));
+        let program = parser.parse();
+        // Only where: the message would quote the file's tokens.
+        if let Some(err) = parser.errors().first() {
+            return Err(error(
+                ErrorKind::Import,
+                format!("parse error in {} at {}", shown, err.span),
+            ));
+        }
+
+        // Modules run in an environment of their own: they only see what
+        // they import, and the importer only sees what they export.
+        let env = Environment::new();
+        self.modules.loading.push((key.clone(), shown.clone()));
+        let result = self.eval(&program, &env);
+        self.modules.loading.pop();
+        match result {
+            // Stops the importer's run as it stopped the module's.
+            Object::Error(err) if err.kind == ErrorKind::LimitExceeded => {
+                return Err(Object::Error(err))
+            }
+            // Already says which import failed; it is located again at the
+            // `import` of this module.
+            Object::Error(err) if err.kind == ErrorKind::Import => {
+                return Err(error(ErrorKind::Import, err.message))
+            }
+            Object::Error(err) => return Err(error(ErrorKind::Import, module_error(&shown, &err))),
+            _ => {}
+        }
+
+        let exports = program
+            .statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::Let(let_stat) if let_stat.export => Some(let_stat.pattern.bindings()),
+                _ => None,
+            })
+            .flatten()
+            .map(|ident| {
+                let value = env.borrow().get(&ident.name).unwrap_or(Object::Null);
+                (ident.name.clone(), value)
+            })
+            .collect();
+        let module = Rc::new(Module {
+            path: shown,
+            exports,
+        });
+        self.modules.cache.insert(key, module.clone());
+        Ok(module)
+    }
+
+    // Evaluates a program read from `file`, which its imports are relative
+    // to, and which is part of any cycle they lead back to.
+    pub fn eval_file(&mut self, program: &Program, env: &Env, file: &Path) -> Object {
+        let key = fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf());
+        let shown = file.display().to_string();
+        self.modules.loading.push((key, shown));
+        let result = self.eval(program, env);
+        self.modules.loading.pop();
+        result
+    }
+
+    // Relative paths are looked up next to the importing module, then in the
+    // search path, then in the working directory.
+    fn find_module(&self, path: &str) -> Option<PathBuf> {
+        let path = Path::new(path);
+        if path.is_absolute() {
+            return path.is_file().then(|| path.to_path_buf());
+        }
+        let importer = self
+            .modules
+            .loading
+            .last()
+            .and_then(|(file, _)| file.parent());
+        importer
+            .into_iter()
+            .chain(self.config.search_path.iter().map(PathBuf::as_path))
+            .map(|dir| dir.join(path))
+            .chain(iter::once(path.to_path_buf()))
+            .find(|candidate| candidate.is_file())
+    }
+}
+
+// An error raised while evaluating a module, located in its file.
+fn module_error(file: &str, err: &RuntimeError) -> String {
+    match err.span {
+        Some(span) => format!("{}:{}: {:?}: {}", file, span, err.kind, err.message),
+        None => format!("{}: {:?}: {}", file, err.kind, err.message),
+    }
+}
@@ -0,0 +1,245 @@
+use std::{collections::BTreeMap, rc::Rc};
+
+use crate::{
+    ast::{ident::Ident, match_expr::Arm, pattern::Pattern, Expr},
+    object::{
+        environment::{Env, Environment},
+        error::ErrorKind,
+        HashKey, Object,
+    },
+    token::Span,
+};
+
+use super::{error, field, Evaluator};
+
+// Why a value does not fit a pattern.
+enum Mismatch {
+    // The value has the wrong shape, as said in the message.
+    Shape(String),
+    // A default failed, or returned from the function.
+    Abrupt(Object),
+}
+
+type Bindings<'a> = Vec<(&'a Ident, Object)>;
+
+impl Evaluator {
+    // Runs the first arm whose pattern matches `subject` and whose guard
+    // holds. The bindings of an arm are made before its guard runs, in an
+    // environment of the arm's own.
+    pub(super) fn eval_match(
+        &mut self,
+        subject: &Expr,
+        arms: &[Arm],
+        span: Span,
+        env: &Env,
+        tail: bool,
+    ) -> Object {
+        let value = self.eval_expression(subject, env);
+        if value.is_abrupt() {
+            return value;
+        }
+        for arm in arms {
+            let mut bindings = Vec::new();
+            match self.fit(&arm.pattern, &value, env, &mut bindings) {
+                Ok(()) => {}
+                Err(Mismatch::Shape(_)) => continue,
+                Err(Mismatch::Abrupt(value)) => return value,
+            }
+            let arm_env = Environment::new_enclosed(env.clone());
+            self.heap.track(&arm_env, &self.config.gc);
+            for (ident, value) in bindings {
+                self.bind(&arm_env, ident, value);
+            }
+            if let Some(guard) = &arm.guard {
+                let guard = self.eval_expression(guard, &arm_env);
+                if guard.is_abrupt() {
+                    return guard;
+                }
+                if !guard.is_truthy() {
+                    continue;
+                }
+            }
+            return self.eval_statement(&arm.body, &arm_env, tail);
+        }
+        self.locate(
+            error(
+                ErrorKind::NoMatch,
+                format!("no pattern matches {}", value.repr()),
+            ),
+            span,
+        )
+    }
+
+    // `let pattern = value;`, failing at the `let` when the value does not
+    // have the shape of the pattern. Nothing is bound then.
+    pub(super) fn destructure(
+        &mut self,
+        pattern: &Pattern,
+        value: Object,
+        span: Span,
+        env: &Env,
+    ) -> Object {
+        let mut bindings = Vec::new();
+        match self.fit(pattern, &value, env, &mut bindings) {
+            Ok(()) => {
+                for (ident, value) in bindings {
+                    self.bind(env, ident, value);
+                }
+                Object::Null
+            }
+            Err(Mismatch::Shape(message)) => self.locate(error(ErrorKind::NoMatch, message), span),
+            Err(Mismatch::Abrupt(value)) => value,
+        }
+    }
+
+    // Matches `value` against `pattern`, collecting what it binds. Defaults
+    // run in `env`, before any of the bindings are made.
+    fn fit<'a>(
+        &mut self,
+        pattern: &'a Pattern,
+        value: &Object,
+        env: &Env,
+        bindings: &mut Bindings<'a>,
+    ) -> Result<(), Mismatch> {
+        match (pattern, value) {
+            (Pattern::Wildcard, _) => Ok(()),
+            (Pattern::Binding(ident), value) => {
+                bindings.push((ident, value.clone()));
+                Ok(())
+            }
+            (Pattern::Default(pattern, _), value) => self.fit(pattern, value, env, bindings),
+            (Pattern::Int(a), Object::Int(b)) if a == b => Ok(()),
+            (Pattern::Bool(a), Object::Bool(b)) if a == b => Ok(()),
+            (Pattern::String(a), Object::String(b)) if a == b => Ok(()),
+            (Pattern::Int(i), value) => Err(unexpected(Object::Int(*i), value)),
+            (Pattern::Bool(b), value) => Err(unexpected(Object::Bool(*b), value)),
+            (Pattern::String(s), value) => Err(unexpected(Object::String(s.clone()), value)),
+            (Pattern::Array(patterns, rest), Object::Array(elements)) => {
+                check_len(patterns, rest.is_some(), elements.len())?;
+                for (i, pattern) in patterns.iter().enumerate() {
+                    match elements.get(i) {
+                        Some(element) => self.fit(pattern, element, env, bindings)?,
+                        None => self.fit_default(pattern, env, bindings)?,
+                    }
+                }
+                if let Some(rest) = rest {
+                    let others = elements[patterns.len().min(elements.len())..].to_vec();
+                    let others = self.track(Object::Array(Rc::new(others)));
+                    if others.is_abrupt() {
+                        return Err(Mismatch::Abrupt(others));
+                    }
+                    bindings.push((rest, others));
+                }
+                Ok(())
+            }
+            (Pattern::Array(..), value) => Err(Mismatch::Shape(format!(
+                "cannot destructure {} as an array",
+                value.type_name()
+            ))),
+            (Pattern::Hash(entries, rest), Object::Hash(_) | Object::ErrorValue(_)) => {
+                for (key, pattern) in entries {
+                    let key = key_of(key);
+                    match key.as_ref().and_then(|key| field(value, key)) {
+                        Some(found) => self.fit(pattern, &found, env, bindings)?,
+                        None if matches!(pattern, Pattern::Default(..)) => {
+                            self.fit_default(pattern, env, bindings)?
+                        }
+                        None => {
+                            let key = key.map_or(Object::Null, |key| key.to_object());
+                            return Err(Mismatch::Shape(format!("missing key {}", key.repr())));
+                        }
+                    }
+                }
+                if let Some(rest) = rest {
+                    let mut others = all_fields(value);
+                    for (key, _) in entries {
+                        if let Some(key) = key_of(key) {
+                            others.remove(&key);
+                        }
+                    }
+                    let others = self.track(Object::Hash(Rc::new(others)));
+                    if others.is_abrupt() {
+                        return Err(Mismatch::Abrupt(others));
+                    }
+                    bindings.push((rest, others));
+                }
+                Ok(())
+            }
+            (Pattern::Hash(..), value) => Err(Mismatch::Shape(format!(
+                "cannot destructure {} as a hash",
+                value.type_name()
+            ))),
+        }
+    }
+
+    // For an element or entry the value does not have: its default, if any,
+    // matched against the pattern.
+    fn fit_default<'a>(
+        &mut self,
+        pattern: &'a Pattern,
+        env: &Env,
+        bindings: &mut Bindings<'a>,
+    ) -> Result<(), Mismatch> {
+        let Pattern::Default(pattern, default) = pattern else {
+            return Err(Mismatch::Shape(String::from("missing element")));
+        };
+        let value = self.eval_expression(default, env);
+        if value.is_abrupt() {
+            return Err(Mismatch::Abrupt(value));
+        }
+        self.fit(pattern, &value, env, bindings)
+    }
+}
+
+// Patterns with defaults may be left out, as long as everything after them
+// is too.
+fn check_len(patterns: &[Pattern], rest: bool, len: usize) -> Result<(), Mismatch> {
+    let min = patterns
+        .iter()
+        .rposition(|pattern| !matches!(pattern, Pattern::Default(..)))
+        .map_or(0, |i| i + 1);
+    let max = patterns.len();
+    let expected = match (rest, min == max) {
+        (true, _) if len >= min => return Ok(()),
+        (false, _) if (min..=max).contains(&len) => return Ok(()),
+        (true, _) => format!("at least {}", min),
+        (false, true) => max.to_string(),
+        (false, false) => format!("{} to {}", min, max),
+    };
+    Err(Mismatch::Shape(format!(
+        "expected {} elements, got {}",
+        expected, len
+    )))
+}
+
+fn unexpected(expected: Object, value: &Object) -> Mismatch {
+    Mismatch::Shape(format!(
+        "expected {}, got {}",
+        expected.repr(),
+        value.repr()
+    ))
+}
+
+// The entries of a hash, or the fields of an error value.
+fn all_fields(value: &Object) -> BTreeMap<HashKey, Object> {
+    match value {
+        Object::Hash(entries) => entries.as_ref().clone(),
+        _ => ["message", "kind", "trace"]
+            .into_iter()
+            .filter_map(|name| {
+                let key = HashKey::String(name.to_string());
+                field(value, &key).map(|value| (key, value))
+            })
+            .collect(),
+    }
+}
+
+// The key a literal in a hash pattern stands for.
+fn key_of(key: &Expr) -> Option<HashKey> {
+    match key {
+        Expr::Int(i) => Some(HashKey::Int(*i)),
+        Expr::Bool(b) => Some(HashKey::Bool(*b)),
+        Expr::String(s) => Some(HashKey::String(s.clone())),
+        _ => None,
+    }
+}
@@ -0,0 +1,865 @@
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::{
+        evaluator::{Config, Evaluator, Limit, Limits},
+        lexer,
+        object::{environment::Environment, error::ErrorKind, Object},
+        parser::Parser,
+    };
+
+    fn eval_with(input: &str, config: Config) -> Object {
+        let mut parser = Parser::new(lexer::Lexer::new(input));
+        let prog = parser.parse();
+        let env = Environment::new();
+        Evaluator::with_config(config).eval(&prog, &env)
+    }
+
+    fn eval(input: &str) -> String {
+        eval_with(input, Config::default()).to_string()
+    }
+
+    #[test]
+    fn test_eval_expression() {
+        assert_eq!(eval("5;"), "5");
+        assert_eq!(eval("-5 + 10;"), "5");
+        assert_eq!(eval("(1 + 2) * 3 - 4 / 2;"), "7");
+        assert_eq!(eval("!true;"), "false");
+        assert_eq!(eval("!!5;"), "true");
+        assert_eq!(eval("1 < 2 == true;"), "true");
+        assert_eq!(eval("3 > 5 != false;"), "false");
+    }
+
+    #[test]
+    fn test_eval_let_and_return() {
+        assert_eq!(eval("let a = 5; let b = a * 2; b + 1;"), "11");
+        assert_eq!(eval("let a = 5\nlet b = a * 2\nb + 1"), "11");
+        assert_eq!(eval("return 2 * 5; 9;"), "10");
+        assert_eq!(
+            eval("if (10 > 1) { if (10 > 1) { return 10; } return 1; }"),
+            "10"
+        );
+        // A line break ends a `return`.
+        assert_eq!(eval("let f = func() {\n return\n 1\n}\nf()"), "null");
+    }
+
+    #[test]
+    fn test_eval_function() {
+        assert_eq!(eval("let add = func(a, b) { a + b; }; add(2, 3);"), "5");
+        assert_eq!(
+            eval(
+                "let adder = func(x) { func(y) { x + y; }; }; let add_two = adder(2); add_two(3);"
+            ),
+            "5"
+        );
+        assert_eq!(
+            eval("let fact = func(n) { if (n == 0) { 1; } else { n * fact(n - 1); } }; fact(10);"),
+            "3628800"
+        );
+    }
+
+    #[test]
+    fn test_eval_if_expression() {
+        assert_eq!(eval("let x = if (1 < 2) { 1 } else { 2 }; x;"), "1");
+        assert_eq!(eval("let x = if (false) { 1 }; x;"), "null");
+        assert_eq!(eval("[if (true) { let a = 2; a * 3 }, 1];"), "[6, 1]");
+        assert_eq!(eval("(if (true) { 1 } else { 2 }) + 1;"), "2");
+        // A block is worth its last statement.
+        assert_eq!(eval("let f = func(x) { let y = x + 1; y * 2 }; f(1);"), "4");
+        // A `return` in a branch leaves the function, not just the `if`.
+        assert_eq!(
+            eval("let f = func(x) { let y = if (x) { return 1; } else { 2 }; y + 1 }; [f(true), f(false)];"),
+            "[1, 3]"
+        );
+        // Calls in the branches of an `if` are still tail calls.
+        assert_eq!(
+            eval(
+                "let count = func(n, acc) { if (n == 0) { acc } else { count(n - 1, acc + 1) } }; \
+                 count(100000, 0);"
+            ),
+            "100000"
+        );
+    }
+
+    #[test]
+    fn test_eval_errors() {
+        assert_eq!(
+            eval("5 + true;"),
+            "TypeMismatch: type mismatch: INTEGER + BOOLEAN at 1:3"
+        );
+        assert_eq!(
+            eval("-true;"),
+            "UnknownOperator: unknown operator: -BOOLEAN at 1:1"
+        );
+        assert_eq!(
+            eval("foobar;"),
+            "UnknownIdentifier: identifier not found: foobar at 1:1"
+        );
+        assert_eq!(eval("1 / 0;"), "DivisionByZero: division by zero at 1:3");
+        assert_eq!(
+            eval("let f = func(a) { a; }; f(1, 2);"),
+            "WrongArity: wrong number of arguments: expected 1, got 2 at 1:25"
+        );
+        assert_eq!(
+            eval("let a = 1; a();"),
+            "NotCallable: not a function: INTEGER at 1:12"
+        );
+    }
+
+    #[test]
+    fn test_error_trace() {
+        let res = eval_with(
+            "let check = func(x) {
+                x + true;
+            };
+            let run = func() {
+                let y = check(1);
+                y;
+            };
+            run();",
+            Config::default(),
+        );
+        let Object::Error(err) = res else {
+            panic!("expected an error, got {}", res);
+        };
+        assert_eq!(err.kind, ErrorKind::TypeMismatch);
+        assert_eq!(err.span.map(|s| (s.line, s.column)), Some((2, 19)));
+        let frames: Vec<(&str, usize, usize)> = err
+            .trace
+            .iter()
+            .map(|f| (f.function.as_str(), f.call_site.line, f.call_site.column))
+            .collect();
+        assert_eq!(frames, vec![("check", 5, 25), ("run", 8, 13)]);
+        assert_eq!(
+            err.to_string(),
+            "TypeMismatch: type mismatch: INTEGER + BOOLEAN at 2:19\n    \
+             in check (called at 5:25)\n    \
+             in run (called at 8:13)"
+        );
+
+        assert_eq!(
+            eval("let f = func() { func() { 1 / 0; }; }; f()();"),
+            "DivisionByZero: division by zero at 1:29\n    in <anonymous> (called at 1:40)"
+        );
+    }
+
+    #[test]
+    fn test_eval_array_and_string() {
+        assert_eq!(eval("[1, 2 * 2, \"x\"];"), "[1, 4, \"x\"]");
+        assert_eq!(eval("let a = [1, [2, 3]]; a[1][0] + a[0];"), "3");
+        assert_eq!(eval("\"cake\"[1];"), "a");
+        assert_eq!(eval("\"foo\" + \"bar\" == \"foobar\";"), "true");
+        assert_eq!(
+            eval("[1, 2][2];"),
+            "IndexOutOfBounds: index 2 out of bounds for length 2 at 1:7"
+        );
+        assert_eq!(
+            eval("[1][-1];"),
+            "IndexOutOfBounds: index -1 out of bounds for length 1 at 1:4"
+        );
+    }
+
+    #[test]
+    fn test_interpolation() {
+        assert_eq!(
+            eval(
+                r#"let name = "Ada"; let count = 2; "Hello, ${name}! You have ${count + 1} messages";"#
+            ),
+            "Hello, Ada! You have 3 messages"
+        );
+        // Values as printed, strings inside arrays quoted.
+        assert_eq!(
+            eval(r#"let f = func(x) { x; }; "${[1, "a"]} ${true} ${f(func() { "}"; })()}";"#),
+            "[1, \"a\"] true }"
+        );
+        assert_eq!(eval(r#""\${not} ${"${"nested"}"}";"#), "${not} nested");
+        assert_eq!(
+            eval(r#""x: ${1 / 0}";"#),
+            "DivisionByZero: division by zero at 1:9"
+        );
+    }
+
+    #[test]
+    fn test_hash() {
+        assert_eq!(
+            eval(r#"let h = {"b": 2, "a": [1, "x"], 3: true}; h;"#),
+            r#"{3: true, "a": [1, "x"], "b": 2}"#
+        );
+        assert_eq!(
+            eval(r#"let h = {"a": 1, "a": 2}; [h["a"], h["b"]];"#),
+            "[2, null]"
+        );
+        assert_eq!(
+            eval("let h = {[1]: 2};"),
+            "TypeMismatch: unusable as hash key: ARRAY at 1:9"
+        );
+        assert_eq!(
+            eval("let h = {}; h[[1]];"),
+            "TypeMismatch: unusable as hash key: ARRAY at 1:13"
+        );
+    }
+
+    #[test]
+    fn test_destructuring() {
+        let cases = [
+            (
+                "let [a, b, ...rest] = [1, 2, 3, 4]; [a, b, rest];",
+                "[1, 2, [3, 4]]",
+            ),
+            ("let [a, ...rest] = [1]; rest;", "[]"),
+            (
+                r#"let {name, age} = {"name": "cake", "age": 3}; "${name} ${age}";"#,
+                "cake 3",
+            ),
+            (r#"let {"n": [x, {y}]} = {"n": [1, {"y": 2}]}; x + y;"#, "3"),
+            (
+                r#"let {a, ...others} = {"a": 1, "b": 2, "c": 3}; others;"#,
+                r#"{"b": 2, "c": 3}"#,
+            ),
+            ("let d = 5; let [a, b = d] = [1]; [a, b];", "[1, 5]"),
+            (r#"let {x = 1, y = 2} = {"y": 3}; [x, y];"#, "[1, 3]"),
+            (
+                r#"try { throw "boom"; } catch (e) { let {message} = e; message; };"#,
+                "boom",
+            ),
+            // Destructured names are bindings like any other.
+            ("let [a] = [1]; let a = a + 1; a;", "2"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(eval(input), expected, "{}", input);
+        }
+
+        let errors = [
+            (
+                "let [a, b] = [1];",
+                "NoMatch: expected 2 elements, got 1 at 1:1",
+            ),
+            (
+                "let [a, b = 0] = [1, 2, 3];",
+                "NoMatch: expected 1 to 2 elements, got 3 at 1:1",
+            ),
+            (
+                "let [a, b, ...c] = [];",
+                "NoMatch: expected at least 2 elements, got 0 at 1:1",
+            ),
+            (
+                "let [a] = 1;",
+                "NoMatch: cannot destructure INTEGER as an array at 1:1",
+            ),
+            (
+                r#"let {a} = "a";"#,
+                "NoMatch: cannot destructure STRING as a hash at 1:1",
+            ),
+            (
+                r#"let {name} = {"nom": 1};"#,
+                r#"NoMatch: missing key "name" at 1:1"#,
+            ),
+            ("let [1, x] = [2, 3];", "NoMatch: expected 1, got 2 at 1:1"),
+            (
+                "let [a = 1 / 0] = [];",
+                "DivisionByZero: division by zero at 1:12",
+            ),
+            // Defaults run before any of the names are bound.
+            (
+                "let [a, b = a] = [1];",
+                "UnknownIdentifier: identifier not found: a at 1:13",
+            ),
+        ];
+        for (input, expected) in errors {
+            assert_eq!(eval(input), expected, "{}", input);
+        }
+        // Nothing is bound when the value does not fit.
+        assert_eq!(
+            eval("let a = 0; try { let [a, b] = [1]; } catch (e) {}; a;"),
+            "0"
+        );
+    }
+
+    #[test]
+    fn test_match() {
+        let describe = r#"let describe = func(v) {
+            match (v) {
+                0 => "zero",
+                -1 => "minus one",
+                [a, b] => "pair ${a} ${b}",
+                {"name": name, "age": age} => "${name} is ${age}",
+                true => "yes",
+                n if n > 100 => { let m = n * 2; "big ${m}"; }
+                _ => "other",
+            }
+        };"#;
+        let cases = [
+            ("0", "zero"),
+            ("-1", "minus one"),
+            ("[1, [2]]", "pair 1 [2]"),
+            (r#"{"name": "cake", "age": 3, "x": 1}"#, "cake is 3"),
+            ("true", "yes"),
+            ("101", "big 202"),
+            ("5", "other"),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(
+                eval(&format!("{} describe({});", describe, value)),
+                expected
+            );
+        }
+
+        assert_eq!(
+            eval(r#"match ([1, 2, 3]) { [a, b] => 0, {"a": a} => 1, _ => 2 };"#),
+            "2"
+        );
+        assert_eq!(eval(r#"match ({"b": 1}) { {"a": a} => 0, {} => 1 };"#), "1");
+        // Each arm binds in a scope of its own: outer bindings survive.
+        assert_eq!(eval("let x = 1; match (5) { x => x }; x;"), "1");
+        assert_eq!(
+            eval("let f = func() { let x = 1; match (5) { x => x }; x; }; f();"),
+            "1"
+        );
+        assert_eq!(
+            eval("match ([1, 2]) { [x, y] => 0 }; x;"),
+            "UnknownIdentifier: identifier not found: x at 1:33"
+        );
+        assert_eq!(
+            eval(
+                r#"try { 1 / 0; } catch (e) { match (e) { {"kind": "DivisionByZero"} => "div", _ => "other" } };"#
+            ),
+            "div"
+        );
+        assert_eq!(
+            eval("match (1 + 1) { 1 => 0 };"),
+            "NoMatch: no pattern matches 2 at 1:1"
+        );
+        // A `return` in an arm leaves the function.
+        assert_eq!(
+            eval("let f = func(x) { let y = match (x) { 1 => { return 0; } _ => x }; y + 1; }; [f(1), f(2)];"),
+            "[0, 3]"
+        );
+        // Arms in tail position make tail calls.
+        assert_eq!(
+            eval("let count = func(n, acc) { match (n) { 0 => acc, _ => count(n - 1, acc + 1) } }; count(100000, 0);"),
+            "100000"
+        );
+    }
+
+    #[test]
+    fn test_try_catch() {
+        assert_eq!(
+            eval("try { 1 / 0; } catch (e) { e[\"kind\"]; };"),
+            "DivisionByZero"
+        );
+        assert_eq!(
+            eval("try { [1][5]; } catch (e) { e[\"message\"]; };"),
+            "index 5 out of bounds for length 1"
+        );
+        assert_eq!(
+            eval(
+                "let f = func(x) { if (x == 0) { throw \"zero\"; } x; };
+                let g = func() { f(0) + 1; };
+                try { g(); } catch (e) { [e[\"kind\"], e[\"message\"], e[\"trace\"]]; };"
+            ),
+            "[\"Thrown\", \"zero\", [\"f (called at 2:34)\", \"g (called at 3:23)\"]]"
+        );
+        // Fields can be read as members too.
+        assert_eq!(
+            eval("try { 1 / 0; } catch (e) { [e.kind, e.message]; };"),
+            "[\"DivisionByZero\", \"division by zero\"]"
+        );
+        assert_eq!(
+            eval("try { 1 / 0; } catch (e) { e.nope; };"),
+            "UnknownIdentifier: nope is not a field of ERROR at 1:28"
+        );
+        // Uncaught errors and rethrows keep their original location.
+        assert_eq!(
+            eval("try { 1 + true; } catch (e) { throw e; };"),
+            "TypeMismatch: type mismatch: INTEGER + BOOLEAN at 1:9"
+        );
+        assert_eq!(eval("throw 5;"), "Thrown: 5 at 1:1");
+        // The error, and what the handler binds, are only seen by the handler.
+        assert_eq!(
+            eval("let e = 1; try { throw 2; } catch (e) { 0; }; e;"),
+            "1"
+        );
+        assert_eq!(
+            eval("let g = func() { let e = 1; try { throw 2; } catch (e) { 0; }; e; }; g();"),
+            "1"
+        );
+        assert_eq!(
+            eval("try { throw 2; } catch (e) { let r = e; }; r;"),
+            "UnknownIdentifier: identifier not found: r at 1:44"
+        );
+    }
+
+    #[test]
+    fn test_try_finally() {
+        assert_eq!(
+            eval("let f = func() { try { return 1; } finally { 2; } }; f();"),
+            "1"
+        );
+        assert_eq!(
+            eval("try { 1; } catch (e) { 2; } finally { let ran = true; }; ran;"),
+            "true"
+        );
+        assert_eq!(
+            eval("try { throw \"a\"; } finally { 1; };"),
+            "Thrown: a at 1:7"
+        );
+        assert_eq!(
+            eval("let f = func() { try { throw 1; } catch (e) { return 2; } finally { return 3; } }; f();"),
+            "3"
+        );
+        // A call returned from inside `try` must not escape the handler as a
+        // tail call.
+        assert_eq!(
+            eval(
+                "let boom = func() { 1 / 0; };
+                let f = func() { try { return boom(); } catch (e) { return e[\"kind\"]; } };
+                f();"
+            ),
+            "DivisionByZero"
+        );
+        assert_eq!(
+            eval_with(
+                "let f = func() { try { f(); } catch (e) { e[\"kind\"]; } }; f();",
+                Config {
+                    max_depth: 50,
+                    ..Config::default()
+                },
+            )
+            .to_string(),
+            "StackOverflow"
+        );
+    }
+
+    #[test]
+    fn test_result_values() {
+        assert_eq!(eval("[ok(1), err(\"bad\")];"), "[ok(1), err(\"bad\")]");
+        assert_eq!(
+            eval("[is_ok(ok(1)), is_ok(err(1)), is_err(err(1))];"),
+            "[true, false, true]"
+        );
+        assert_eq!(
+            eval("[unwrap_or(ok(1), 2), unwrap_or(err(1), 2)];"),
+            "[1, 2]"
+        );
+        assert_eq!(eval("[unwrap(ok(1)), unwrap_err(err(2))];"), "[1, 2]");
+        assert_eq!(
+            eval("unwrap(err(\"bad\"));"),
+            "Thrown: called `unwrap` on err(\"bad\") at 1:1"
+        );
+        assert_eq!(
+            eval("is_ok(1);"),
+            "TypeMismatch: argument to `is_ok` must be RESULT, got INTEGER at 1:1"
+        );
+        assert_eq!(
+            eval("is_ok();"),
+            "WrongArity: wrong number of arguments to `is_ok`: expected 1, got 0 at 1:1"
+        );
+    }
+
+    #[test]
+    fn test_propagate() {
+        let parse =
+            "let parse = func(x) { if (x < 0) { err(\"negative\"); } else { ok(x * 2); } };";
+        assert_eq!(
+            eval(&format!(
+                "{} let sum = func(a, b) {{ ok(parse(a)? + parse(b)?); }}; [sum(1, 2), sum(1, -2)];",
+                parse
+            )),
+            "[ok(6), err(\"negative\")]"
+        );
+        assert_eq!(
+            eval(&format!(
+                "{} let run = func() {{ let v = parse(-1)?; throw \"unreachable\"; }}; run();",
+                parse
+            )),
+            "err(\"negative\")"
+        );
+        assert_eq!(eval("ok(5)? + 1;"), "6");
+        assert_eq!(eval("err(5)?; 1;"), "err(5)");
+        assert_eq!(
+            eval("5?;"),
+            "TypeMismatch: `?` expects a RESULT, got INTEGER at 1:2"
+        );
+    }
+
+    #[test]
+    fn test_tail_call_self_recursion() {
+        assert_eq!(
+            eval(
+                "let count = func(n, acc) {
+                    if (n == 0) { return acc; }
+                    count(n - 1, acc + 1);
+                };
+                count(1000000, 0);"
+            ),
+            "1000000"
+        );
+        assert_eq!(
+            eval(
+                "let loop = func(n) {
+                    if (n == 0) { 0; } else { return loop(n - 1); }
+                };
+                loop(100000);"
+            ),
+            "0"
+        );
+    }
+
+    #[test]
+    fn test_tail_call_mutual_recursion() {
+        assert_eq!(
+            eval(
+                "let even = func(n) { if (n == 0) { true; } else { odd(n - 1); } };
+                let odd = func(n) { if (n == 0) { false; } else { even(n - 1); } };
+                even(1000001);"
+            ),
+            "false"
+        );
+    }
+
+    #[test]
+    fn test_stack_overflow() {
+        let res = eval_with(
+            "let inner = func(n) { 1 + inner(n + 1); };
+            let outer = func() { inner(0); };
+            outer();",
+            Config {
+                max_depth: 20,
+                ..Config::default()
+            },
+        );
+        let Object::Error(err) = res else {
+            panic!("expected an error, got {}", res);
+        };
+        assert_eq!(err.kind, ErrorKind::StackOverflow);
+        assert_eq!(err.message, "maximum call depth of 20 exceeded");
+        let names: Vec<&str> = err.trace.iter().map(|f| f.function.as_str()).collect();
+        assert_eq!(names, vec!["inner"; 10]);
+        assert_eq!(err.omitted_frames, 10);
+
+        // Tail calls do not count towards the limit.
+        assert_eq!(
+            eval_with(
+                "let count = func(n) { if (n == 0) { return 0; } count(n - 1); }; count(100);",
+                Config {
+                    max_depth: 5,
+                    ..Config::default()
+                },
+            )
+            .to_string(),
+            "0"
+        );
+    }
+
+    #[test]
+    fn test_modules() {
+        let root = std::env::temp_dir().join(format!("cake-modules-{}", std::process::id()));
+        let lib = root.join("lib");
+        std::fs::create_dir_all(&lib).unwrap();
+        let files = [
+            (
+                "lib/math.cake",
+                "import \"count.cake\" as count; count.hit(); \
+                 export let square = func(x) { x * x; }; let hidden = 1;",
+            ),
+            // Evaluated once, however many times it is imported.
+            (
+                "lib/count.cake",
+                "export let hit = func() { 1; }; export let loaded = 1;",
+            ),
+            (
+                "lib/counter.cake",
+                "export let make = func(start) { let n = start * 2; func(step) { n + step; }; };",
+            ),
+            ("a.cake", "import \"b.cake\" as b;"),
+            ("b.cake", "import \"a.cake\" as a;"),
+            ("broken.cake", "export let x = 1 +;"),
+            ("failing.cake", "let x = 1;\nx();"),
+        ];
+        for (file, source) in files {
+            std::fs::write(root.join(file), source).unwrap();
+        }
+        let config = Config {
+            search_path: vec![root.clone()],
+            ..Config::default()
+        };
+        let eval = |input: &str| eval_with(input, config.clone()).to_string();
+
+        assert_eq!(
+            eval("import \"lib/math.cake\" as m; import \"lib/math.cake\" as n; m.square(3) + n.square(1);"),
+            "10"
+        );
+        // The search path is used from within modules too, and the
+        // importing module's directory first.
+        assert_eq!(eval("import \"lib/count.cake\" as c; c.loaded;"), "1");
+        // Functions keep the slots of the module they were defined in.
+        assert_eq!(
+            eval(
+                "import \"lib/counter.cake\" as c; \
+                 let f = func(a, b) { let g = c.make(a); g(b); }; f(5, 1);"
+            ),
+            "11"
+        );
+        assert_eq!(
+            eval("import \"lib/math.cake\" as m; m.hidden;"),
+            format!(
+                "UnknownIdentifier: hidden is not exported by {} at 1:30",
+                root.join("lib/math.cake").display()
+            )
+        );
+        assert_eq!(
+            eval("let x = 1; x.y;"),
+            "TypeMismatch: member access not supported: INTEGER.y at 1:12"
+        );
+        assert_eq!(
+            eval("import \"missing.cake\" as m;"),
+            "Import: module not found: missing.cake at 1:1"
+        );
+        let shown = |file: &str| root.join(file).display().to_string();
+        assert_eq!(
+            eval("import \"a.cake\" as a;"),
+            format!(
+                "Import: import cycle: {} -> {} -> {} at 1:1",
+                shown("a.cake"),
+                shown("b.cake"),
+                shown("a.cake")
+            )
+        );
+        assert_eq!(
+            eval("import \"broken.cake\" as m;"),
+            format!(
+                "Import: parse error in {} at 1:19 at 1:1",
+                shown("broken.cake")
+            )
+        );
+        assert_eq!(
+            eval("try { import \"failing.cake\" as m; } catch (e) { e[\"message\"]; }"),
+            format!(
+                "{}:2:1: NotCallable: not a function: INTEGER",
+                shown("failing.cake")
+            )
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_gc() {
+        let cycles = "let make = func() { let f = func() { f(); }; 0; }; ";
+        assert_eq!(
+            eval(&format!("{}make(); make(); make(); gc();", cycles)),
+            "3"
+        );
+        assert_eq!(eval("let g = func() { 1; }; g(); gc();"), "0");
+
+        // The host drops an environment that a closure in it keeps alive.
+        let mut parser = Parser::new(lexer::Lexer::new("let f = func() { f(); };"));
+        let prog = parser.parse();
+        let mut evaluator = Evaluator::default();
+        let env = Environment::new();
+        let weak = std::rc::Rc::downgrade(&env);
+        evaluator.eval(&prog, &env);
+        drop(env);
+        assert!(weak.upgrade().is_some());
+        assert_eq!(evaluator.collect_garbage(), 1);
+        assert!(weak.upgrade().is_none());
+        let stats = evaluator.gc_stats();
+        assert_eq!((stats.collections, stats.freed, stats.live), (1, 1, 0));
+
+        // Collecting as often as possible frees the cycles, and only them.
+        let mut config = Config::default();
+        config.gc.threshold = 1;
+        config.gc.growth = 0;
+        let input = format!(
+            "{}let adder = func(x) {{ func(y) {{ x + y; }}; }}; let add = adder(2); \
+             let repeat = func(n) {{ if (n > 0) {{ make(); repeat(n - 1); }} }}; \
+             repeat(50); [add(3), gc()];",
+            cycles
+        );
+        assert_eq!(eval_with(&input, config.clone()).to_string(), "[5, 0]");
+        assert_eq!(
+            eval_with(
+                "let fact = func(n) { if (n == 0) { 1; } else { n * fact(n - 1); } }; fact(10);",
+                config
+            )
+            .to_string(),
+            "3628800"
+        );
+    }
+
+    #[test]
+    fn test_limits() {
+        // The result and the limit the host sees.
+        let run = |input: &str, limits: Limits| {
+            let mut parser = Parser::new(lexer::Lexer::new(input));
+            let prog = parser.parse();
+            let mut evaluator = Evaluator::with_config(Config {
+                limits,
+                ..Config::default()
+            });
+            let result = evaluator.eval(&prog, &Environment::new());
+            (result.to_string(), evaluator.exceeded())
+        };
+        let steps = || Limits {
+            max_steps: Some(1000),
+            ..Limits::default()
+        };
+
+        let forever = "let f = func(n) { f(n + 1); }; ";
+        assert_eq!(
+            run(&format!("{}f(0);", forever), steps()),
+            (
+                String::from("LimitExceeded: step budget of 1000 exceeded at 1:23\n    in f (called at 1:19)"),
+                Some(Limit::Steps)
+            )
+        );
+        // Neither `catch` nor `finally` run.
+        let (result, exceeded) = run(
+            &format!(
+                "{}try {{ f(0); }} catch (e) {{ 1; }} finally {{ let x = 2; }};",
+                forever
+            ),
+            steps(),
+        );
+        assert!(result.starts_with("LimitExceeded: step budget"));
+        assert_eq!(exceeded, Some(Limit::Steps));
+        assert_eq!(run("1 + 2;", steps()), (String::from("3"), None));
+
+        let (result, exceeded) = run(
+            &format!("{}f(0);", forever),
+            Limits {
+                timeout: Some(std::time::Duration::from_millis(50)),
+                ..Limits::default()
+            },
+        );
+        assert!(result.starts_with("LimitExceeded: timeout of 50ms exceeded"));
+        assert_eq!(exceeded, Some(Limit::Timeout));
+
+        assert_eq!(
+            run(
+                "let f = func(s) { f(s + s); }; f(\"ab\");",
+                Limits {
+                    max_string_len: Some(100),
+                    ..Limits::default()
+                }
+            ),
+            (
+                String::from(
+                    "LimitExceeded: string of 128 bytes exceeds the maximum of 100 at 1:23\n    in f (called at 1:19)"
+                ),
+                Some(Limit::StringLen)
+            )
+        );
+        assert_eq!(
+            run(
+                "[1, 2, 3];",
+                Limits {
+                    max_array_len: Some(2),
+                    ..Limits::default()
+                }
+            ),
+            (
+                String::from("LimitExceeded: array of 3 elements exceeds the maximum of 2"),
+                Some(Limit::ArrayLen)
+            )
+        );
+        // Builtins fail before allocating.
+        assert_eq!(
+            run(
+                "import \"list\" as l; l.range(1000000000000);",
+                Limits {
+                    max_memory: Some(1024),
+                    ..Limits::default()
+                }
+            ),
+            (
+                String::from("LimitExceeded: memory cap of 1024 bytes exceeded at 1:21"),
+                Some(Limit::Memory)
+            )
+        );
+        // Strings too: the last `replace` would make 8 GB.
+        let strings = Limits {
+            max_memory: Some(1000000),
+            max_string_len: Some(100000),
+            max_array_len: Some(1000),
+            ..Limits::default()
+        };
+        let setup = format!(
+            "import \"string\" as s; let a = \"{}\"; let b = s.replace(a, \"a\", a); ",
+            "a".repeat(300)
+        );
+        let cases = [
+            ("s.replace(b, \"a\", b);", "string of 8100000000 bytes"),
+            ("s.join([b, b], \"\");", "string of 180000 bytes"),
+            ("s.format(\"{}{}\", b, b);", "string of 180000 bytes"),
+            ("\"${b}${b}\";", "string of 180000 bytes"),
+            ("s.split(b, \"\");", "array of 90000 elements"),
+        ];
+        for (input, message) in cases {
+            let (result, exceeded) = run(&format!("{}{}", setup, input), strings.clone());
+            assert!(
+                result.starts_with(&format!("LimitExceeded: {}", message)),
+                "{}",
+                result
+            );
+            assert!(exceeded.is_some());
+        }
+
+        let config = Config {
+            allow_io: false,
+            ..Config::default()
+        };
+        assert_eq!(
+            eval_with("import \"io\" as io;", config.clone()).to_string(),
+            "Import: the io module is disabled at 1:1"
+        );
+        // Files are refused whether or not they exist, and nothing of them
+        // is read.
+        let secret = std::env::temp_dir().join(format!("cake-secret-{}.txt", std::process::id()));
+        std::fs::write(&secret, "hunter2").unwrap();
+        let input = format!(
+            "try {{ import {:?} as s; }} catch (e) {{ e[\"message\"]; }}",
+            secret.display().to_string()
+        );
+        assert_eq!(
+            eval_with(&input, config.clone()).to_string(),
+            format!("file imports are disabled: {}", secret.display())
+        );
+        std::fs::remove_file(&secret).unwrap();
+        assert_eq!(
+            eval_with("import \"missing.cake\" as m;", config).to_string(),
+            "Import: file imports are disabled: missing.cake at 1:1"
+        );
+    }
+
+    // Runs the fuzz seed corpus the same way as the `eval` fuzz target, so
+    // that inputs which once crashed stay fixed without a fuzzer at hand.
+    #[test]
+    fn test_fuzz_seeds() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/seeds");
+        let mut seeds = 0;
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let source = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+            eval_with(
+                &source,
+                Config {
+                    max_depth: 64,
+                    ..Config::default()
+                },
+            );
+            seeds += 1;
+        }
+        assert!(seeds > 0);
+    }
+
+    proptest! {
+        // Malformed programs are reported, never panic.
+        #[test]
+        fn prop_eval_never_panics(input in "(let|func|if|else|return|try|catch|throw|[a-c0-9]{1,20}|[ ,;(){}\\[\\]=+*/<>!?-]|\"[a-c]*\"?){0,40}") {
+            eval_with(&input, Config {
+                    max_depth: 16,
+                    ..Config::default()
+                });
+        }
+    }
+}
@@ -0,0 +1,416 @@
+use crate::{
+    ast::{
+        comment::Comment,
+        ident::Ident,
+        interpolated::Fragment,
+        match_expr::Arm,
+        operators::{InfixOperator, PrefixOperator},
+        pattern::Pattern,
+        Expr, Node, Program, Statement,
+    },
+    parser::precedence::{self, CALL, PREFIX},
+};
+
+mod test;
+
+const INDENT: &str = "    ";
+
+// Prints `program` back as canonical source: one statement per line, four
+// spaces of indentation, and only the parentheses the grammar needs. The
+// output parses back to the same tree, comments included.
+pub fn format(program: &Program) -> String {
+    let mut printer = Printer::default();
+    printer.statements(&program.statements);
+    printer.out
+}
+
+#[derive(Default)]
+struct Printer {
+    out: String,
+    indent: usize,
+}
+
+impl Printer {
+    fn statements(&mut self, statements: &[Statement]) {
+        for (i, statement) in statements.iter().enumerate() {
+            if let Statement::Comment(Comment {
+                text,
+                trailing: true,
+            }) = statement
+            {
+                // Goes on the end of the line written last, unless that line
+                // is a comment it would become part of.
+                let after_comment = i > 0 && matches!(statements[i - 1], Statement::Comment(_));
+                if self.out.ends_with('\n') && !after_comment {
+                    self.out.pop();
+                    self.out.push_str(&format!(" //{}\n", text));
+                    continue;
+                }
+            }
+            for _ in 0..self.indent {
+                self.out.push_str(INDENT);
+            }
+            self.statement(statement);
+            self.out.push('\n');
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Let(let_stat) => {
+                if let_stat.export {
+                    self.out.push_str("export ");
+                }
+                self.out.push_str("let ");
+                self.pattern(&let_stat.pattern);
+                if let Some(ty) = &let_stat.ty {
+                    self.out.push_str(&format!(": {}", ty));
+                }
+                self.out.push_str(" = ");
+                self.expr(&let_stat.expr);
+                self.out.push(';');
+            }
+            Statement::Return(return_stat) => {
+                self.out.push_str("return");
+                if let Some(expr) = &return_stat.return_expr {
+                    self.out.push(' ');
+                    self.expr(expr);
+                }
+                self.out.push(';');
+            }
+            Statement::Throw(throw_stat) => {
+                self.out.push_str("throw ");
+                self.expr(&throw_stat.expr);
+                self.out.push(';');
+            }
+            // An `if` or a `match` ends the statement on its own, and only
+            // does so when it is all of it.
+            Statement::Expression(expr @ (Expr::If(_) | Expr::Match(..))) => self.expr(expr),
+            Statement::Expression(expr) => {
+                let parens = matches!(
+                    leftmost(expr),
+                    Expr::Hash(..) | Expr::If(_) | Expr::Match(..)
+                );
+                self.operand(expr, parens);
+                self.out.push(';');
+            }
+            Statement::BlockStatement(block) => self.block(block),
+            Statement::Try(try_stat) => {
+                self.out.push_str("try ");
+                self.statement(&try_stat.body);
+                if let Some(catch) = &try_stat.catch {
+                    self.out
+                        .push_str(&format!(" catch ({}) ", catch.ident.name));
+                    self.statement(&catch.body);
+                }
+                if let Some(finally) = &try_stat.finally {
+                    self.out.push_str(" finally ");
+                    self.statement(finally);
+                }
+            }
+            Statement::Import(import) => {
+                self.out.push_str(&format!(
+                    "import {} as {};",
+                    quote(&import.path),
+                    import.alias.name
+                ));
+            }
+            Statement::Comment(comment) => {
+                self.out.push_str(&format!("//{}", comment.text));
+            }
+            Statement::Illegal(s) => self.out.push_str(s),
+        }
+    }
+
+    fn block(&mut self, block: &[Statement]) {
+        if block.is_empty() {
+            self.out.push_str("{}");
+            return;
+        }
+        self.out.push_str("{\n");
+        self.indent += 1;
+        self.statements(block);
+        self.indent -= 1;
+        for _ in 0..self.indent {
+            self.out.push_str(INDENT);
+        }
+        self.out.push('}');
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Ident(ident) => self.out.push_str(&ident.name),
+            Expr::Int(i) => self.out.push_str(&i.to_string()),
+            Expr::Bool(b) => self.out.push_str(&b.to_string()),
+            Expr::String(s) => self.out.push_str(&quote(s)),
+            Expr::Illegal(s) => self.out.push_str(s),
+            Expr::Prefix(op, right, _) => {
+                self.out.push_str(match op {
+                    PrefixOperator::Plus => "+",
+                    PrefixOperator::Minus => "-",
+                    PrefixOperator::Bang => "!",
+                });
+                self.operand(right, binding(right) < PREFIX);
+            }
+            Expr::Infix(left, op, right, _) => {
+                let prec = infix_precedence(op);
+                // Operators are left-associative: an operand of the same
+                // precedence only needs parentheses on the right.
+                self.operand(left, binding(left) < prec);
+                self.out.push_str(&format!(" {:?} ", op));
+                self.operand(right, binding(right) <= prec);
+            }
+            Expr::Function(params, ret, body) => {
+                let params: Vec<String> = params.iter().map(Node::literal).collect();
+                self.out.push_str(&format!("func({}) ", params.join(", ")));
+                if let Some(ret) = ret {
+                    self.out.push_str(&format!("-> {} ", ret));
+                }
+                self.statement(body);
+            }
+            Expr::Call(func, args, _) => {
+                self.operand(func, binding(func) < CALL);
+                self.out.push('(');
+                self.list(args);
+                self.out.push(')');
+            }
+            Expr::Array(elements) => {
+                self.out.push('[');
+                self.list(elements);
+                self.out.push(']');
+            }
+            Expr::Index(left, index, _) => {
+                self.operand(left, binding(left) < CALL);
+                self.out.push('[');
+                self.expr(index);
+                self.out.push(']');
+            }
+            Expr::Propagate(expr, _) => {
+                self.operand(expr, binding(expr) < CALL);
+                self.out.push('?');
+            }
+            Expr::Member(expr, name, _) => {
+                self.operand(expr, binding(expr) < CALL);
+                self.out.push('.');
+                self.out.push_str(&name.name);
+            }
+            Expr::Interpolated(fragments, _) => {
+                self.out.push('"');
+                for fragment in fragments {
+                    match fragment {
+                        Fragment::Text(text) => self.out.push_str(&escape(text)),
+                        Fragment::Expr(expr) => {
+                            self.out.push_str("${");
+                            self.expr(expr);
+                            self.out.push('}');
+                        }
+                    }
+                }
+                self.out.push('"');
+            }
+            Expr::Hash(entries, _) => {
+                self.out.push('{');
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push_str(", ");
+                    }
+                    self.expr(key);
+                    self.out.push_str(": ");
+                    self.expr(value);
+                }
+                self.out.push('}');
+            }
+            Expr::If(if_expr) => {
+                self.out.push_str("if (");
+                self.expr(&if_expr.condition);
+                self.out.push_str(") ");
+                self.statement(&if_expr.then);
+                if let Some(else_) = &if_expr.else_ {
+                    self.out.push_str(" else ");
+                    self.statement(else_);
+                }
+            }
+            Expr::Match(subject, arms, _) => {
+                self.out.push_str("match (");
+                self.expr(subject);
+                self.out.push_str(") ");
+                self.arms(arms);
+            }
+        }
+    }
+
+    // One arm per line. Blocks end an arm on their own, expressions with a
+    // `,`.
+    fn arms(&mut self, arms: &[Arm]) {
+        if arms.is_empty() {
+            self.out.push_str("{}");
+            return;
+        }
+        self.out.push_str("{\n");
+        self.indent += 1;
+        for arm in arms {
+            for _ in 0..self.indent {
+                self.out.push_str(INDENT);
+            }
+            self.pattern(&arm.pattern);
+            if let Some(guard) = &arm.guard {
+                self.out.push_str(" if ");
+                self.expr(guard);
+            }
+            self.out.push_str(" => ");
+            match arm.body.as_ref() {
+                Statement::Expression(expr) => {
+                    self.operand(expr, matches!(leftmost(expr), Expr::Hash(..)));
+                    self.out.push(',');
+                }
+                body => self.statement(body),
+            }
+            self.out.push('\n');
+        }
+        self.indent -= 1;
+        for _ in 0..self.indent {
+            self.out.push_str(INDENT);
+        }
+        self.out.push('}');
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Wildcard => self.out.push('_'),
+            Pattern::Binding(ident) => self.out.push_str(&ident.name),
+            Pattern::Int(i) => self.out.push_str(&i.to_string()),
+            Pattern::Bool(b) => self.out.push_str(&b.to_string()),
+            Pattern::String(s) => self.out.push_str(&quote(s)),
+            Pattern::Array(elements, rest) => {
+                self.out.push('[');
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push_str(", ");
+                    }
+                    self.pattern(element);
+                }
+                self.rest(rest, elements.is_empty());
+                self.out.push(']');
+            }
+            Pattern::Hash(entries, rest) => {
+                self.out.push('{');
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push_str(", ");
+                    }
+                    // `"name": name` is written `name`.
+                    let (bound, default) = match value {
+                        Pattern::Default(pattern, default) => (pattern.as_ref(), Some(default)),
+                        pattern => (pattern, None),
+                    };
+                    match (key, bound) {
+                        (Expr::String(key), Pattern::Binding(ident)) if *key == ident.name => {
+                            self.out.push_str(key);
+                            if let Some(default) = default {
+                                self.out.push_str(" = ");
+                                self.expr(default);
+                            }
+                        }
+                        _ => {
+                            self.expr(key);
+                            self.out.push_str(": ");
+                            self.pattern(value);
+                        }
+                    }
+                }
+                self.rest(rest, entries.is_empty());
+                self.out.push('}');
+            }
+            Pattern::Default(pattern, default) => {
+                self.pattern(pattern);
+                self.out.push_str(" = ");
+                self.expr(default);
+            }
+        }
+    }
+
+    fn rest(&mut self, rest: &Option<Ident>, first: bool) {
+        if let Some(rest) = rest {
+            if !first {
+                self.out.push_str(", ");
+            }
+            self.out.push_str(&format!("...{}", rest.name));
+        }
+    }
+
+    fn operand(&mut self, expr: &Expr, parens: bool) {
+        if parens {
+            self.out.push('(');
+            self.expr(expr);
+            self.out.push(')');
+        } else {
+            self.expr(expr);
+        }
+    }
+
+    fn list(&mut self, exprs: &[Expr]) {
+        for (i, expr) in exprs.iter().enumerate() {
+            if i > 0 {
+                self.out.push_str(", ");
+            }
+            self.expr(expr);
+        }
+    }
+}
+
+// How tightly an expression holds together, in parser precedences: operands
+// binding more loosely than their context need parentheses.
+fn binding(expr: &Expr) -> i32 {
+    match expr {
+        Expr::Infix(_, op, _, _) => infix_precedence(op),
+        Expr::Prefix(..) => PREFIX,
+        _ => precedence::POSTFIX,
+    }
+}
+
+// The operand `expr` prints starting with. A hash literal there would read
+// as a block where a statement or an arm begins, and an `if` or a `match`
+// would end the statement.
+fn leftmost(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Infix(left, _, _, _)
+        | Expr::Call(left, _, _)
+        | Expr::Index(left, _, _)
+        | Expr::Propagate(left, _)
+        | Expr::Member(left, _, _) => leftmost(left),
+        expr => expr,
+    }
+}
+
+fn infix_precedence(op: &InfixOperator) -> i32 {
+    match op {
+        InfixOperator::Eq | InfixOperator::NotEq => precedence::EQUALS,
+        InfixOperator::LessThan | InfixOperator::GreaterThan => precedence::LESS_GREATER,
+        InfixOperator::Plus | InfixOperator::Minus => precedence::SUM,
+        InfixOperator::Star | InfixOperator::Slash => precedence::PRODUCT,
+    }
+}
+
+// A string literal the lexer reads back as `s`.
+fn quote(s: &str) -> String {
+    format!("\"{}\"", escape(s))
+}
+
+// The text of a string literal, without the quotes.
+fn escape(s: &str) -> String {
+    let mut res = String::new();
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\t' => res.push_str("\\t"),
+            '\r' => res.push_str("\\r"),
+            // Would start an interpolation.
+            '$' if chars.peek() == Some(&'{') => res.push_str("\\$"),
+            c => res.push(c),
+        }
+    }
+    res
+}
@@ -0,0 +1,504 @@
+#[cfg(test)]
+mod tests {
+    use proptest::{collection::vec, prelude::*};
+
+    use crate::{
+        ast::{
+            comment::Comment,
+            ident::Ident,
+            if_else::IfExpression,
+            import::Import,
+            interpolated::Fragment,
+            let_stat::Let,
+            match_expr::Arm,
+            operators::{InfixOperator, PrefixOperator},
+            pattern::Pattern,
+            return_stat::Return,
+            same_code::SameCode,
+            throw_stat::Throw,
+            try_catch::{Catch, Try},
+            types::{Param, TypeExpr, TYPE_NAMES},
+            Expr, Program, Statement,
+        },
+        formatter::format,
+        lexer,
+        parser::Parser,
+        token::{Span, KEYWORDS},
+    };
+
+    fn parse(input: &str) -> Program {
+        let mut parser = Parser::new(lexer::Lexer::new(input));
+        let program = parser.parse();
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+        program
+    }
+
+    fn fmt(input: &str) -> String {
+        format(&parse(input))
+    }
+
+    #[test]
+    fn test_format_statements() {
+        assert_eq!(
+            fmt("let   add=func(a,b){return a+b;};add( 1,2 );"),
+            "let add = func(a, b) {\n    return a + b;\n};\nadd(1, 2);\n"
+        );
+        assert_eq!(
+            fmt("if(x<1){throw \"no\";}else{x;}"),
+            "if (x < 1) {\n    throw \"no\";\n} else {\n    x;\n}\n"
+        );
+        assert_eq!(
+            fmt("try{f();}catch(e){}finally{g();}"),
+            "try {\n    f();\n} catch (e) {} finally {\n    g();\n}\n"
+        );
+        assert_eq!(fmt(""), "");
+    }
+
+    #[test]
+    fn test_format_parentheses() {
+        assert_eq!(fmt("(1 + 2) * 3;"), "(1 + 2) * 3;\n");
+        assert_eq!(fmt("(1 * 2) + 3;"), "1 * 2 + 3;\n");
+        assert_eq!(fmt("1 - (2 - 3);"), "1 - (2 - 3);\n");
+        assert_eq!(fmt("(1 - 2) - 3;"), "1 - 2 - 3;\n");
+        assert_eq!(fmt("-(a + b);"), "-(a + b);\n");
+        assert_eq!(fmt("(-a) * b;"), "-a * b;\n");
+        assert_eq!(fmt("(-f)(x);"), "(-f)(x);\n");
+        assert_eq!(fmt("(a + b)[0]?;"), "(a + b)[0]?;\n");
+        assert_eq!(fmt("!(!x);"), "!!x;\n");
+        assert_eq!(fmt("(a < b) == (c > d);"), "a < b == c > d;\n");
+    }
+
+    #[test]
+    fn test_format_strings() {
+        assert_eq!(
+            fmt(r#"let s = "a\"b\\c\nd";"#),
+            "let s = \"a\\\"b\\\\c\\nd\";\n"
+        );
+    }
+
+    #[test]
+    fn test_format_interpolation() {
+        assert_eq!(
+            fmt(r#""a ${ x+1 } \${b} $${c}";"#),
+            "\"a ${x + 1} \\${b} $${c}\";\n"
+        );
+        assert_eq!(
+            fmt(r#""${func(){ "${y}"; }}";"#),
+            "\"${func() {\n    \"${y}\";\n}}\";\n"
+        );
+    }
+
+    #[test]
+    fn test_format_match() {
+        assert_eq!(
+            fmt(r#"let r=match(x){0=>({"a":1}),[a,-1] if a>0=>{a;}{"k":_}=>2,};"#),
+            "let r = match (x) {\n    0 => ({\"a\": 1}),\n    [a, -1] if a > 0 => {\n        a;\n    }\n    {\"k\": _} => 2,\n};\n"
+        );
+        assert_eq!(fmt("match (x) {}"), "match (x) {}\n");
+        assert_eq!(
+            fmt(r#"let [a,b=1,...c]=x;let {"name":name,"k":k=2,...r}:any=y;let [...d]=z;"#),
+            "let [a, b = 1, ...c] = x;\nlet {name, k = 2, ...r}: any = y;\nlet [...d] = z;\n"
+        );
+        assert_eq!(fmt("({}[1]);"), "({}[1]);\n");
+    }
+
+    #[test]
+    fn test_format_comments() {
+        let input = "\
+// Leading comment
+let a = 1;   // trailing
+func(x) { // opener
+  // inside
+  x;
+};
+// at the end
+";
+        assert_eq!(
+            fmt(input),
+            "\
+// Leading comment
+let a = 1; // trailing
+func(x) { // opener
+    // inside
+    x;
+};
+// at the end
+"
+        );
+
+        // A comment can't trail another one without becoming part of it.
+        let comment = |text: &str, trailing| {
+            Statement::Comment(Comment {
+                text: text.to_string(),
+                trailing,
+            })
+        };
+        let program = Program {
+            statements: vec![comment(" a", false), comment(" b", true)],
+        };
+        assert_eq!(format(&program), "// a\n// b\n");
+    }
+
+    // What the `parse` fuzz target checks, on its seeds.
+    #[test]
+    fn test_format_fuzz_seeds() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/seeds");
+        let mut seeds = 0;
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let source = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+            let mut parser = Parser::new(lexer::Lexer::new(&source));
+            let program = parser.parse();
+            if parser.errors().is_empty() {
+                let printed = format(&program);
+                let mut reparser = Parser::new(lexer::Lexer::new(&printed));
+                assert!(reparser.parse().same_code(&program), "{}", printed);
+                seeds += 1;
+            }
+        }
+        assert!(seeds > 0);
+    }
+
+    #[test]
+    fn test_same_code() {
+        // Spans are left out, the code is not.
+        assert!(parse("1+2;").same_code(&parse("1 + 2;")));
+        assert!(parse("f(a,b);").same_code(&parse("f(\n  a,\n  b\n);")));
+        assert_ne!(parse("1+2;"), parse("1 + 2;"));
+        assert!(!parse("1 + 2;").same_code(&parse("1 + 3;")));
+        assert!(!parse("let a = 1;").same_code(&parse("let b = 1;")));
+        assert!(!parse("f(a);").same_code(&parse("f(a, b);")));
+    }
+
+    #[test]
+    fn test_format_round_trip() {
+        let input = "\
+let fib = func(n) { if (n < 2) { return n; } return fib(n - 1) + fib(n - 2); };
+let xs = [1, \"two\", [3 * (4 + 5)], func() {}];
+try { throw xs[1 + 1][0]; } catch (e) { -(-e?) / 2; } finally {}
+{ let inner = !true == false; }
+";
+        let formatted = fmt(input);
+        assert!(parse(&formatted).same_code(&parse(input)));
+        assert_eq!(fmt(&formatted), formatted);
+    }
+
+    fn ident() -> impl Strategy<Value = Ident> {
+        "[a-zA-Z_]{1,6}"
+            .prop_filter("keywords aren't identifiers", |name| {
+                KEYWORDS.iter().all(|(keyword, _)| keyword != name)
+            })
+            .prop_map(|name| Ident::new(name, Span::default(), 0))
+    }
+
+    fn type_expr() -> impl Strategy<Value = TypeExpr> {
+        let leaf = proptest::sample::select(TYPE_NAMES).prop_map(|(_, ty)| ty.clone());
+        leaf.prop_recursive(3, 8, 3, |inner| {
+            prop_oneof![
+                inner.clone().prop_map(|ty| TypeExpr::Array(Box::new(ty))),
+                (vec(inner.clone(), 0..3), inner)
+                    .prop_map(|(params, ret)| TypeExpr::Func(params, Box::new(ret))),
+            ]
+        })
+    }
+
+    fn param() -> impl Strategy<Value = Param> {
+        (ident(), proptest::option::of(type_expr())).prop_map(|(ident, ty)| Param { ident, ty })
+    }
+
+    fn prefix_op() -> impl Strategy<Value = PrefixOperator> {
+        prop_oneof![
+            Just(PrefixOperator::Plus),
+            Just(PrefixOperator::Minus),
+            Just(PrefixOperator::Bang),
+        ]
+    }
+
+    fn infix_op() -> impl Strategy<Value = InfixOperator> {
+        prop_oneof![
+            Just(InfixOperator::Plus),
+            Just(InfixOperator::Minus),
+            Just(InfixOperator::Star),
+            Just(InfixOperator::Slash),
+            Just(InfixOperator::Eq),
+            Just(InfixOperator::NotEq),
+            Just(InfixOperator::LessThan),
+            Just(InfixOperator::GreaterThan),
+        ]
+    }
+
+    fn pattern() -> impl Strategy<Value = Pattern> {
+        let key = prop_oneof![
+            (0..i64::MAX).prop_map(Expr::Int),
+            any::<bool>().prop_map(Expr::Bool),
+            any::<String>().prop_map(Expr::String),
+        ];
+        let leaf = prop_oneof![
+            Just(Pattern::Wildcard),
+            ident()
+                .prop_filter("`_` is the wildcard", |ident| ident.name != "_")
+                .prop_map(Pattern::Binding),
+            any::<i64>().prop_map(Pattern::Int),
+            any::<bool>().prop_map(Pattern::Bool),
+            any::<String>().prop_map(Pattern::String),
+        ];
+        let default = prop_oneof![
+            ident().prop_map(Expr::Ident),
+            (0..i64::MAX).prop_map(Expr::Int),
+        ];
+        leaf.prop_recursive(3, 12, 3, move |inner| {
+            let element = prop_oneof![
+                inner.clone(),
+                (inner, default.clone())
+                    .prop_map(|(pattern, default)| Pattern::Default(Box::new(pattern), default)),
+            ];
+            let entry = prop_oneof![
+                (key.clone(), element.clone()),
+                ident()
+                    .prop_map(|ident| (Expr::String(ident.name.clone()), Pattern::Binding(ident))),
+            ];
+            prop_oneof![
+                (vec(element, 0..3), proptest::option::of(ident()))
+                    .prop_map(|(elements, rest)| Pattern::Array(elements, rest)),
+                (vec(entry, 0..3), proptest::option::of(ident()))
+                    .prop_map(|(entries, rest)| Pattern::Hash(entries, rest)),
+            ]
+        })
+    }
+
+    fn block(stmt: impl Strategy<Value = Statement>) -> impl Strategy<Value = Box<Statement>> {
+        vec(stmt, 0..3).prop_map(|block| Box::new(Statement::BlockStatement(block)))
+    }
+
+    // Expressions as the parser builds them: negative numbers only exist as
+    // `-` applied to a literal.
+    fn expr() -> impl Strategy<Value = Expr> {
+        let span = Span::default();
+        let leaf = prop_oneof![
+            ident().prop_map(Expr::Ident),
+            (0..i64::MAX).prop_map(Expr::Int),
+            any::<bool>().prop_map(Expr::Bool),
+            any::<String>().prop_map(Expr::String),
+        ];
+        leaf.prop_recursive(4, 48, 4, move |inner| {
+            let body = prop_oneof![
+                inner.clone().prop_map(Statement::Expression),
+                proptest::option::of(inner.clone())
+                    .prop_map(move |return_expr| Statement::Return(Return { return_expr, span })),
+            ];
+            prop_oneof![
+                (prefix_op(), inner.clone()).prop_map(move |(op, right)| Expr::Prefix(
+                    op,
+                    Box::new(right),
+                    span
+                )),
+                (inner.clone(), infix_op(), inner.clone()).prop_map(move |(left, op, right)| {
+                    Expr::Infix(Box::new(left), op, Box::new(right), span)
+                }),
+                (
+                    vec(param(), 0..3),
+                    proptest::option::of(type_expr()),
+                    block(body.clone())
+                )
+                    .prop_map(|(params, ret, body)| Expr::Function(params, ret, body)),
+                (
+                    inner.clone(),
+                    block(body.clone()),
+                    proptest::option::of(block(body.clone()))
+                )
+                    .prop_map(move |(condition, then, else_)| {
+                        Expr::If(Box::new(IfExpression {
+                            condition,
+                            then,
+                            else_,
+                            span,
+                        }))
+                    }),
+                (inner.clone(), vec(inner.clone(), 0..3)).prop_map(move |(func, args)| Expr::Call(
+                    Box::new(func),
+                    args,
+                    span
+                )),
+                vec(inner.clone(), 0..3).prop_map(Expr::Array),
+                vec((inner.clone(), inner.clone()), 0..3)
+                    .prop_map(move |entries| Expr::Hash(entries, span)),
+                (
+                    inner.clone(),
+                    vec(
+                        (
+                            pattern(),
+                            proptest::option::of(inner.clone()),
+                            prop_oneof![
+                                inner
+                                    .clone()
+                                    .prop_map(|expr| Box::new(Statement::Expression(expr))),
+                                block(body.clone()),
+                            ]
+                        ),
+                        0..3
+                    )
+                )
+                    .prop_map(move |(subject, arms)| Expr::Match(
+                        Box::new(subject),
+                        arms.into_iter()
+                            .map(|(pattern, guard, body)| Arm {
+                                pattern,
+                                guard,
+                                body
+                            })
+                            .collect(),
+                        span
+                    )),
+                (inner.clone(), inner.clone()).prop_map(move |(left, index)| Expr::Index(
+                    Box::new(left),
+                    Box::new(index),
+                    span
+                )),
+                inner
+                    .clone()
+                    .prop_map(move |expr| Expr::Propagate(Box::new(expr), span)),
+                (inner.clone(), ident()).prop_map(move |(expr, name)| Expr::Member(
+                    Box::new(expr),
+                    name,
+                    span
+                )),
+                (any::<String>(), vec((inner, any::<String>()), 1..3)).prop_map(
+                    move |(first, rest)| {
+                        let mut fragments = Vec::new();
+                        for (text, expr) in std::iter::once(first)
+                            .map(|text| (text, None))
+                            .chain(rest.into_iter().map(|(expr, text)| (text, Some(expr))))
+                        {
+                            if let Some(expr) = expr {
+                                fragments.push(Fragment::Expr(expr));
+                            }
+                            if !text.is_empty() {
+                                fragments.push(Fragment::Text(text));
+                            }
+                        }
+                        Expr::Interpolated(fragments, span)
+                    }
+                ),
+            ]
+        })
+    }
+
+    fn comment(trailing: bool) -> impl Strategy<Value = Statement> {
+        "( [a-z0-9/]{1,4}){0,3}"
+            .prop_map(move |text| Statement::Comment(Comment { text, trailing }))
+    }
+
+    fn statement() -> impl Strategy<Value = Statement> {
+        let leaf = prop_oneof![
+            (
+                prop_oneof![
+                    ident().prop_map(Pattern::Binding),
+                    pattern().prop_filter("destructures", |pattern| matches!(
+                        pattern,
+                        Pattern::Array(..) | Pattern::Hash(..)
+                    )),
+                ],
+                proptest::option::of(type_expr()),
+                expr()
+            )
+                .prop_map(|(pattern, ty, expr)| {
+                    Statement::Let(Let {
+                        pattern,
+                        ty,
+                        expr,
+                        export: false,
+                        span: Span::default(),
+                    })
+                }),
+            (any::<String>(), ident()).prop_map(|(path, alias)| Statement::Import(Import {
+                path,
+                alias,
+                span: Span::default(),
+            })),
+            proptest::option::of(expr()).prop_map(|return_expr| Statement::Return(Return {
+                return_expr,
+                span: Span::default()
+            })),
+            expr().prop_map(|expr| Statement::Throw(Throw {
+                expr,
+                span: Span::default()
+            })),
+            expr().prop_map(Statement::Expression),
+            comment(false),
+        ];
+        leaf.prop_recursive(3, 16, 3, |inner| {
+            prop_oneof![
+                vec(inner.clone(), 0..3).prop_map(Statement::BlockStatement),
+                (
+                    expr(),
+                    block(inner.clone()),
+                    proptest::option::of(block(inner.clone()))
+                )
+                    .prop_map(|(condition, then, else_)| {
+                        Statement::Expression(Expr::If(Box::new(IfExpression {
+                            condition,
+                            then,
+                            else_,
+                            span: Span::default(),
+                        })))
+                    }),
+                (
+                    block(inner.clone()),
+                    proptest::option::of((ident(), block(inner.clone()))),
+                    proptest::option::of(block(inner)),
+                )
+                    .prop_filter("try needs catch or finally", |(_, catch, finally)| {
+                        catch.is_some() || finally.is_some()
+                    })
+                    .prop_map(|(body, catch, finally)| Statement::Try(Try {
+                        body,
+                        catch: catch.map(|(ident, body)| Catch { ident, body }),
+                        finally,
+                        span: Span::default(),
+                    })),
+            ]
+        })
+    }
+
+    // Statements, each possibly followed by a comment at the end of its line.
+    // Top level `let`s can be exported.
+    fn program() -> impl Strategy<Value = Program> {
+        let line = (
+            statement(),
+            any::<bool>(),
+            proptest::option::of(comment(true)),
+        );
+        vec(line, 0..6).prop_map(|lines| {
+            let mut statements = Vec::new();
+            for (mut statement, export, comment) in lines {
+                if let Statement::Let(let_stat) = &mut statement {
+                    let_stat.export = export;
+                }
+                let is_comment = matches!(statement, Statement::Comment(_));
+                statements.push(statement);
+                if let Some(comment) = comment.filter(|_| !is_comment) {
+                    statements.push(comment);
+                }
+            }
+            Program { statements }
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_expression_round_trip(expr in expr()) {
+            let program = Program {
+                statements: vec![Statement::Expression(expr)],
+            };
+            let source = format(&program);
+            let parsed = parse(&source);
+            prop_assert!(parsed.same_code(&program), "{}\n{:?}\n{:?}", source, parsed, program);
+        }
+
+        #[test]
+        fn prop_program_round_trip(program in program()) {
+            let source = format(&program);
+            let parsed = parse(&source);
+            prop_assert!(parsed.same_code(&program), "{}\n{:?}\n{:?}", source, parsed, program);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::token::{lookup_ident, Token};
+use crate::token::{lookup_ident, Span, Token};
 
 mod test;
 
@@ -8,7 +8,14 @@ pub struct Lexer {
     pos: usize,
     read_pos: usize,
     ch: Option<char>,
-    errors: Vec<String>,
+    line: usize,
+    column: usize,
+    span: Span,
+    // Whether a line break came before the token last returned.
+    newline: bool,
+    // For each `${` being read, innermost last, the number of `{` opened
+    // since: the `}` that closes it resumes the string.
+    templates: Vec<usize>,
 }
 
 impl Lexer {
@@ -19,13 +26,23 @@ impl Lexer {
             pos: 0,
             read_pos: 0,
             ch: None,
-            errors: vec![],
+            line: 1,
+            column: 0,
+            span: Span::default(),
+            newline: false,
+            templates: vec![],
         };
         res.read_char();
         res
     }
 
     fn read_char(&mut self) {
+        if self.ch == Some('\n') {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
         if self.read_pos >= self.input.len() {
             self.ch = None;
         } else {
@@ -35,7 +52,7 @@ impl Lexer {
         self.read_pos += 1;
     }
 
-    fn peek_char(&mut self) -> Option<char> {
+    fn peek_char(&self) -> Option<char> {
         if self.read_pos >= self.input.len() {
             None
         } else {
@@ -43,17 +60,44 @@ impl Lexer {
         }
     }
 
+    // Span of the token last returned by `next_token`.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    // Whether the token last returned by `next_token` starts a new line.
+    pub fn newline(&self) -> bool {
+        self.newline
+    }
+
     pub fn next_token(&mut self) -> Token {
+        let line = self.line;
         self.skip_whitespace();
+        self.newline = self.line > line;
+        let (start, line, column) = (self.pos.min(self.input.len()), self.line, self.column);
+        let tok = self.read_token();
+        self.span = Span {
+            start,
+            end: self.pos.min(self.input.len()),
+            line,
+            column,
+        };
+        tok
+    }
+
+    fn read_token(&mut self) -> Token {
         let tok = match self.ch {
-            Some('=') => {
-                if let Some('=') = self.peek_char() {
+            Some('=') => match self.peek_char() {
+                Some('=') => {
                     self.read_char();
                     Token::Eq
-                } else {
-                    Token::Assign
                 }
-            }
+                Some('>') => {
+                    self.read_char();
+                    Token::FatArrow
+                }
+                _ => Token::Assign,
+            },
             Some('!') => {
                 if let Some('=') = self.peek_char() {
                     self.read_char();
@@ -63,17 +107,68 @@ impl Lexer {
                 }
             }
             Some(';') => Token::Semicolon,
+            Some(':') => Token::Colon,
             Some('(') => Token::LParen,
             Some(')') => Token::RParen,
             Some(',') => Token::Comma,
-            Some('{') => Token::LBrace,
-            Some('}') => Token::RBrace,
+            Some('{') => {
+                if let Some(open) = self.templates.last_mut() {
+                    *open += 1;
+                }
+                Token::LBrace
+            }
+            Some('}') if self.templates.last() == Some(&0) => match self.read_string() {
+                Some((s, true)) => Token::TemplateMiddle(s),
+                Some((s, false)) => {
+                    self.templates.pop();
+                    Token::TemplateEnd(s)
+                }
+                None => return Token::Illegal(vec!['"']),
+            },
+            Some('}') => {
+                if let Some(open) = self.templates.last_mut() {
+                    *open -= 1;
+                }
+                Token::RBrace
+            }
+            Some('[') => Token::LBracket,
+            Some(']') => Token::RBracket,
+            Some('"') => match self.read_string() {
+                Some((s, false)) => Token::String(s),
+                Some((s, true)) => {
+                    self.templates.push(0);
+                    Token::TemplateStart(s)
+                }
+                None => return Token::Illegal(vec!['"']),
+            },
             Some('+') => Token::Plus,
-            Some('-') => Token::Minus,
+            Some('-') => {
+                if let Some('>') = self.peek_char() {
+                    self.read_char();
+                    Token::Arrow
+                } else {
+                    Token::Minus
+                }
+            }
             Some('*') => Token::Star,
-            Some('/') => Token::Slash,
+            Some('/') => {
+                if let Some('/') = self.peek_char() {
+                    return Token::Comment(self.read_comment());
+                }
+                Token::Slash
+            }
             Some('<') => Token::LessThan,
             Some('>') => Token::GreaterThan,
+            Some('?') => Token::Question,
+            Some('.') => {
+                if self.input.get(self.read_pos..self.read_pos + 2) == Some(&['.', '.']) {
+                    self.read_char();
+                    self.read_char();
+                    Token::Ellipsis
+                } else {
+                    Token::Dot
+                }
+            }
             None => Token::EOF,
             Some(a) => {
                 let res = if is_letter(a) {
@@ -115,6 +210,49 @@ impl Lexer {
         res
     }
 
+    // Reads a `//` comment up to, but not including, the end of the line.
+    pub fn read_comment(&mut self) -> Vec<char> {
+        self.read_char();
+        self.read_char();
+        let mut res = Vec::new();
+        while self.ch.is_some() && self.ch != Some('\n') {
+            res.push(self.ch.unwrap());
+            self.read_char();
+        }
+        res
+    }
+
+    // Reads a string literal with the current char on the opening quote, or
+    // the `}` of an embedded expression, and leaves it on the closing quote,
+    // or the `{` of the next `${`. The flag tells which one it stopped at.
+    // Returns `None` if the string is not terminated before the end of the
+    // input.
+    pub fn read_string(&mut self) -> Option<(Vec<char>, bool)> {
+        let mut res = Vec::new();
+        loop {
+            self.read_char();
+            match self.ch {
+                None => return None,
+                Some('"') => return Some((res, false)),
+                Some('$') if self.peek_char() == Some('{') => {
+                    self.read_char();
+                    return Some((res, true));
+                }
+                Some('\\') => {
+                    self.read_char();
+                    match self.ch {
+                        None => return None,
+                        Some('n') => res.push('\n'),
+                        Some('t') => res.push('\t'),
+                        Some('r') => res.push('\r'),
+                        Some(c) => res.push(c),
+                    }
+                }
+                Some(c) => res.push(c),
+            }
+        }
+    }
+
     pub fn read_identifier(&mut self) -> String {
         let mut res = String::new();
         while self.ch.is_some() && is_letter(self.ch.unwrap()) {
@@ -125,10 +263,10 @@ impl Lexer {
     }
 }
 
-fn is_letter(ch: char) -> bool {
-    (ch >= 'a' && ch <= 'z') || (ch >= 'A' && ch <= 'Z') || ch == '_'
+pub fn is_letter(ch: char) -> bool {
+    ch.is_ascii_alphabetic() || ch == '_'
 }
 
 fn is_digit(ch: char) -> bool {
-    ch >= '0' && ch <= '9'
+    ch.is_ascii_digit()
 }
@@ -1,5 +1,7 @@
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use crate::{lexer::Lexer, token::Token};
 
     #[test]
@@ -90,4 +92,228 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_string_and_brackets() {
+        let mut lexer = Lexer::new(r#"["a b", "q\"\n"] "open"#);
+        let res = lexer.read_all();
+        assert_eq!(
+            res,
+            vec![
+                Token::LBracket,
+                Token::String(vec!['a', ' ', 'b']),
+                Token::Comma,
+                Token::String(vec!['q', '"', '\n']),
+                Token::RBracket,
+                Token::Illegal(vec!['"']),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_annotations() {
+        let mut lexer = Lexer::new("x: int -> a - >");
+        let res = lexer.read_all();
+        assert_eq!(
+            res,
+            vec![
+                Token::Ident(vec!['x']),
+                Token::Colon,
+                Token::Ident(vec!['i', 'n', 't']),
+                Token::Arrow,
+                Token::Ident(vec!['a']),
+                Token::Minus,
+                Token::GreaterThan,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_modules() {
+        let mut lexer = Lexer::new("import \"m\" as m; export let x = m.f;");
+        let res = lexer.read_all();
+        assert_eq!(
+            res,
+            vec![
+                Token::Import,
+                Token::String(vec!['m']),
+                Token::As,
+                Token::Ident(vec!['m']),
+                Token::Semicolon,
+                Token::Export,
+                Token::Let,
+                Token::Ident(vec!['x']),
+                Token::Assign,
+                Token::Ident(vec!['m']),
+                Token::Dot,
+                Token::Ident(vec!['f']),
+                Token::Semicolon,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_match() {
+        let mut lexer = Lexer::new("match (x) { _ => 1 } == =>");
+        let res = lexer.read_all();
+        assert_eq!(
+            res,
+            vec![
+                Token::Match,
+                Token::LParen,
+                Token::Ident(vec!['x']),
+                Token::RParen,
+                Token::LBrace,
+                Token::Ident(vec!['_']),
+                Token::FatArrow,
+                Token::Int(vec!['1']),
+                Token::RBrace,
+                Token::Eq,
+                Token::FatArrow,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ellipsis() {
+        let mut lexer = Lexer::new("[a, ...rest] m.x ..");
+        let res = lexer.read_all();
+        assert_eq!(
+            res,
+            vec![
+                Token::LBracket,
+                Token::Ident(vec!['a']),
+                Token::Comma,
+                Token::Ellipsis,
+                Token::Ident(vec!['r', 'e', 's', 't']),
+                Token::RBracket,
+                Token::Ident(vec!['m']),
+                Token::Dot,
+                Token::Ident(vec!['x']),
+                Token::Dot,
+                Token::Dot,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let chars = |s: &str| s.chars().collect::<Vec<char>>();
+        let mut lexer = Lexer::new(r#""a${f(func() { "${x}"; })}b${y}\${z}""#);
+        let res = lexer.read_all();
+        assert_eq!(
+            res,
+            vec![
+                Token::TemplateStart(chars("a")),
+                Token::Ident(chars("f")),
+                Token::LParen,
+                Token::Func,
+                Token::LParen,
+                Token::RParen,
+                Token::LBrace,
+                Token::TemplateStart(vec![]),
+                Token::Ident(chars("x")),
+                Token::TemplateEnd(vec![]),
+                Token::Semicolon,
+                Token::RBrace,
+                Token::RParen,
+                Token::TemplateMiddle(chars("b")),
+                Token::Ident(chars("y")),
+                Token::TemplateEnd(chars("${z}")),
+            ]
+        );
+        let mut lexer = Lexer::new("\"${x} and $ {y}\" \"${x");
+        assert_eq!(
+            lexer.read_all(),
+            vec![
+                Token::TemplateStart(vec![]),
+                Token::Ident(chars("x")),
+                Token::TemplateEnd(chars(" and $ {y}")),
+                Token::TemplateStart(vec![]),
+                Token::Ident(chars("x")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_comment() {
+        let mut lexer = Lexer::new("a / b; // half\n//");
+        let res = lexer.read_all();
+        assert_eq!(
+            res,
+            vec![
+                Token::Ident(vec!['a']),
+                Token::Slash,
+                Token::Ident(vec!['b']),
+                Token::Semicolon,
+                Token::Comment(vec![' ', 'h', 'a', 'l', 'f']),
+                Token::Comment(vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_span() {
+        let mut lexer = Lexer::new("let x = 56;\n  x == y;");
+        let mut spans = Vec::new();
+        loop {
+            let tok = lexer.next_token();
+            let span = lexer.span();
+            spans.push((span.line, span.column, span.start, span.end));
+            if tok == Token::EOF {
+                break;
+            }
+        }
+        assert_eq!(
+            spans,
+            vec![
+                (1, 1, 0, 3),
+                (1, 5, 4, 5),
+                (1, 7, 6, 7),
+                (1, 9, 8, 10),
+                (1, 11, 10, 11),
+                (2, 3, 14, 15),
+                (2, 5, 16, 18),
+                (2, 8, 19, 20),
+                (2, 9, 20, 21),
+                (2, 10, 21, 21),
+            ]
+        );
+    }
+
+    proptest! {
+        // Every token consumes at least one char, so any input ends in EOF
+        // after at most one token per char, and stays there.
+        #[test]
+        fn prop_lexer_ends_in_eof(input in any::<String>()) {
+            let len = input.chars().count();
+            let mut lexer = Lexer::new(&input);
+            let mut last_end = 0;
+            let mut tokens = 0;
+            loop {
+                let token = lexer.next_token();
+                let span = lexer.span();
+                prop_assert!(span.start >= last_end && span.start <= span.end && span.end <= len);
+                last_end = span.end;
+                if token == Token::EOF {
+                    break;
+                }
+                tokens += 1;
+                prop_assert!(tokens <= len);
+            }
+            prop_assert_eq!(lexer.next_token(), Token::EOF);
+        }
+
+        // Same, over inputs made of the chars the lexer gives meaning to.
+        #[test]
+        fn prop_lexer_ends_in_eof_on_code(input in "[a-z0-9 \\n\"\\\\/+*!=<>?,;(){}\\[\\]-]{0,64}") {
+            let len = input.chars().count();
+            let mut lexer = Lexer::new(&input);
+            let mut tokens = 0;
+            while lexer.next_token() != Token::EOF {
+                tokens += 1;
+                prop_assert!(tokens <= len);
+            }
+        }
+    }
 }
@@ -0,0 +1,15 @@
+pub mod ast;
+pub mod checker;
+pub mod cli;
+pub mod evaluator;
+pub mod formatter;
+pub mod lexer;
+pub mod linter;
+pub mod lsp;
+pub mod object;
+pub mod optimize;
+pub mod parser;
+pub mod repl;
+pub mod resolver;
+pub mod stdlib;
+pub mod token;
@@ -0,0 +1,68 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+
+use super::{Level, Rule};
+
+// Settings shared by the tools working on a project, looked up in the
+// current directory and the ones above it.
+pub const PROJECT_FILE: &str = "cake.json";
+
+// The level of each rule, for the rules the project sets.
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+    levels: HashMap<Rule, Level>,
+}
+
+impl LintConfig {
+    pub fn level(&self, rule: Rule) -> Level {
+        self.levels
+            .get(&rule)
+            .copied()
+            .unwrap_or_else(|| rule.default_level())
+    }
+
+    pub fn set(&mut self, rule: Rule, level: Level) {
+        self.levels.insert(rule, level);
+    }
+
+    // Reads the `lint` table of a project file, e.g.
+    // `{ "lint": { "empty-block": "off", "unused": "error" } }`.
+    pub fn from_project(project: &Value) -> Result<Self, String> {
+        let mut config = Self::default();
+        let lint = match project.get("lint") {
+            Some(Value::Object(lint)) => lint,
+            Some(_) => return Err(String::from("`lint` must be an object")),
+            None => return Ok(config),
+        };
+        for (name, level) in lint {
+            let rule =
+                Rule::from_name(name).ok_or_else(|| format!("unknown lint rule: {}", name))?;
+            let level = level
+                .as_str()
+                .and_then(Level::from_name)
+                .ok_or_else(|| format!("invalid level for {}: {}", name, level))?;
+            config.set(rule, level);
+        }
+        Ok(config)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text =
+            fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        let project: Value =
+            serde_json::from_str(&text).map_err(|err| format!("{}: {}", path.display(), err))?;
+        Self::from_project(&project).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+}
+
+// The closest project file in `dir` or a directory above it.
+pub fn find_project(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(PROJECT_FILE))
+        .find(|path| path.is_file())
+}
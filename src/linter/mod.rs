@@ -0,0 +1,454 @@
+use std::fmt::Display;
+
+use crate::{
+    ast::{
+        interpolated::Fragment,
+        match_expr::Arm,
+        operators::{InfixOperator, PrefixOperator},
+        pattern::Pattern,
+        same_code::SameCode,
+        Expr, Program, Statement,
+    },
+    resolver::{self, DiagnosticKind},
+    token::Span,
+};
+
+pub mod config;
+mod test;
+
+use config::LintConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    // `x == true`, `x != false`.
+    BoolComparison,
+    // `if (true)`, `if (1 < 2)`.
+    ConstantCondition,
+    // `x == x`.
+    SelfComparison,
+    // `if (c) {}`, `try {} ...`.
+    EmptyBlock,
+    // `!!x`.
+    DoubleNegation,
+    // A function that returns a value on some paths and ends without one on
+    // others, as after a `let` or an `if` with no `else`.
+    InconsistentReturn,
+    // A `match` on booleans without an arm for one of them.
+    NonExhaustiveMatch,
+    // The findings of the resolver.
+    Undefined,
+    Shadowing,
+    Unused,
+    Unreachable,
+}
+
+pub const RULES: [Rule; 11] = [
+    Rule::BoolComparison,
+    Rule::ConstantCondition,
+    Rule::SelfComparison,
+    Rule::EmptyBlock,
+    Rule::DoubleNegation,
+    Rule::InconsistentReturn,
+    Rule::NonExhaustiveMatch,
+    Rule::Undefined,
+    Rule::Shadowing,
+    Rule::Unused,
+    Rule::Unreachable,
+];
+
+impl Rule {
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::BoolComparison => "bool-comparison",
+            Rule::ConstantCondition => "constant-condition",
+            Rule::SelfComparison => "self-comparison",
+            Rule::EmptyBlock => "empty-block",
+            Rule::DoubleNegation => "double-negation",
+            Rule::InconsistentReturn => "inconsistent-return",
+            Rule::NonExhaustiveMatch => "non-exhaustive-match",
+            Rule::Undefined => "undefined",
+            Rule::Shadowing => "shadowing",
+            Rule::Unused => "unused",
+            Rule::Unreachable => "unreachable",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        RULES.into_iter().find(|rule| rule.name() == name)
+    }
+
+    pub fn default_level(self) -> Level {
+        match self {
+            Rule::Undefined => Level::Error,
+            _ => Level::Warning,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Off,
+    Warning,
+    Error,
+}
+
+impl Level {
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Off => "off",
+            Level::Warning => "warning",
+            Level::Error => "error",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Level::Off, Level::Warning, Level::Error]
+            .into_iter()
+            .find(|level| level.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    pub rule: Rule,
+    pub level: Level,
+    pub message: String,
+    pub span: Span,
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [{}]", self.message, self.rule.name())
+    }
+}
+
+// The findings of the rules `config` leaves on, in source order.
+pub fn lint(program: &Program, config: &LintConfig) -> Vec<Lint> {
+    let mut linter = Linter {
+        config,
+        lints: Vec::new(),
+    };
+    for diagnostic in resolver::resolve(program).diagnostics {
+        let rule = match diagnostic.kind {
+            DiagnosticKind::Undefined => Rule::Undefined,
+            DiagnosticKind::Shadowed => Rule::Shadowing,
+            DiagnosticKind::Unused => Rule::Unused,
+            DiagnosticKind::Unreachable => Rule::Unreachable,
+        };
+        linter.report(rule, diagnostic.message, diagnostic.span);
+    }
+    linter.statements(&program.statements);
+    linter.lints.sort_by_key(|lint| lint.span.start);
+    linter.lints
+}
+
+struct Linter<'a> {
+    config: &'a LintConfig,
+    lints: Vec<Lint>,
+}
+
+impl Linter<'_> {
+    fn report(&mut self, rule: Rule, message: String, span: Span) {
+        let level = self.config.level(rule);
+        if level != Level::Off {
+            self.lints.push(Lint {
+                rule,
+                level,
+                message,
+                span,
+            });
+        }
+    }
+
+    fn statements(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            self.statement(statement);
+        }
+    }
+
+    // Reports `block` if nothing in it runs. A comment in it is taken as a
+    // sign that it is empty on purpose.
+    fn empty_block(&mut self, block: &Statement, what: &str, span: Span) {
+        if let Statement::BlockStatement(statements) = block {
+            if statements.is_empty() {
+                self.report(Rule::EmptyBlock, format!("empty `{}` block", what), span);
+            }
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Let(let_stat) => {
+                self.expr(&let_stat.expr);
+                for default in let_stat.pattern.defaults() {
+                    self.expr(default);
+                }
+            }
+            Statement::Return(return_stat) => {
+                if let Some(expr) = &return_stat.return_expr {
+                    self.expr(expr);
+                }
+            }
+            Statement::Expression(expr) => self.expr(expr),
+            Statement::Throw(throw_stat) => self.expr(&throw_stat.expr),
+            Statement::BlockStatement(block) => self.statements(block),
+            Statement::Try(try_stat) => {
+                self.empty_block(&try_stat.body, "try", try_stat.span);
+                self.statement(&try_stat.body);
+                if let Some(catch) = &try_stat.catch {
+                    self.empty_block(&catch.body, "catch", catch.ident.span);
+                    self.statement(&catch.body);
+                }
+                if let Some(finally) = &try_stat.finally {
+                    self.empty_block(finally, "finally", try_stat.span);
+                    self.statement(finally);
+                }
+            }
+            Statement::Import(_) | Statement::Comment(_) | Statement::Illegal(_) => {}
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Ident(_) | Expr::Int(_) | Expr::Bool(_) | Expr::String(_) | Expr::Illegal(_) => {}
+            Expr::Prefix(op, right, span) => {
+                if let (PrefixOperator::Bang, Expr::Prefix(PrefixOperator::Bang, _, _)) =
+                    (op, right.as_ref())
+                {
+                    let message = String::from("double negation, use the value itself");
+                    self.report(Rule::DoubleNegation, message, *span);
+                }
+                self.expr(right);
+            }
+            Expr::Infix(left, op, right, span) => {
+                self.comparison(left, op, right, *span);
+                self.expr(left);
+                self.expr(right);
+            }
+            Expr::Function(_, _, body) => {
+                if let Some(span) = first_return(body) {
+                    if !ends_with_value(body) {
+                        let message = String::from(
+                            "function returns a value here but can also run off its end",
+                        );
+                        self.report(Rule::InconsistentReturn, message, span);
+                    }
+                }
+                self.statement(body);
+            }
+            Expr::Call(func, args, _) => {
+                self.expr(func);
+                args.iter().for_each(|arg| self.expr(arg));
+            }
+            Expr::Array(elements) => elements.iter().for_each(|e| self.expr(e)),
+            Expr::Index(left, index, _) => {
+                self.expr(left);
+                self.expr(index);
+            }
+            Expr::Propagate(expr, _) | Expr::Member(expr, _, _) => self.expr(expr),
+            Expr::Interpolated(fragments, _) => {
+                for fragment in fragments {
+                    if let Fragment::Expr(expr) = fragment {
+                        self.expr(expr);
+                    }
+                }
+            }
+            Expr::Hash(entries, _) => {
+                for (key, value) in entries {
+                    self.expr(key);
+                    self.expr(value);
+                }
+            }
+            Expr::If(if_expr) => {
+                if is_constant(&if_expr.condition) {
+                    let message = match truthiness(&if_expr.condition) {
+                        Some(value) => format!("condition is always {}", value),
+                        None => String::from("condition is constant"),
+                    };
+                    self.report(Rule::ConstantCondition, message, if_expr.span);
+                }
+                self.empty_block(&if_expr.then, "if", if_expr.span);
+                self.expr(&if_expr.condition);
+                self.statement(&if_expr.then);
+                if let Some(else_) = &if_expr.else_ {
+                    self.empty_block(else_, "else", if_expr.span);
+                    self.statement(else_);
+                }
+            }
+            Expr::Match(subject, arms, span) => {
+                self.exhaustive(arms, *span);
+                self.expr(subject);
+                for arm in arms {
+                    if let Some(guard) = &arm.guard {
+                        self.expr(guard);
+                    }
+                    self.statement(&arm.body);
+                }
+            }
+        }
+    }
+
+    // A `match` with boolean patterns is taken to be on a boolean, which
+    // needs an arm for both values unless one matches anything. Guarded arms
+    // may not apply, and cover nothing.
+    fn exhaustive(&mut self, arms: &[Arm], span: Span) {
+        let on_bool = arms
+            .iter()
+            .any(|arm| matches!(arm.pattern, Pattern::Bool(_)));
+        let covered = |value: bool| {
+            arms.iter().any(|arm| {
+                arm.guard.is_none()
+                    && (arm.pattern.is_irrefutable() || arm.pattern == Pattern::Bool(value))
+            })
+        };
+        let missing: Vec<String> = [true, false]
+            .into_iter()
+            .filter(|value| !covered(*value))
+            .map(|value| format!("`{}`", value))
+            .collect();
+        if on_bool && !missing.is_empty() {
+            let message = format!(
+                "non-exhaustive match: {} not covered",
+                missing.join(" and ")
+            );
+            self.report(Rule::NonExhaustiveMatch, message, span);
+        }
+    }
+
+    fn comparison(&mut self, left: &Expr, op: &InfixOperator, right: &Expr, span: Span) {
+        let comparison = matches!(
+            op,
+            InfixOperator::Eq
+                | InfixOperator::NotEq
+                | InfixOperator::LessThan
+                | InfixOperator::GreaterThan
+        );
+        if !comparison {
+            return;
+        }
+        if let InfixOperator::Eq | InfixOperator::NotEq = op {
+            if let Some(Expr::Bool(value)) = [left, right]
+                .into_iter()
+                .find(|side| matches!(side, Expr::Bool(_)))
+            {
+                let message = format!("comparison to `{}`, use the condition itself", value);
+                self.report(Rule::BoolComparison, message, span);
+            }
+        }
+        if left.same_code(right) && is_pure(left) {
+            let message = String::from("both sides of the comparison are the same");
+            self.report(Rule::SelfComparison, message, span);
+        }
+    }
+}
+
+// Whether an expression is made of literals only.
+fn is_constant(expr: &Expr) -> bool {
+    match expr {
+        Expr::Int(_)
+        | Expr::Bool(_)
+        | Expr::String(_)
+        | Expr::Array(_)
+        | Expr::Hash(..)
+        | Expr::Function(..) => true,
+        Expr::Prefix(_, right, _) => is_constant(right),
+        Expr::Infix(left, _, right, _) => is_constant(left) && is_constant(right),
+        _ => false,
+    }
+}
+
+// Whether a constant condition holds, when that is plain from the literals.
+fn truthiness(expr: &Expr) -> Option<bool> {
+    match expr {
+        Expr::Bool(value) => Some(*value),
+        Expr::Int(_) | Expr::String(_) | Expr::Array(_) | Expr::Hash(..) | Expr::Function(..) => {
+            Some(true)
+        }
+        Expr::Prefix(PrefixOperator::Bang, right, _) => truthiness(right).map(|value| !value),
+        _ => None,
+    }
+}
+
+// Whether evaluating an expression twice gives the same value: no calls.
+fn is_pure(expr: &Expr) -> bool {
+    match expr {
+        Expr::Call(..) | Expr::If(_) | Expr::Match(..) | Expr::Illegal(_) => false,
+        Expr::Prefix(_, right, _) | Expr::Propagate(right, _) | Expr::Member(right, _, _) => {
+            is_pure(right)
+        }
+        Expr::Infix(left, _, right, _) | Expr::Index(left, right, _) => {
+            is_pure(left) && is_pure(right)
+        }
+        Expr::Array(elements) => elements.iter().all(is_pure),
+        Expr::Hash(entries, _) => entries
+            .iter()
+            .all(|(key, value)| is_pure(key) && is_pure(value)),
+        Expr::Interpolated(fragments, _) => fragments.iter().all(|fragment| match fragment {
+            Fragment::Text(_) => true,
+            Fragment::Expr(expr) => is_pure(expr),
+        }),
+        Expr::Ident(_) | Expr::Int(_) | Expr::Bool(_) | Expr::String(_) | Expr::Function(..) => {
+            true
+        }
+    }
+}
+
+// The first `return` of a function body, not counting nested functions.
+// Whether every way through a function body ends with a value: a `return`,
+// a `throw`, or an expression as its last statement.
+fn ends_with_value(statement: &Statement) -> bool {
+    match statement {
+        Statement::Return(_) | Statement::Throw(_) => true,
+        Statement::BlockStatement(block) => {
+            let mut code = block
+                .iter()
+                .filter(|statement| !matches!(statement, Statement::Comment(_)));
+            code.clone().any(Statement::always_exits)
+                || code.next_back().is_some_and(ends_with_value)
+        }
+        Statement::Expression(Expr::If(if_expr)) => match &if_expr.else_ {
+            Some(else_) => ends_with_value(&if_expr.then) && ends_with_value(else_),
+            None => false,
+        },
+        Statement::Expression(Expr::Match(_, arms, _)) => {
+            arms.iter().all(|arm| ends_with_value(&arm.body))
+        }
+        Statement::Expression(_) => true,
+        Statement::Try(try_stat) => {
+            let handled = match &try_stat.catch {
+                Some(catch) => ends_with_value(&catch.body),
+                None => true,
+            };
+            (ends_with_value(&try_stat.body) && handled)
+                || try_stat.finally.as_ref().is_some_and(|f| f.always_exits())
+        }
+        Statement::Let(_)
+        | Statement::Import(_)
+        | Statement::Comment(_)
+        | Statement::Illegal(_) => false,
+    }
+}
+
+fn first_return(statement: &Statement) -> Option<Span> {
+    match statement {
+        Statement::Return(return_stat) => Some(return_stat.span),
+        Statement::BlockStatement(block) => block.iter().find_map(first_return),
+        Statement::Expression(Expr::If(if_expr)) => {
+            first_return(&if_expr.then).or_else(|| if_expr.else_.as_deref().and_then(first_return))
+        }
+        Statement::Try(try_stat) => first_return(&try_stat.body)
+            .or_else(|| {
+                try_stat
+                    .catch
+                    .as_ref()
+                    .and_then(|catch| first_return(&catch.body))
+            })
+            .or_else(|| try_stat.finally.as_deref().and_then(first_return)),
+        Statement::Let(_)
+        | Statement::Import(_)
+        | Statement::Expression(_)
+        | Statement::Throw(_)
+        | Statement::Comment(_)
+        | Statement::Illegal(_) => None,
+    }
+}
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        lexer,
+        linter::{
+            config::{find_project, LintConfig},
+            lint, Level, Rule,
+        },
+        parser::Parser,
+    };
+
+    // The lints of `input`, as `line:column rule`.
+    fn lints_with(input: &str, config: &LintConfig) -> Vec<String> {
+        let mut parser = Parser::new(lexer::Lexer::new(input));
+        let program = parser.parse();
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+        lint(&program, config)
+            .iter()
+            .map(|lint| format!("{} {}", lint.span, lint.rule.name()))
+            .collect()
+    }
+
+    fn lints(input: &str) -> Vec<String> {
+        lints_with(input, &LintConfig::default())
+    }
+
+    #[test]
+    fn test_lint_expressions() {
+        assert_eq!(
+            lints("let x = 1; x == true; false != x; x == x; x < x; !!x; f() == f();"),
+            [
+                "1:14 bool-comparison",
+                "1:29 bool-comparison",
+                "1:37 self-comparison",
+                "1:45 self-comparison",
+                "1:50 double-negation",
+                "1:55 undefined",
+                "1:62 undefined",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lint_statements() {
+        assert_eq!(
+            lints("if (true) { 1; }\nif (!0 == 1) { 1; }\nlet c = 1;\nif (c) {} else { 2; }"),
+            [
+                "1:1 constant-condition",
+                "2:1 constant-condition",
+                "4:1 empty-block",
+            ]
+        );
+        // A comment says the block is empty on purpose.
+        assert_eq!(
+            lints("try { 1; } catch (_e) {} finally { // nothing\n};"),
+            ["1:19 empty-block"]
+        );
+        assert_eq!(
+            lints(
+                "let f = func(a) { if (a) { return 1; } };
+                let g = func(a) { if (a) { return 1; } return 2; };
+                let h = func(a) { a; };
+                f(1); g(1); h(1);"
+            ),
+            ["1:28 inconsistent-return"]
+        );
+        // A last expression is the value of the other paths.
+        assert!(lints(
+            "let f = func(a) { if (a) { return 1 } 2 };
+            let count = func(n, acc) { if (n == 0) { return acc; } count(n - 1, acc + 1); };
+            f(1); count(1, 0);"
+        )
+        .is_empty());
+        assert_eq!(
+            lints("let f = func(a) { if (a) { return 1; } let _b = 2; }; f(1);"),
+            ["1:28 inconsistent-return"]
+        );
+    }
+
+    #[test]
+    fn test_lint_match() {
+        assert_eq!(
+            lints(
+                "let b = true;\nmatch (b) { true => 1 };\nmatch (b) { true => 1, x if x => 2 };\n\
+                 match (b) { true => 1, false => 2 };\nmatch (b) { false => 1, _ => 2 };"
+            ),
+            ["2:1 non-exhaustive-match", "3:1 non-exhaustive-match"]
+        );
+    }
+
+    #[test]
+    fn test_lint_config() {
+        let config = LintConfig::from_project(&json!({
+            "lint": { "bool-comparison": "off", "unused": "error" }
+        }))
+        .unwrap();
+        assert_eq!(config.level(Rule::BoolComparison), Level::Off);
+        assert_eq!(config.level(Rule::Unused), Level::Error);
+        assert_eq!(config.level(Rule::EmptyBlock), Level::Warning);
+        assert_eq!(
+            lints_with("let a = true; a == true;", &config),
+            Vec::<String>::new()
+        );
+        assert_eq!(lints_with("let b = 1;", &config), ["1:5 unused"]);
+
+        assert_eq!(
+            LintConfig::from_project(&json!({ "lint": { "nope": "off" } })).unwrap_err(),
+            "unknown lint rule: nope"
+        );
+        assert_eq!(
+            LintConfig::from_project(&json!({ "lint": { "unused": "loud" } })).unwrap_err(),
+            "invalid level for unused: \"loud\""
+        );
+
+        // The closest project file up the tree wins.
+        let root = std::env::temp_dir().join(format!("cake-lint-{}", std::process::id()));
+        let nested = root.join("src").join("deep");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(root.join("cake.json"), "{}").unwrap();
+        assert_eq!(find_project(&nested), Some(root.join("cake.json")));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use crate::{
+    ast::Program,
+    checker,
+    evaluator::builtins,
+    resolver::{self, BindingKind, Diagnostic, Scope},
+    token::Span,
+};
+
+#[derive(Debug, Clone)]
+pub struct Definition {
+    pub name: String,
+    pub span: Span,
+    pub kind: BindingKind,
+    // What the checker infers for the bound value, e.g. `int` or
+    // `func(int) -> int`.
+    pub ty: Option<String>,
+    pub scope: usize,
+    // The scope of the function a `let` binds, if it binds a literal one.
+    pub body: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Reference {
+    pub span: Span,
+    pub name: String,
+    pub def: Option<usize>,
+}
+
+// The names of a program, as the resolver sees them, and what is known about
+// the values they hold.
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    pub defs: Vec<Definition>,
+    pub refs: Vec<Reference>,
+    pub diagnostics: Vec<Diagnostic>,
+    scopes: Vec<Scope>,
+}
+
+pub enum Target<'a> {
+    Def(&'a Definition),
+    Builtin(&'a str),
+}
+
+impl Analysis {
+    pub fn new(program: &Program) -> Self {
+        let resolution = resolver::resolve(program);
+        let types = checker::infer(program, HashMap::new()).bindings;
+        let defs = resolution
+            .bindings
+            .into_iter()
+            .map(|binding| Definition {
+                ty: types.get(&binding.span.start).map(ToString::to_string),
+                name: binding.name,
+                span: binding.span,
+                kind: binding.kind,
+                scope: binding.scope,
+                body: binding.body,
+            })
+            .collect();
+        let refs = resolution
+            .uses
+            .into_iter()
+            .map(|r| Reference {
+                span: r.span,
+                name: r.name,
+                def: r.binding,
+            })
+            .collect();
+        Self {
+            defs,
+            refs,
+            diagnostics: resolution.diagnostics,
+            scopes: resolution.scopes,
+        }
+    }
+
+    fn innermost(&self, offset: usize) -> usize {
+        (0..self.scopes.len())
+            .rev()
+            .find(|scope| {
+                let scope = &self.scopes[*scope];
+                scope.start <= offset && offset <= scope.end
+            })
+            .unwrap_or(0)
+    }
+
+    // The name under `offset`, at its definition or at a use, and the span of
+    // that occurrence.
+    pub fn target(&self, offset: usize) -> Option<(Target<'_>, Span)> {
+        let contains = |span: &Span| span.start <= offset && offset <= span.end;
+        if let Some(reference) = self.refs.iter().find(|r| contains(&r.span)) {
+            let target = match reference.def {
+                Some(def) => Target::Def(&self.defs[def]),
+                None => builtins::names()
+                    .find(|name| *name == reference.name)
+                    .map(Target::Builtin)?,
+            };
+            return Some((target, reference.span));
+        }
+        self.defs
+            .iter()
+            .find(|def| contains(&def.span))
+            .map(|def| (Target::Def(def), def.span))
+    }
+
+    // Definitions visible at `offset`, innermost first, each name once.
+    pub fn visible(&self, offset: usize) -> Vec<&Definition> {
+        let mut res: Vec<&Definition> = Vec::new();
+        let mut current = Some(self.innermost(offset));
+        while let Some(scope) = current {
+            let scope = &self.scopes[scope];
+            for def in scope.bindings.iter().rev() {
+                let def = &self.defs[*def];
+                if res.iter().all(|seen| seen.name != def.name) {
+                    res.push(def);
+                }
+            }
+            current = scope.parent;
+        }
+        res
+    }
+
+    // `let` bindings made directly in `scope`, in order.
+    pub fn bindings(&self, scope: usize) -> impl Iterator<Item = &Definition> {
+        self.scopes[scope]
+            .bindings
+            .iter()
+            .map(|def| &self.defs[*def])
+            .filter(|def| def.kind == BindingKind::Let)
+    }
+}
@@ -0,0 +1,79 @@
+use serde_json::{json, Value};
+
+use crate::{
+    ast::Program,
+    lexer,
+    parser::{error::ParseError, Parser},
+    token::Span,
+};
+
+use super::analysis::Analysis;
+
+// An open file: its text, the parse of it, and what is known about its names.
+pub struct Document {
+    chars: Vec<char>,
+    // Char offset at which each line starts.
+    line_starts: Vec<usize>,
+    pub program: Program,
+    pub errors: Vec<ParseError>,
+    pub analysis: Analysis,
+}
+
+impl Document {
+    pub fn new(text: &str) -> Self {
+        let chars: Vec<char> = text.chars().collect();
+        let mut line_starts = vec![0];
+        line_starts.extend(
+            chars
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| **c == '\n')
+                .map(|(i, _)| i + 1),
+        );
+        let mut parser = Parser::new(lexer::Lexer::new(text));
+        let program = parser.parse();
+        let errors = parser.errors().to_vec();
+        let analysis = Analysis::new(&program);
+        Self {
+            chars,
+            line_starts,
+            program,
+            errors,
+            analysis,
+        }
+    }
+
+    // LSP positions count lines from 0 and characters in UTF-16 code units.
+    pub fn position(&self, offset: usize) -> Value {
+        let offset = offset.min(self.chars.len());
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let character: usize = self.chars[self.line_starts[line]..offset]
+            .iter()
+            .map(|c| c.len_utf16())
+            .sum();
+        json!({ "line": line, "character": character })
+    }
+
+    pub fn range(&self, span: Span) -> Value {
+        json!({ "start": self.position(span.start), "end": self.position(span.end) })
+    }
+
+    // Char offset of an LSP position, clamped to the document.
+    pub fn offset(&self, position: &Value) -> usize {
+        let line = position["line"].as_u64().unwrap_or(0) as usize;
+        let Some(start) = self.line_starts.get(line) else {
+            return self.chars.len();
+        };
+        let mut units = position["character"].as_u64().unwrap_or(0) as usize;
+        let mut offset = *start;
+        while offset < self.chars.len() && self.chars[offset] != '\n' {
+            let width = self.chars[offset].len_utf16();
+            if units < width {
+                break;
+            }
+            units -= width;
+            offset += 1;
+        }
+        offset
+    }
+}
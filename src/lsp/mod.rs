@@ -0,0 +1,334 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+};
+
+use analysis::{Definition, Target};
+use document::Document;
+use serde_json::{json, Value};
+
+use crate::{
+    evaluator::builtins,
+    resolver::{BindingKind, DiagnosticKind},
+    token::KEYWORDS,
+};
+
+pub mod analysis;
+pub mod document;
+mod test;
+
+// JSON-RPC error codes used by the protocol.
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_REQUEST: i64 = -32600;
+
+// LSP enumerations, as numbers on the wire.
+const SEVERITY_ERROR: u8 = 1;
+const SEVERITY_WARNING: u8 = 2;
+const TAG_UNNECESSARY: u8 = 1;
+const SYMBOL_FUNCTION: u8 = 12;
+const SYMBOL_VARIABLE: u8 = 13;
+const COMPLETION_FUNCTION: u8 = 3;
+const COMPLETION_VARIABLE: u8 = 6;
+const COMPLETION_KEYWORD: u8 = 14;
+const SYNC_FULL: u8 = 1;
+
+// Largest message body accepted, well above any source file worth editing:
+// the body is allocated before it is read, at the size the client claims.
+const MAX_MESSAGE_LEN: usize = 16 * 1024 * 1024;
+// Longest header line accepted, for the same reason.
+const MAX_HEADER_LEN: u64 = 1024;
+
+// Serves one client over `input` and `output` until it sends `exit`. Returns
+// the process exit code: 0 if the client asked for a shutdown first.
+pub fn serve(mut input: impl BufRead, mut output: impl Write) -> i32 {
+    let mut server = Server::default();
+    loop {
+        let message = match read_message(&mut input) {
+            Ok(Some(message)) => message,
+            // The client went away without saying goodbye.
+            Ok(None) => return 1,
+            Err(err) => {
+                eprintln!("lsp: {}", err);
+                return 1;
+            }
+        };
+        if message["method"] == "exit" {
+            return if server.shutdown { 0 } else { 1 };
+        }
+        for reply in server.handle(&message) {
+            if let Err(err) = write_message(&mut output, &reply) {
+                eprintln!("lsp: {}", err);
+                return 1;
+            }
+        }
+    }
+}
+
+// Reads one `Content-Length` framed message, `None` at the end of input.
+pub fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if io::Read::take(&mut *input, MAX_HEADER_LEN).read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        if !line.ends_with('\n') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "header line too long or cut short",
+            ));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let Some(length) = length else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "message without Content-Length",
+        ));
+    };
+    if length > MAX_MESSAGE_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "message of {} bytes exceeds the maximum of {}",
+                length, MAX_MESSAGE_LEN
+            ),
+        ));
+    }
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+pub fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+#[derive(Default)]
+struct Server {
+    documents: HashMap<String, Document>,
+    shutdown: bool,
+}
+
+impl Server {
+    // Replies and notifications to send back for `message`.
+    fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        let Some(id) = message.get("id") else {
+            return self.notification(method, params);
+        };
+        let result = match method {
+            "initialize" => Ok(capabilities()),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            _ if self.shutdown => Err((INVALID_REQUEST, String::from("server is shutting down"))),
+            "textDocument/definition" => Ok(self.definition(params)),
+            "textDocument/hover" => Ok(self.hover(params)),
+            "textDocument/documentSymbol" => Ok(self.symbols(params)),
+            "textDocument/completion" => Ok(self.completion(params)),
+            _ => Err((METHOD_NOT_FOUND, format!("unknown method: {}", method))),
+        };
+        let reply = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message },
+            }),
+        };
+        vec![reply]
+    }
+
+    fn notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let text = match method {
+            "textDocument/didOpen" => params["textDocument"]["text"].as_str(),
+            // Documents are synced in full, the last change holds the text.
+            "textDocument/didChange" => params["contentChanges"]
+                .as_array()
+                .and_then(|changes| changes.last())
+                .and_then(|change| change["text"].as_str()),
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+                return vec![publish_diagnostics(uri, vec![])];
+            }
+            _ => None,
+        };
+        let Some(text) = text else {
+            return vec![];
+        };
+        let document = Document::new(text);
+        let errors = document.errors.iter().map(|err| {
+            json!({
+                "range": document.range(err.span),
+                "severity": SEVERITY_ERROR,
+                "source": "cake",
+                "message": err.message,
+            })
+        });
+        let warnings = document.analysis.diagnostics.iter().map(|diagnostic| {
+            let severity = if diagnostic.is_error() {
+                SEVERITY_ERROR
+            } else {
+                SEVERITY_WARNING
+            };
+            let mut value = json!({
+                "range": document.range(diagnostic.span),
+                "severity": severity,
+                "source": "cake",
+                "message": diagnostic.message,
+            });
+            // Editors fade out code that does nothing.
+            if let DiagnosticKind::Unused | DiagnosticKind::Unreachable = diagnostic.kind {
+                value["tags"] = json!([TAG_UNNECESSARY]);
+            }
+            value
+        });
+        let diagnostics = errors.chain(warnings).collect();
+        self.documents.insert(uri.to_string(), document);
+        vec![publish_diagnostics(uri, diagnostics)]
+    }
+
+    // The document and char offset a `TextDocumentPositionParams` points at.
+    fn locate(&self, params: &Value) -> Option<(&Document, usize)> {
+        let uri = params["textDocument"]["uri"].as_str()?;
+        let document = self.documents.get(uri)?;
+        let offset = document.offset(&params["position"]);
+        Some((document, offset))
+    }
+
+    fn definition(&self, params: &Value) -> Value {
+        let Some((document, offset)) = self.locate(params) else {
+            return Value::Null;
+        };
+        match document.analysis.target(offset) {
+            Some((Target::Def(def), _)) => json!({
+                "uri": params["textDocument"]["uri"],
+                "range": document.range(def.span),
+            }),
+            _ => Value::Null,
+        }
+    }
+
+    fn hover(&self, params: &Value) -> Value {
+        let Some((document, offset)) = self.locate(params) else {
+            return Value::Null;
+        };
+        let Some((target, span)) = document.analysis.target(offset) else {
+            return Value::Null;
+        };
+        let text = match target {
+            Target::Def(def) => describe(def),
+            Target::Builtin(name) => format!("builtin {}", name),
+        };
+        json!({
+            "contents": { "kind": "markdown", "value": format!("```cake\n{}\n```", text) },
+            "range": document.range(span),
+        })
+    }
+
+    fn symbols(&self, params: &Value) -> Value {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        match self.documents.get(uri) {
+            Some(document) => Value::Array(symbols(document, 0)),
+            None => Value::Null,
+        }
+    }
+
+    fn completion(&self, params: &Value) -> Value {
+        let Some((document, offset)) = self.locate(params) else {
+            return Value::Null;
+        };
+        let keywords = KEYWORDS
+            .iter()
+            .map(|(keyword, _)| json!({ "label": keyword, "kind": COMPLETION_KEYWORD }));
+        let builtins = builtins::names()
+            .map(|name| json!({ "label": name, "kind": COMPLETION_FUNCTION, "detail": "builtin" }));
+        let names = document.analysis.visible(offset).into_iter().map(|def| {
+            let kind = match def.body {
+                Some(_) => COMPLETION_FUNCTION,
+                None => COMPLETION_VARIABLE,
+            };
+            json!({ "label": def.name, "kind": kind, "detail": describe(def) })
+        });
+        Value::Array(names.chain(builtins).chain(keywords).collect())
+    }
+}
+
+fn capabilities() -> Value {
+    json!({
+        "capabilities": {
+            "textDocumentSync": SYNC_FULL,
+            "definitionProvider": true,
+            "hoverProvider": true,
+            "documentSymbolProvider": true,
+            "completionProvider": {},
+        },
+        "serverInfo": { "name": "cake", "version": env!("CARGO_PKG_VERSION") },
+    })
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+// How a name reads in a hover, e.g. `let n: int`.
+fn describe(def: &Definition) -> String {
+    let keyword = match def.kind {
+        BindingKind::Let => "let",
+        BindingKind::Param => "param",
+        BindingKind::Catch => "catch",
+        BindingKind::Import => "import",
+        BindingKind::Pattern => "pattern",
+    };
+    match &def.ty {
+        Some(ty) => format!("{} {}: {}", keyword, def.name, ty),
+        None => format!("{} {}", keyword, def.name),
+    }
+}
+
+// The `let` bindings of `scope`, with those of the functions they bind
+// nested under them.
+fn symbols(document: &Document, scope: usize) -> Vec<Value> {
+    document
+        .analysis
+        .bindings(scope)
+        .map(|def| {
+            let range = document.range(def.span);
+            let (kind, children) = match def.body {
+                Some(body) => (SYMBOL_FUNCTION, symbols(document, body)),
+                None => (SYMBOL_VARIABLE, vec![]),
+            };
+            let mut symbol = json!({
+                "name": def.name,
+                "kind": kind,
+                "range": range,
+                "selectionRange": range,
+                "children": children,
+            });
+            if let Some(ty) = &def.ty {
+                symbol["detail"] = json!(ty);
+            }
+            symbol
+        })
+        .collect()
+}
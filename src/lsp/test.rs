@@ -0,0 +1,276 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use serde_json::{json, Value};
+
+    use crate::lsp::{read_message, serve, write_message, MAX_MESSAGE_LEN};
+
+    const URI: &str = "file:///main.cake";
+
+    // Runs the server over `requests` and returns what it wrote back.
+    fn session(requests: &[Value]) -> (i32, Vec<Value>) {
+        let mut input = Vec::new();
+        for request in requests {
+            write_message(&mut input, request).unwrap();
+        }
+        let mut output = Vec::new();
+        let code = serve(Cursor::new(input), &mut output);
+        let mut output = Cursor::new(output);
+        let mut replies = Vec::new();
+        while let Some(reply) = read_message(&mut output).unwrap() {
+            replies.push(reply);
+        }
+        (code, replies)
+    }
+
+    fn request(id: u64, method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+    }
+
+    fn notification(method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "method": method, "params": params })
+    }
+
+    fn open(text: &str) -> Value {
+        notification(
+            "textDocument/didOpen",
+            json!({ "textDocument": { "uri": URI, "languageId": "cake", "version": 1, "text": text } }),
+        )
+    }
+
+    fn at(line: u32, character: u32) -> Value {
+        json!({ "textDocument": { "uri": URI }, "position": { "line": line, "character": character } })
+    }
+
+    fn result(replies: &[Value], id: u64) -> &Value {
+        &replies.iter().find(|reply| reply["id"] == id).unwrap()["result"]
+    }
+
+    // Runs `requests` against a server that has `text` open, with a proper
+    // shutdown.
+    fn with_document(text: &str, requests: &[Value]) -> Vec<Value> {
+        let mut messages = vec![request(0, "initialize", json!({})), open(text)];
+        messages.extend_from_slice(requests);
+        messages.push(request(99, "shutdown", Value::Null));
+        messages.push(notification("exit", Value::Null));
+        let (code, replies) = session(&messages);
+        assert_eq!(code, 0);
+        replies
+    }
+
+    #[test]
+    fn test_lifecycle() {
+        let (code, replies) = session(&[
+            request(1, "initialize", json!({})),
+            notification("initialized", json!({})),
+            request(2, "nope", json!({})),
+            request(3, "shutdown", Value::Null),
+            notification("exit", Value::Null),
+        ]);
+        assert_eq!(code, 0);
+        assert_eq!(
+            result(&replies, 1)["capabilities"]["hoverProvider"],
+            json!(true)
+        );
+        assert_eq!(replies[1]["error"]["code"], json!(-32601));
+        assert_eq!(result(&replies, 3), &Value::Null);
+
+        // Exiting without a shutdown is an error.
+        let (code, _) = session(&[notification("exit", Value::Null)]);
+        assert_eq!(code, 1);
+    }
+
+    #[test]
+    fn test_message_limits() {
+        // Refused before anything is allocated for the body.
+        let header = format!("Content-Length: {}\r\n\r\n", MAX_MESSAGE_LEN + 1);
+        let err = read_message(&mut Cursor::new(header)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "message of {} bytes exceeds the maximum of {}",
+                MAX_MESSAGE_LEN + 1,
+                MAX_MESSAGE_LEN
+            )
+        );
+        let header = format!("Content-Length: 2{}\r\n\r\n", " ".repeat(2000));
+        let err = read_message(&mut Cursor::new(header)).unwrap_err();
+        assert_eq!(err.to_string(), "header line too long or cut short");
+
+        let input = format!("Content-Length: {}\r\n\r\n", usize::MAX);
+        assert_eq!(serve(Cursor::new(input), Vec::new()), 1);
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let replies = with_document(
+            "let a = 1;\nlet b = ;\na;\n",
+            &[
+                notification(
+                    "textDocument/didChange",
+                    json!({ "textDocument": { "uri": URI, "version": 2 }, "contentChanges": [{ "text": "let a = 1; a;" }] }),
+                ),
+                notification(
+                    "textDocument/didClose",
+                    json!({ "textDocument": { "uri": URI } }),
+                ),
+            ],
+        );
+        let diagnostics: Vec<&Value> = replies
+            .iter()
+            .filter(|reply| reply["method"] == "textDocument/publishDiagnostics")
+            .map(|reply| &reply["params"]["diagnostics"])
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [
+                &json!([{
+                    "range": { "start": { "line": 1, "character": 8 }, "end": { "line": 1, "character": 9 } },
+                    "severity": 1,
+                    "source": "cake",
+                    "message": "unexpected token: Semicolon",
+                }]),
+                &json!([]),
+                &json!([]),
+            ]
+        );
+
+        // Resolver findings are warnings, except for undefined names.
+        let replies = with_document("let f = func(x) { return 1; y; };\nf(1);\n", &[]);
+        let diagnostics = &replies[1]["params"]["diagnostics"];
+        let found: Vec<(&str, u64, bool)> = diagnostics
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|d| {
+                (
+                    d["message"].as_str().unwrap(),
+                    d["severity"].as_u64().unwrap(),
+                    d.get("tags").is_some(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("unused binding: x", 2, true),
+                ("unreachable code", 2, true),
+                ("undefined identifier: y", 1, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_definition_and_hover() {
+        let text = "\
+let n = 10;
+let add = func(a, b) { a + b + n; };
+let even = func(x) { if (x == 0) { true; } else { odd(x - 1); } };
+let odd = func(x) { if (x == 0) { false; } else { even(x - 1); } };
+let s = \"é\" + \"x\";
+add(n, 1);
+ok(s);
+let x = add(1, 2);
+let inc = func(i: int) { i; };
+";
+        let replies = with_document(
+            text,
+            &[
+                // `n` inside `add` and the `a` param.
+                request(1, "textDocument/definition", at(1, 31)),
+                request(2, "textDocument/definition", at(1, 23)),
+                // `odd` is used before its `let`.
+                request(3, "textDocument/definition", at(2, 51)),
+                request(4, "textDocument/hover", at(5, 1)),
+                request(5, "textDocument/hover", at(4, 4)),
+                request(6, "textDocument/hover", at(6, 0)),
+                request(7, "textDocument/hover", at(1, 15)),
+                request(8, "textDocument/definition", at(6, 0)),
+                // A call's result and an annotated param.
+                request(9, "textDocument/hover", at(7, 4)),
+                request(10, "textDocument/hover", at(8, 15)),
+            ],
+        );
+        let range = |line: u32, start: u32, end: u32| json!({ "start": { "line": line, "character": start }, "end": { "line": line, "character": end } });
+        assert_eq!(
+            result(&replies, 1),
+            &json!({ "uri": URI, "range": range(0, 4, 5) })
+        );
+        assert_eq!(result(&replies, 2)["range"], range(1, 15, 16));
+        assert_eq!(result(&replies, 3)["range"], range(3, 4, 7));
+        let hover = |id| result(&replies, id)["contents"]["value"].clone();
+        assert_eq!(
+            hover(4),
+            json!("```cake\nlet add: func(int, int) -> int\n```")
+        );
+        assert_eq!(hover(5), json!("```cake\nlet s: str\n```"));
+        assert_eq!(hover(6), json!("```cake\nbuiltin ok\n```"));
+        assert_eq!(hover(7), json!("```cake\nparam a: int\n```"));
+        assert_eq!(hover(9), json!("```cake\nlet x: int\n```"));
+        assert_eq!(hover(10), json!("```cake\nparam i: int\n```"));
+        assert_eq!(result(&replies, 6)["range"], range(6, 0, 2));
+        assert_eq!(result(&replies, 8), &Value::Null);
+    }
+
+    #[test]
+    fn test_symbols_and_completion() {
+        let text = "\
+let total = 0;
+let outer = func(x) {
+    let inner = x * 2;
+    inner;
+};
+";
+        let replies = with_document(
+            text,
+            &[
+                request(
+                    1,
+                    "textDocument/documentSymbol",
+                    json!({ "textDocument": { "uri": URI } }),
+                ),
+                request(2, "textDocument/completion", at(3, 4)),
+                request(3, "textDocument/completion", at(5, 0)),
+            ],
+        );
+        let symbols = result(&replies, 1);
+        let names = |symbols: &Value| -> Vec<(String, u64)> {
+            symbols
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|s| {
+                    (
+                        s["name"].as_str().unwrap().to_string(),
+                        s["kind"].as_u64().unwrap(),
+                    )
+                })
+                .collect()
+        };
+        assert_eq!(
+            names(symbols),
+            [(String::from("total"), 13), (String::from("outer"), 12)]
+        );
+        assert_eq!(
+            names(&symbols[1]["children"]),
+            [(String::from("inner"), 13)]
+        );
+        assert_eq!(symbols[0]["detail"], json!("int"));
+
+        let labels = |id| -> Vec<String> {
+            result(&replies, id)
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|item| item["label"].as_str().unwrap().to_string())
+                .collect()
+        };
+        let inside = labels(2);
+        assert_eq!(inside[..4], ["inner", "x", "outer", "total"]);
+        assert!(inside.contains(&String::from("unwrap")));
+        assert!(inside.contains(&String::from("let")));
+        let outside = labels(3);
+        assert_eq!(outside[..2], ["outer", "total"]);
+    }
+}
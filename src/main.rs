@@ -1,11 +1,6 @@
-#![allow(dead_code)]
-
-mod ast;
-mod lexer;
-mod parser;
-mod repl;
-mod token;
+use cake_interpreter::cli;
 
 fn main() {
-    repl::repl();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    std::process::exit(cli::run(&args));
 }
@@ -0,0 +1,83 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::resolver::Slot;
+
+use super::Object;
+
+pub type Env = Rc<RefCell<Environment>>;
+
+#[derive(Debug, Default)]
+pub struct Environment {
+    store: HashMap<String, Object>,
+    // Bindings the resolver gave a slot, unset until their `let` runs.
+    slots: Vec<Option<Object>>,
+    outer: Option<Env>,
+}
+
+impl Environment {
+    pub fn new() -> Env {
+        Rc::new(RefCell::new(Self::default()))
+    }
+
+    pub fn new_enclosed(outer: Env) -> Env {
+        Rc::new(RefCell::new(Self {
+            store: HashMap::new(),
+            slots: Vec::new(),
+            outer: Some(outer),
+        }))
+    }
+
+    pub fn get(&self, name: &str) -> Option<Object> {
+        match self.store.get(name) {
+            Some(obj) => Some(obj.clone()),
+            None => self
+                .outer
+                .as_ref()
+                .and_then(|outer| outer.borrow().get(name)),
+        }
+    }
+
+    pub fn get_slot(&self, slot: Slot) -> Option<Object> {
+        if slot.depth == 0 {
+            return self.slots.get(slot.index).cloned().flatten();
+        }
+        let outer = Slot {
+            depth: slot.depth - 1,
+            index: slot.index,
+        };
+        self.outer
+            .as_ref()
+            .and_then(|env| env.borrow().get_slot(outer))
+    }
+
+    pub fn set_slot(&mut self, index: usize, value: Object) {
+        if index >= self.slots.len() {
+            self.slots.resize(index + 1, None);
+        }
+        self.slots[index] = Some(value);
+    }
+
+    // Bindings of this scope only, sorted by name.
+    pub fn bindings(&self) -> Vec<(String, Object)> {
+        let mut bindings: Vec<(String, Object)> = self
+            .store
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        bindings.sort_by(|a, b| a.0.cmp(&b.0));
+        bindings
+    }
+
+    pub(super) fn outer(&self) -> Option<&Env> {
+        self.outer.as_ref()
+    }
+
+    // Every value bound in this scope.
+    pub(super) fn values(&self) -> impl Iterator<Item = &Object> {
+        self.store.values().chain(self.slots.iter().flatten())
+    }
+
+    pub fn set(&mut self, name: &str, value: Object) {
+        self.store.insert(name.to_string(), value);
+    }
+}
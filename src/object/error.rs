@@ -0,0 +1,94 @@
+use std::fmt::Display;
+
+use crate::token::Span;
+
+// Only the innermost frames are kept in a trace, deep recursion would
+// otherwise produce thousands of identical lines.
+pub const MAX_TRACE_FRAMES: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    TypeMismatch,
+    UnknownOperator,
+    UnknownIdentifier,
+    NotCallable,
+    WrongArity,
+    DivisionByZero,
+    IndexOutOfBounds,
+    Illegal,
+    StackOverflow,
+    // An `import` whose file cannot be found, read, parsed or evaluated.
+    Import,
+    // Raised by a `throw` of anything but a caught error.
+    Thrown,
+    // One of the evaluator's `Limits`. Cannot be caught.
+    LimitExceeded,
+    // A `match` none of whose arms apply, or a `let` whose value does not
+    // have the shape of its pattern.
+    NoMatch,
+}
+
+// An active Cake function call: the function's name, or `<anonymous>`, and
+// where it was called from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub function: String,
+    pub call_site: Span,
+}
+
+impl Display for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (called at {})", self.function, self.call_site)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeError {
+    pub kind: ErrorKind,
+    pub message: String,
+    pub span: Option<Span>,
+    // Innermost frame first.
+    pub trace: Vec<Frame>,
+    pub omitted_frames: usize,
+}
+
+impl RuntimeError {
+    pub fn new(kind: ErrorKind, message: String) -> Self {
+        Self {
+            kind,
+            message,
+            span: None,
+            trace: Vec::new(),
+            omitted_frames: 0,
+        }
+    }
+
+    // Records where the error happened. `frames` is the evaluator's call
+    // stack, outermost first.
+    pub fn locate(&mut self, span: Span, frames: &[Frame]) {
+        self.span = Some(span);
+        self.trace = frames
+            .iter()
+            .rev()
+            .take(MAX_TRACE_FRAMES)
+            .cloned()
+            .collect();
+        self.omitted_frames = frames.len().saturating_sub(MAX_TRACE_FRAMES);
+    }
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}: {}", self.kind, self.message)?;
+        if let Some(span) = self.span {
+            write!(f, " at {}", span)?;
+        }
+        for frame in &self.trace {
+            write!(f, "\n    in {}", frame)?;
+        }
+        if self.omitted_frames > 0 {
+            write!(f, "\n    ... {} more frames", self.omitted_frames)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,261 @@
+//! Cycle collection. Values are reference counted, which frees everything
+//! but cycles. Every cycle goes through an environment: a closure holds the
+//! environment it was created in, which can hold the closure, as in
+//! `let f = func() { f(); };`.
+//!
+//! The collector traces the values reachable from the environments the
+//! evaluator created, and subtracts the references found along the way from
+//! their reference counts. What is left are references from outside: the
+//! host's, or those of the evaluator while it runs. Everything not reachable
+//! from a value referenced from outside is garbage, and the environments in
+//! it are cleared to break its cycles.
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
+    mem,
+    rc::{Rc, Weak},
+};
+
+use super::{
+    environment::{Env, Environment},
+    Function, HashKey, Module, Object,
+};
+
+#[derive(Debug, Clone)]
+pub struct GcConfig {
+    // Collect when enough environments were created since the last
+    // collection. `gc()` collects either way.
+    pub automatic: bool,
+    // The least number of environments created between two automatic
+    // collections.
+    pub threshold: usize,
+    // Also wait for this percentage of the environments that survived the
+    // last collection, so that collections, which take time proportional to
+    // the live environments, get rarer as there are more of them.
+    pub growth: usize,
+}
+
+impl Default for GcConfig {
+    fn default() -> Self {
+        Self {
+            automatic: true,
+            threshold: 1000,
+            growth: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GcStats {
+    pub collections: usize,
+    // Environments alive after the last collection, or pruning.
+    pub live: usize,
+    // Environments freed by all collections.
+    pub freed: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct Heap {
+    // Every environment that may be part of a cycle. Dropped ones are
+    // removed at the next collection.
+    envs: Vec<Weak<RefCell<Environment>>>,
+    created: usize,
+    stats: GcStats,
+}
+
+impl Heap {
+    pub fn stats(&self) -> GcStats {
+        self.stats.clone()
+    }
+
+    // Tracks a new environment, first collecting when it is time to.
+    pub fn track(&mut self, env: &Env, config: &GcConfig) {
+        self.created += 1;
+        let wait = config
+            .threshold
+            .max(self.stats.live.saturating_mul(config.growth) / 100);
+        if self.created >= wait {
+            if config.automatic {
+                self.collect();
+            } else {
+                self.prune();
+            }
+        }
+        self.envs.push(Rc::downgrade(env));
+    }
+
+    // Tracks an environment created outside the evaluator, unless it already
+    // is.
+    pub fn track_root(&mut self, env: &Env) {
+        let weak = Rc::downgrade(env);
+        if !self.envs.iter().any(|tracked| tracked.ptr_eq(&weak)) {
+            self.envs.push(weak);
+        }
+    }
+
+    fn prune(&mut self) {
+        self.envs.retain(|env| env.strong_count() > 0);
+        self.stats.live = self.envs.len();
+        self.created = 0;
+    }
+
+    // Frees the cycles no longer reachable, and returns the number of
+    // environments freed.
+    pub fn collect(&mut self) -> usize {
+        let roots: Vec<Env> = self.envs.iter().filter_map(Weak::upgrade).collect();
+        let mut graph = Graph::default();
+        for env in roots {
+            graph.discover(Node::Env(env));
+        }
+        let garbage = graph.garbage();
+        let freed = garbage.len();
+
+        // Cleared before anything is dropped: dropping what they held can
+        // drop environments of the graph, which must not be borrowed then.
+        let contents: Vec<Environment> = garbage
+            .iter()
+            .map(|env| mem::take(&mut *env.borrow_mut()))
+            .collect();
+        drop(graph);
+        drop(garbage);
+        drop(contents);
+
+        self.stats.collections += 1;
+        self.stats.freed += freed;
+        self.prune();
+        freed
+    }
+}
+
+// A value that holds references to others.
+enum Node {
+    Env(Env),
+    Function(Rc<Function>),
+    Array(Rc<Vec<Object>>),
+    Hash(Rc<BTreeMap<HashKey, Object>>),
+    Module(Rc<Module>),
+}
+
+impl Node {
+    fn key(&self) -> usize {
+        match self {
+            Node::Env(env) => Rc::as_ptr(env) as *const () as usize,
+            Node::Function(func) => Rc::as_ptr(func) as *const () as usize,
+            Node::Array(array) => Rc::as_ptr(array) as *const () as usize,
+            Node::Hash(hash) => Rc::as_ptr(hash) as *const () as usize,
+            Node::Module(module) => Rc::as_ptr(module) as *const () as usize,
+        }
+    }
+
+    fn strong_count(&self) -> usize {
+        match self {
+            Node::Env(env) => Rc::strong_count(env),
+            Node::Function(func) => Rc::strong_count(func),
+            Node::Array(array) => Rc::strong_count(array),
+            Node::Hash(hash) => Rc::strong_count(hash),
+            Node::Module(module) => Rc::strong_count(module),
+        }
+    }
+
+    // Calls `f` once for each reference this node holds.
+    fn children(&self, f: &mut dyn FnMut(Node)) {
+        match self {
+            Node::Env(env) => {
+                let env = env.borrow();
+                if let Some(outer) = env.outer() {
+                    f(Node::Env(outer.clone()));
+                }
+                for value in env.values() {
+                    references(value, f);
+                }
+            }
+            Node::Function(func) => f(Node::Env(func.env.clone())),
+            Node::Array(elements) => elements.iter().for_each(|x| references(x, f)),
+            Node::Hash(entries) => entries.values().for_each(|x| references(x, f)),
+            Node::Module(module) => module.exports.iter().for_each(|(_, x)| references(x, f)),
+        }
+    }
+}
+
+// The nodes a value holds references to.
+fn references(value: &Object, f: &mut dyn FnMut(Node)) {
+    match value {
+        Object::Function(func) => f(Node::Function(func.clone())),
+        Object::Array(elements) => f(Node::Array(elements.clone())),
+        Object::Hash(entries) => f(Node::Hash(entries.clone())),
+        Object::Module(module) => f(Node::Module(module.clone())),
+        Object::TailCall(func, args, _) => {
+            f(Node::Function(func.clone()));
+            args.iter().for_each(|x| references(x, f));
+        }
+        Object::Return(value) | Object::Ok(value) | Object::Err(value) => references(value, f),
+        _ => {}
+    }
+}
+
+#[derive(Default)]
+struct Graph {
+    // Each node reachable from the tracked environments, held once.
+    nodes: HashMap<usize, Node>,
+}
+
+impl Graph {
+    fn discover(&mut self, node: Node) {
+        let mut pending = vec![node];
+        while let Some(node) = pending.pop() {
+            if self.nodes.contains_key(&node.key()) {
+                continue;
+            }
+            node.children(&mut |child| {
+                if !self.nodes.contains_key(&child.key()) {
+                    pending.push(child);
+                }
+            });
+            self.nodes.insert(node.key(), node);
+        }
+    }
+
+    // The environments only referenced from within the graph, by nodes
+    // that are themselves only referenced from within the graph.
+    fn garbage(&self) -> Vec<Env> {
+        // References from outside: all of them, less the graph's own copy,
+        // less those held by other nodes. Counts are read before `children`
+        // makes copies of its own.
+        let mut outside: HashMap<usize, usize> = self
+            .nodes
+            .iter()
+            .map(|(key, node)| (*key, node.strong_count() - 1))
+            .collect();
+        for node in self.nodes.values() {
+            node.children(&mut |child| {
+                if let Some(count) = outside.get_mut(&child.key()) {
+                    *count = count.saturating_sub(1);
+                }
+            });
+        }
+
+        let mut reachable: Vec<usize> = outside
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .map(|(key, _)| *key)
+            .collect();
+        let mut marked: HashSet<usize> = reachable.iter().copied().collect();
+        while let Some(key) = reachable.pop() {
+            self.nodes[&key].children(&mut |child| {
+                if marked.insert(child.key()) {
+                    reachable.push(child.key());
+                }
+            });
+        }
+
+        self.nodes
+            .iter()
+            .filter(|(key, _)| !marked.contains(key))
+            .filter_map(|(_, node)| match node {
+                Node::Env(env) => Some(env.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+}
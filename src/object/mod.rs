@@ -0,0 +1,168 @@
+use std::{collections::BTreeMap, fmt::Display, rc::Rc};
+
+use environment::Env;
+use error::RuntimeError;
+
+use crate::evaluator::Evaluator;
+
+use crate::{
+    ast::{types::Param, Node, Statement},
+    resolver::Slots,
+    token::Span,
+};
+
+pub mod environment;
+pub mod error;
+pub mod gc;
+
+#[derive(Debug, Clone)]
+pub struct Function {
+    // Name of the `let` binding the literal was assigned to, if any.
+    pub name: Option<String>,
+    pub params: Vec<Param>,
+    pub body: Statement,
+    pub env: Env,
+    // Where the body's locals live, from the program the literal is in.
+    pub slots: Rc<Slots>,
+}
+
+// The exports of an imported file, in the order they are defined.
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub path: String,
+    pub exports: Vec<(String, Object)>,
+}
+
+pub type BuiltinFn = fn(&mut Evaluator, Vec<Object>) -> Object;
+
+#[derive(Debug, Clone)]
+pub struct Builtin {
+    pub name: &'static str,
+    pub func: BuiltinFn,
+}
+
+// The values a hash can be keyed by.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HashKey {
+    Int(i64),
+    Bool(bool),
+    String(String),
+}
+
+impl HashKey {
+    pub fn from_object(value: &Object) -> Option<Self> {
+        match value {
+            Object::Int(i) => Some(HashKey::Int(*i)),
+            Object::Bool(b) => Some(HashKey::Bool(*b)),
+            Object::String(s) => Some(HashKey::String(s.clone())),
+            _ => None,
+        }
+    }
+
+    pub fn to_object(&self) -> Object {
+        match self {
+            HashKey::Int(i) => Object::Int(*i),
+            HashKey::Bool(b) => Object::Bool(*b),
+            HashKey::String(s) => Object::String(s.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Object {
+    Int(i64),
+    Bool(bool),
+    String(String),
+    Array(Rc<Vec<Object>>),
+    // Kept sorted by key, which is the order they print in.
+    Hash(Rc<BTreeMap<HashKey, Object>>),
+    Null,
+    Return(Box<Object>),
+    Function(Rc<Function>),
+    Builtin(Builtin),
+    // Result values built by the `ok` and `err` builtins.
+    Ok(Box<Object>),
+    Err(Box<Object>),
+    // A call in tail position, handed back to the caller's trampoline instead
+    // of being evaluated on a fresh Rust stack frame.
+    TailCall(Rc<Function>, Vec<Object>, Span),
+    Error(RuntimeError),
+    // An error caught by `try`/`catch`, usable as an ordinary value.
+    ErrorValue(Rc<RuntimeError>),
+    Module(Rc<Module>),
+}
+
+impl Object {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Object::Int(_) => "INTEGER",
+            Object::Bool(_) => "BOOLEAN",
+            Object::String(_) => "STRING",
+            Object::Array(_) => "ARRAY",
+            Object::Hash(_) => "HASH",
+            Object::Null => "NULL",
+            Object::Return(_) => "RETURN",
+            Object::Function(_) => "FUNCTION",
+            Object::Builtin(_) => "BUILTIN",
+            Object::Ok(_) | Object::Err(_) => "RESULT",
+            Object::TailCall(_, _, _) => "TAIL_CALL",
+            Object::Error(_) | Object::ErrorValue(_) => "ERROR",
+            Object::Module(_) => "MODULE",
+        }
+    }
+
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Object::Null | Object::Bool(false))
+    }
+
+    // Errors and returns, including those that are tail calls, interrupt
+    // the evaluation of the enclosing expressions and statements until they
+    // reach a handler or a function boundary.
+    pub fn is_abrupt(&self) -> bool {
+        matches!(
+            self,
+            Object::Error(_) | Object::Return(_) | Object::TailCall(_, _, _)
+        )
+    }
+
+    // Display form used inside containers, where strings are quoted.
+    pub fn repr(&self) -> String {
+        match self {
+            Object::String(s) => format!("{:?}", s),
+            x => x.to_string(),
+        }
+    }
+}
+
+impl Display for Object {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Object::Int(i) => write!(f, "{}", i),
+            Object::Bool(b) => write!(f, "{}", b),
+            Object::String(s) => write!(f, "{}", s),
+            Object::Array(elements) => {
+                let elements = elements.iter().map(|x| x.repr()).collect::<Vec<String>>();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            Object::Hash(entries) => {
+                let entries = entries
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key.to_object().repr(), value.repr()))
+                    .collect::<Vec<String>>();
+                write!(f, "{{{}}}", entries.join(", "))
+            }
+            Object::Null => write!(f, "null"),
+            Object::Return(value) => write!(f, "{}", value),
+            Object::Function(func) => {
+                write!(f, "Func{} {}", func.params.literal(), func.body.literal())
+            }
+            Object::Builtin(builtin) => write!(f, "builtin({})", builtin.name),
+            Object::Ok(value) => write!(f, "ok({})", value.repr()),
+            Object::Err(value) => write!(f, "err({})", value.repr()),
+            Object::TailCall(func, _, _) => write!(f, "TailCall(Func{})", func.params.literal()),
+            Object::Error(err) => write!(f, "{}", err),
+            Object::ErrorValue(err) => write!(f, "{}", err),
+            Object::Module(module) => write!(f, "module({})", module.path),
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use crate::{
+    ast::{
+        if_else::IfExpression,
+        interpolated::Fragment,
+        match_expr::Arm,
+        operators::{InfixOperator, PrefixOperator},
+        try_catch::{Catch, Try},
+        Expr, Program, Statement,
+    },
+    evaluator::{eval_infix, eval_prefix},
+    object::Object,
+    token::Span,
+};
+
+mod test;
+
+// Passes to run. They never change what a program does: anything that would
+// fail at runtime, like `1 / 0`, is left for the evaluator to report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptimizeConfig {
+    // Evaluates operators whose operands are literals: `3 + 4` is `7`.
+    pub fold: bool,
+    // Keeps only the branch an `if` on a constant condition takes.
+    pub dead_branches: bool,
+    // `!!x` is `x` where that gives the same value.
+    pub double_negation: bool,
+}
+
+impl Default for OptimizeConfig {
+    fn default() -> Self {
+        Self {
+            fold: true,
+            dead_branches: true,
+            double_negation: true,
+        }
+    }
+}
+
+pub fn optimize(program: Program, config: &OptimizeConfig) -> Program {
+    let optimizer = Optimizer { config };
+    Program {
+        statements: optimizer.statements(program.statements),
+    }
+}
+
+struct Optimizer<'a> {
+    config: &'a OptimizeConfig,
+}
+
+impl Optimizer<'_> {
+    fn statements(&self, statements: Vec<Statement>) -> Vec<Statement> {
+        statements
+            .into_iter()
+            .map(|statement| self.statement(statement))
+            .collect()
+    }
+
+    fn statement(&self, statement: Statement) -> Statement {
+        match statement {
+            Statement::Let(mut let_stat) => {
+                let_stat.expr = self.expr(let_stat.expr);
+                Statement::Let(let_stat)
+            }
+            Statement::Return(mut return_stat) => {
+                return_stat.return_expr = return_stat.return_expr.map(|expr| self.expr(expr));
+                Statement::Return(return_stat)
+            }
+            Statement::Throw(mut throw_stat) => {
+                throw_stat.expr = self.expr(throw_stat.expr);
+                Statement::Throw(throw_stat)
+            }
+            Statement::Expression(Expr::If(if_expr)) => {
+                let if_expr = self.if_expr(*if_expr);
+                self.taken(&if_expr)
+                    .unwrap_or_else(|| Statement::Expression(Expr::If(Box::new(if_expr))))
+            }
+            Statement::Expression(expr) => Statement::Expression(self.expr(expr)),
+            Statement::BlockStatement(block) => Statement::BlockStatement(self.statements(block)),
+            Statement::Try(try_stat) => Statement::Try(Try {
+                body: Box::new(self.statement(*try_stat.body)),
+                catch: try_stat.catch.map(|catch| Catch {
+                    ident: catch.ident,
+                    body: Box::new(self.statement(*catch.body)),
+                }),
+                finally: try_stat
+                    .finally
+                    .map(|finally| Box::new(self.statement(*finally))),
+                span: try_stat.span,
+            }),
+            statement @ (Statement::Import(_) | Statement::Comment(_) | Statement::Illegal(_)) => {
+                statement
+            }
+        }
+    }
+
+    fn if_expr(&self, if_expr: IfExpression) -> IfExpression {
+        IfExpression {
+            condition: self.condition(if_expr.condition),
+            then: Box::new(self.statement(*if_expr.then)),
+            else_: if_expr.else_.map(|else_| Box::new(self.statement(*else_))),
+            span: if_expr.span,
+        }
+    }
+
+    // What an `if` on a constant condition comes down to. Blocks share the
+    // scope around them, so a branch can stand in for the whole `if`, and an
+    // empty block is `null` like an `if` that runs nothing.
+    fn taken(&self, if_expr: &IfExpression) -> Option<Statement> {
+        if !self.config.dead_branches {
+            return None;
+        }
+        match (truthiness(&if_expr.condition)?, &if_expr.else_) {
+            (true, _) => Some(*if_expr.then.clone()),
+            (false, Some(else_)) => Some(*else_.clone()),
+            (false, None) => Some(Statement::BlockStatement(vec![])),
+        }
+    }
+
+    // Only the truthiness of a condition matters, so `!!x` is `x` there
+    // whatever `x` is.
+    fn condition(&self, condition: Expr) -> Expr {
+        let condition = self.expr(condition);
+        if !self.config.double_negation {
+            return condition;
+        }
+        match condition {
+            Expr::Prefix(PrefixOperator::Bang, right, span) => match *right {
+                Expr::Prefix(PrefixOperator::Bang, inner, _) => *inner,
+                right => Expr::Prefix(PrefixOperator::Bang, Box::new(right), span),
+            },
+            condition => condition,
+        }
+    }
+
+    fn expr(&self, expr: Expr) -> Expr {
+        match expr {
+            Expr::Prefix(op, right, span) => {
+                let right = self.expr(*right);
+                if self.config.double_negation && op == PrefixOperator::Bang {
+                    if let Expr::Prefix(PrefixOperator::Bang, inner, _) = &right {
+                        if is_boolean(inner) {
+                            return *inner.clone();
+                        }
+                    }
+                }
+                if self.config.fold {
+                    if let Some(value) = literal_value(&right) {
+                        if let Some(folded) = to_literal(eval_prefix(&op, value)) {
+                            return folded;
+                        }
+                    }
+                }
+                Expr::Prefix(op, Box::new(right), span)
+            }
+            Expr::Infix(left, op, right, span) => {
+                let left = self.expr(*left);
+                let right = self.expr(*right);
+                if self.config.fold {
+                    if let (Some(l), Some(r)) = (literal_value(&left), literal_value(&right)) {
+                        if let Some(folded) = to_literal(eval_infix(&op, l, r)) {
+                            return folded;
+                        }
+                    }
+                }
+                Expr::Infix(Box::new(left), op, Box::new(right), span)
+            }
+            Expr::Function(params, ret, body) => {
+                Expr::Function(params, ret, Box::new(self.statement(*body)))
+            }
+            Expr::Call(func, args, span) => Expr::Call(
+                Box::new(self.expr(*func)),
+                args.into_iter().map(|arg| self.expr(arg)).collect(),
+                span,
+            ),
+            Expr::Array(elements) => {
+                Expr::Array(elements.into_iter().map(|e| self.expr(e)).collect())
+            }
+            Expr::Index(left, index, span) => Expr::Index(
+                Box::new(self.expr(*left)),
+                Box::new(self.expr(*index)),
+                span,
+            ),
+            Expr::Propagate(expr, span) => Expr::Propagate(Box::new(self.expr(*expr)), span),
+            Expr::Member(expr, name, span) => Expr::Member(Box::new(self.expr(*expr)), name, span),
+            Expr::Interpolated(fragments, span) => self.interpolated(fragments, span),
+            Expr::Hash(entries, span) => Expr::Hash(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (self.expr(key), self.expr(value)))
+                    .collect(),
+                span,
+            ),
+            // Where an expression is needed, only a branch of a single
+            // expression can stand in for the `if`.
+            Expr::If(if_expr) => {
+                let if_expr = self.if_expr(*if_expr);
+                match self.taken(&if_expr) {
+                    Some(Statement::BlockStatement(block)) => match block.as_slice() {
+                        [Statement::Expression(expr)] => expr.clone(),
+                        _ => Expr::If(Box::new(if_expr)),
+                    },
+                    _ => Expr::If(Box::new(if_expr)),
+                }
+            }
+            Expr::Match(subject, arms, span) => Expr::Match(
+                Box::new(self.expr(*subject)),
+                arms.into_iter()
+                    .map(|arm| Arm {
+                        pattern: arm.pattern,
+                        guard: arm.guard.map(|guard| self.condition(guard)),
+                        body: Box::new(self.statement(*arm.body)),
+                    })
+                    .collect(),
+                span,
+            ),
+            expr @ (Expr::Ident(_)
+            | Expr::Int(_)
+            | Expr::Bool(_)
+            | Expr::String(_)
+            | Expr::Illegal(_)) => expr,
+        }
+    }
+
+    // Literals are embedded ahead of time, and a string with nothing else
+    // left in it becomes a plain one.
+    fn interpolated(&self, fragments: Vec<Fragment>, span: Span) -> Expr {
+        let mut res: Vec<Fragment> = Vec::new();
+        for fragment in fragments {
+            let fragment = match fragment {
+                Fragment::Expr(expr) => {
+                    let expr = self.expr(expr);
+                    match literal_value(&expr) {
+                        Some(value) if self.config.fold => Fragment::Text(value.to_string()),
+                        _ => Fragment::Expr(expr),
+                    }
+                }
+                text => text,
+            };
+            match (res.last_mut(), fragment) {
+                (Some(Fragment::Text(last)), Fragment::Text(text)) => last.push_str(&text),
+                (_, Fragment::Text(text)) if text.is_empty() => {}
+                (_, fragment) => res.push(fragment),
+            }
+        }
+        match res.as_slice() {
+            [] => Expr::String(String::new()),
+            [Fragment::Text(text)] => Expr::String(text.clone()),
+            _ => Expr::Interpolated(res, span),
+        }
+    }
+}
+
+fn literal_value(expr: &Expr) -> Option<Object> {
+    match expr {
+        Expr::Int(i) => Some(Object::Int(*i)),
+        Expr::Bool(b) => Some(Object::Bool(*b)),
+        Expr::String(s) => Some(Object::String(s.clone())),
+        _ => None,
+    }
+}
+
+// Errors are not folded: they have to happen when the code runs, if it does.
+fn to_literal(value: Object) -> Option<Expr> {
+    match value {
+        Object::Int(i) => Some(Expr::Int(i)),
+        Object::Bool(b) => Some(Expr::Bool(b)),
+        Object::String(s) => Some(Expr::String(s)),
+        _ => None,
+    }
+}
+
+// Whether a condition is known before running, and which way it goes.
+fn truthiness(expr: &Expr) -> Option<bool> {
+    literal_value(expr).map(|value| value.is_truthy())
+}
+
+// Whether an expression always evaluates to a boolean, or fails.
+fn is_boolean(expr: &Expr) -> bool {
+    match expr {
+        Expr::Bool(_) | Expr::Prefix(PrefixOperator::Bang, _, _) => true,
+        Expr::Infix(_, op, _, _) => matches!(
+            op,
+            InfixOperator::Eq
+                | InfixOperator::NotEq
+                | InfixOperator::LessThan
+                | InfixOperator::GreaterThan
+        ),
+        _ => false,
+    }
+}
@@ -0,0 +1,140 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ast::Node,
+        evaluator::Evaluator,
+        lexer,
+        object::environment::Environment,
+        optimize::{optimize, OptimizeConfig},
+        parser::Parser,
+    };
+
+    // The statements of `input` once optimized, as literals.
+    fn optimized_with(input: &str, config: &OptimizeConfig) -> Vec<String> {
+        let mut parser = Parser::new(lexer::Lexer::new(input));
+        let program = parser.parse();
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+        optimize(program, config)
+            .statements
+            .iter()
+            .map(|s| s.literal())
+            .collect()
+    }
+
+    fn optimized(input: &str) -> Vec<String> {
+        optimized_with(input, &OptimizeConfig::default())
+    }
+
+    #[test]
+    fn test_fold() {
+        assert_eq!(
+            optimized("3+4*5 == 3*1+4*5; -(2 - 5); !0; \"a\" + \"b\"; 1 < 2; let x = 2 * 3 + y;"),
+            ["true", "3", "false", "\"ab\"", "true", "Let(x, (6 + y))"]
+        );
+        // What fails at runtime still does.
+        assert_eq!(
+            optimized("1 / 0; 1 + \"a\"; \"a\" - \"b\"; -true; 4 / 2;"),
+            ["(1 / 0)", "(1 + \"a\")", "(\"a\" - \"b\")", "(-true)", "2"]
+        );
+        // Inside functions, calls and arrays too.
+        assert_eq!(
+            optimized("let f = func(a) { return a * (2 + 2); }; f([1 + 1], 0 - 1);"),
+            ["Let(f, Func(a) { Return((a * 4)); })", "f([2],-1)"]
+        );
+    }
+
+    #[test]
+    fn test_fold_interpolation() {
+        assert_eq!(
+            optimized(r#""a${1 + 2}b${x}c${"d"}"; "${true}${""}";"#),
+            ["Interpolated(\"a3b\", x, \"cd\")", "\"true\""]
+        );
+    }
+
+    #[test]
+    fn test_dead_branches() {
+        assert_eq!(
+            optimized(
+                "if (1 < 2) { a; } else { b; }; if (false) { a; } else { b; }; \
+                 if (\"\") { a; }; if (false) { a; }; if (c) { a; };"
+            ),
+            ["{ a; }", "{ b; }", "{ a; }", "{ }", "If(c, { a; })"]
+        );
+        // Where a value is needed, a branch of a single expression replaces
+        // the `if`.
+        assert_eq!(
+            optimized("let x = if (1 > 2) { a } else { b }; let y = if (true) { f(); a };"),
+            ["Let(x, b)", "Let(y, If(true, { f(); a; }))"]
+        );
+        // Nested branches go too.
+        assert_eq!(
+            optimized("if (true) { if (1 == 2) { a; } else { b; } }"),
+            ["{ { b; }; }"]
+        );
+    }
+
+    #[test]
+    fn test_double_negation() {
+        assert_eq!(
+            optimized("!!(a == b); !!!a; !!a; if (!!a) { 1; }; if (!!!a) { 1; };"),
+            [
+                "(a == b)",
+                "(!a)",
+                "(!(!a))",
+                "If(a, { 1; })",
+                "If((!a), { 1; })"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_passes_toggle() {
+        let input = "if (!!(1 < 2)) { a; }";
+        let off = OptimizeConfig {
+            fold: false,
+            dead_branches: false,
+            double_negation: false,
+        };
+        assert_eq!(optimized_with(input, &off), ["If((!(!(1 < 2))), { a; })"]);
+        let fold = OptimizeConfig {
+            fold: true,
+            ..off.clone()
+        };
+        assert_eq!(optimized_with(input, &fold), ["If(true, { a; })"]);
+        let double_negation = OptimizeConfig {
+            double_negation: true,
+            ..off.clone()
+        };
+        assert_eq!(
+            optimized_with(input, &double_negation),
+            ["If((1 < 2), { a; })"]
+        );
+        let dead_branches = OptimizeConfig {
+            dead_branches: true,
+            ..off
+        };
+        assert_eq!(
+            optimized_with(input, &dead_branches),
+            ["If((!(!(1 < 2))), { a; })"]
+        );
+    }
+
+    #[test]
+    fn test_same_result() {
+        let inputs = [
+            "let x = 2 * 3; if (x > 5) { x + 1; } else { 0; }",
+            "let f = func(n) { if (!!true) { return n * (1 + 1); } }; f(21);",
+            "if (false) { 1; }",
+            "let a = [1 + 1, \"a\" + \"b\"]; a[1];",
+            "try { 1 / 0; } catch (e) { e[\"message\"]; }",
+        ];
+        for input in inputs {
+            let program = Parser::new(lexer::Lexer::new(input)).parse();
+            let optimized = Parser::new(lexer::Lexer::new(input)).parse();
+            let optimized = optimize(optimized, &OptimizeConfig::default());
+            let plain = Evaluator::default().eval(&program, &Environment::new());
+            let fast = Evaluator::default().eval(&optimized, &Environment::new());
+            assert_eq!(plain.to_string(), fast.to_string(), "{}", input);
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use std::fmt::Display;
+
+use crate::token::Span;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    // The token the parser could not make sense of.
+    pub span: Span,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
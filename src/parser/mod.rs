@@ -1,28 +1,55 @@
+use error::ParseError;
 use precedence::PREFIX;
 
 use crate::{
     ast::{
         self,
+        comment::Comment,
         ident::Ident,
         if_else::IfExpression,
+        import::Import,
+        interpolated::Fragment,
         let_stat::Let,
+        match_expr::Arm,
         operators::{lookup_infix, lookup_prefix},
+        pattern::Pattern,
         return_stat::Return,
+        throw_stat::Throw,
+        try_catch::{Catch, Try},
+        types::{Param, TypeExpr},
         Expr, Program, Statement,
     },
     lexer,
-    token::{self, Token},
+    token::{self, Span, Token},
 };
 
-mod precedence;
+pub mod error;
+pub mod precedence;
 mod test;
 
+// Deepest nesting of expressions and blocks the parser accepts; it recurses
+// once per level, so unbounded input would overflow the host stack.
+pub const MAX_NESTING: usize = 256;
+
 #[derive(Debug)]
 pub struct Parser {
     lexer: lexer::Lexer,
 
     current_token: token::Token,
     peek_token: token::Token,
+    current_span: Span,
+    peek_span: Span,
+    // Whether a line break separates the peek token from the current one,
+    // comments aside.
+    peek_newline: bool,
+
+    depth: usize,
+    // Identifiers read so far: the id of the next one.
+    idents: usize,
+    errors: Vec<ParseError>,
+    // Comments read ahead of the current token, waiting to be placed in
+    // the statement list that contains them.
+    comments: Vec<(Comment, Span)>,
 }
 
 impl Parser {
@@ -33,24 +60,89 @@ impl Parser {
         );
     }
 
-    pub fn new(mut lexer: lexer::Lexer) -> Self {
-        let current_token = lexer.next_token();
-        let peek_token = lexer.next_token();
-        Self {
+    pub fn new(lexer: lexer::Lexer) -> Self {
+        let mut parser = Self {
             lexer,
-            current_token,
-            peek_token,
-        }
+            current_token: Token::EOF,
+            peek_token: Token::EOF,
+            current_span: Span::default(),
+            peek_span: Span::default(),
+            peek_newline: false,
+            depth: 0,
+            idents: 0,
+            errors: vec![],
+            comments: vec![],
+        };
+        parser.next_token();
+        parser.next_token();
+        parser
+    }
+
+    pub fn errors(&self) -> &[ParseError] {
+        &self.errors
+    }
+
+    fn error(&mut self, message: String, span: Span) {
+        self.errors.push(ParseError { message, span });
+    }
+
+    // The identifier `name` at the current token, numbered in the order
+    // identifiers are read.
+    fn ident(&mut self, name: String) -> Ident {
+        self.idents += 1;
+        Ident::new(name, self.current_span, self.idents - 1)
     }
 
     pub fn next_token(&mut self) {
         self.current_token = self.peek_token.clone();
-        self.peek_token = self.lexer.next_token();
+        self.current_span = self.peek_span;
+        self.peek_newline = false;
+        loop {
+            self.peek_token = self.lexer.next_token();
+            self.peek_span = self.lexer.span();
+            self.peek_newline |= self.lexer.newline();
+            let Token::Comment(text) = &self.peek_token else {
+                break;
+            };
+            let comment = Comment {
+                // Trailing whitespace, including the `\r` of a CRLF line
+                // ending, is not part of the comment.
+                text: text.iter().collect::<String>().trim_end().to_string(),
+                trailing: self.current_token != Token::EOF
+                    && self.current_span.line == self.peek_span.line,
+            };
+            self.comments.push((comment, self.peek_span));
+        }
+    }
+
+    // Moves the pending comments that come before the current token into
+    // `statements`. A comment only trails code that is still there to print:
+    // one after a stray `;` at the start of the program, or after another
+    // comment, stands on its own.
+    fn take_comments(&mut self, statements: &mut Vec<Statement>) {
+        let start = self.current_span.start;
+        let at_end = self.current_token == Token::EOF;
+        let count = self
+            .comments
+            .iter()
+            .take_while(|(_, span)| at_end || span.start < start)
+            .count();
+        for (mut comment, _) in self.comments.drain(..count) {
+            let attached = match statements.last() {
+                Some(Statement::Comment(_)) => false,
+                Some(_) => true,
+                // The opening `{` of a block.
+                None => self.depth > 0,
+            };
+            comment.trailing &= attached;
+            statements.push(Statement::Comment(comment));
+        }
     }
 
     pub fn expect_peek(&mut self, token: Token) -> bool {
         if std::mem::discriminant(&self.peek_token) != std::mem::discriminant(&token) {
-            println!("Error: expected: {:?}, got: {:?}", token, self.peek_token);
+            let message = format!("expected: {:?}, got: {:?}", token, self.peek_token);
+            self.error(message, self.peek_span);
             return false;
         }
         self.next_token();
@@ -60,132 +152,350 @@ impl Parser {
     pub fn parse(&mut self) -> ast::Program {
         let mut statements = Vec::new();
         while Token::EOF != self.current_token.clone() {
-            if let Some(statement) = self.parse_statement() {
-                statements.push(statement);
+            self.take_comments(&mut statements);
+            let errors = self.errors.len();
+            match self.parse_statement() {
+                Some(statement) => statements.push(statement),
+                None if self.errors.len() > errors => self.synchronize(),
+                None => {}
             }
             self.next_token();
         }
+        self.take_comments(&mut statements);
         Program { statements }
     }
 
-    pub fn parse_statement(&mut self) -> Option<ast::Statement> {
-        match self.current_token.clone() {
-            Token::EOF => None,
-            Token::Let => {
-                if !self.expect_peek(Token::Ident(vec![])) {
-                    return None;
-                }
-
-                if let Token::Ident(a) = self.current_token.clone() {
-                    let name: String = a.into_iter().collect();
+    // Skips the rest of a statement that failed to parse, so that its
+    // remaining tokens aren't reported again as errors of their own.
+    fn synchronize(&mut self) {
+        while self.current_token != Token::Semicolon && self.current_token != Token::EOF {
+            self.next_token();
+        }
+    }
 
-                    if !self.expect_peek(Token::Assign) {
-                        return None;
-                    }
+    // Tracks recursion into nested expressions and blocks. Once the limit is
+    // hit the rest of the input is skipped so that the parse unwinds quickly
+    // with a single error.
+    fn enter(&mut self) -> bool {
+        if self.depth >= MAX_NESTING {
+            if self.current_token != Token::EOF {
+                let message = format!("maximum nesting depth of {} exceeded", MAX_NESTING);
+                self.error(message, self.current_span);
+                while self.current_token != Token::EOF {
                     self.next_token();
-                    let value = self.parse_expression(precedence::LOWEST);
+                }
+            }
+            return false;
+        }
+        self.depth += 1;
+        true
+    }
 
-                    if !self.expect_peek(Token::Semicolon) {
-                        return None;
-                    }
+    fn leave(&mut self) {
+        self.depth -= 1;
+    }
 
-                    return Some(Statement::Let(Let {
-                        ident: Ident { name },
-                        expr: value.unwrap(),
-                    }));
+    pub fn parse_statement(&mut self) -> Option<ast::Statement> {
+        if !self.enter() {
+            return None;
+        }
+        let res = self.parse_statement_inner();
+        self.leave();
+        res
+    }
+
+    fn parse_statement_inner(&mut self) -> Option<ast::Statement> {
+        match self.current_token.clone() {
+            // A stray `;` is an empty statement.
+            Token::EOF | Token::Semicolon => None,
+            Token::Let => self.parse_let(false),
+            Token::Export => {
+                // Only top level bindings make up the interface of a module.
+                if self.depth > 1 {
+                    let message = String::from("export is only allowed at the top level");
+                    self.error(message, self.current_span);
+                    return None;
                 }
-                return None;
+                if !self.expect_peek(Token::Let) {
+                    return None;
+                }
+                self.parse_let(true)
             }
+            Token::Import => self.parse_import(),
             Token::Return => {
+                let span = self.current_span;
+                // Unlike other statements, a `return` is complete on its
+                // own, so a line break ends it: its value must start on the
+                // same line.
+                if self.peek_newline
+                    || matches!(
+                        self.peek_token,
+                        Token::Semicolon | Token::RBrace | Token::EOF
+                    )
+                {
+                    self.expect_end(false);
+                    return Some(Statement::Return(Return {
+                        return_expr: None,
+                        span,
+                    }));
+                }
                 self.next_token();
-                if let Some(expr) = self.parse_expression(precedence::LOWEST) {
-                    if !self.expect_peek(Token::Semicolon) {
-                        return None;
-                    }
-                    return Some(Statement::Return(Return { return_expr: expr }));
+                let expr = self.parse_expression(precedence::LOWEST)?;
+                if !self.expect_end(ends_with_block(&expr)) {
+                    return None;
                 }
-                None
+                Some(Statement::Return(Return {
+                    return_expr: Some(expr),
+                    span,
+                }))
             }
-            Token::LBrace => {
+            Token::Throw => {
+                let span = self.current_span;
                 self.next_token();
-                let mut statements = Vec::new();
-                while Token::RBrace != self.current_token && Token::EOF != self.current_token {
-                    if let Some(statement) = self.parse_statement() {
-                        statements.push(statement);
-                    }
+                let expr = self.parse_expression(precedence::LOWEST)?;
+                if !self.expect_end(ends_with_block(&expr)) {
+                    return None;
+                }
+                Some(Statement::Throw(Throw { expr, span }))
+            }
+            Token::Try => self.parse_try(),
+            Token::LBrace => Some(self.parse_block()),
+            // Like a block, an `if` or a `match` ends the statement at its
+            // `}`: only in parentheses does it start a longer expression.
+            Token::If | Token::Match => {
+                let expr = self.parse_prefix()?;
+                if self.peek_token == Token::Semicolon {
                     self.next_token();
                 }
-                return Some(Statement::BlockStatement(statements));
+                Some(Statement::Expression(expr))
             }
-            Token::If => {
-                if !self.expect_peek(Token::LParen) {
+            _ => {
+                let expr = self.parse_expression(precedence::LOWEST)?;
+                if !self.expect_end(ends_with_block(&expr)) {
                     return None;
                 }
-                self.next_token();
-                let condition = self.parse_expression(precedence::LOWEST);
+                Some(Statement::Expression(expr))
+            }
+        }
+    }
 
-                if !self.expect_peek(Token::RParen) {
-                    return None;
-                }
+    // Moves past the `;` ending a statement, which can be left out where
+    // the statement is complete and
+    // - a line ends after it,
+    // - the `}` closing its block or the end of the input follows,
+    // - or, with `after_block`, it ends with a block of its own, like a
+    //   function literal.
+    // Statements are only complete once their expression is: a line break
+    // does not end `let a =` or `f(1,`, and an expression goes on past one
+    // when the next line starts with an operator, so `a\n+ b` is `a + b`.
+    // `(` and `[` are the exception, see `continues`.
+    fn expect_end(&mut self, after_block: bool) -> bool {
+        match self.peek_token {
+            Token::Semicolon => {
+                self.next_token();
+                true
+            }
+            Token::RBrace | Token::EOF => true,
+            _ if self.peek_newline || after_block => true,
+            _ => self.expect_peek(Token::Semicolon),
+        }
+    }
 
-                if !self.expect_peek(Token::LBrace) {
-                    return None;
-                }
+    // Whether the peek token continues the expression before it. A `(` or
+    // `[` that starts a line is not a call or an index: it starts the next
+    // statement, as it would after a `;`.
+    fn continues(&self) -> bool {
+        !(self.peek_newline && matches!(self.peek_token, Token::LParen | Token::LBracket))
+    }
 
-                let then = self.parse_statement();
+    // The larger statements get their own methods, which keeps the stack
+    // frame of `parse_statement_inner`, paid for on every level of nesting,
+    // small.
+    fn parse_let(&mut self, export: bool) -> Option<Statement> {
+        let span = self.current_span;
+        let pattern = if matches!(self.peek_token, Token::LBracket | Token::LBrace) {
+            self.next_token();
+            self.parse_pattern()?
+        } else {
+            if !self.expect_peek(Token::Ident(vec![])) {
+                return None;
+            }
+            match self.current_token.clone() {
+                Token::Ident(a) => Pattern::Binding(self.ident(a.into_iter().collect())),
+                _ => return None,
+            }
+        };
+        let ty = self.parse_annotation(Token::Colon)?;
 
-                if let None = then {
-                    return None;
-                }
+        if !self.expect_peek(Token::Assign) {
+            return None;
+        }
+        self.next_token();
+        let expr = self.parse_expression(precedence::LOWEST)?;
 
-                if self.peek_token != Token::Else {
-                    return Some(Statement::IfExpression(IfExpression {
-                        condition: condition.unwrap(),
-                        then: Box::new(then.unwrap()),
-                        else_: None,
-                    }));
-                }
-                self.next_token();
+        if !self.expect_end(ends_with_block(&expr)) {
+            return None;
+        }
+        Some(Statement::Let(Let {
+            pattern,
+            ty,
+            expr,
+            export,
+            span,
+        }))
+    }
 
-                if !self.expect_peek(Token::LBrace) {
-                    return None;
-                }
+    fn parse_import(&mut self) -> Option<Statement> {
+        let span = self.current_span;
+        if !self.expect_peek(Token::String(vec![])) {
+            return None;
+        }
+        let path = match self.current_token.clone() {
+            Token::String(path) => path.into_iter().collect(),
+            _ => return None,
+        };
+        if !self.expect_peek(Token::As) || !self.expect_peek(Token::Ident(vec![])) {
+            return None;
+        }
+        let alias = match self.current_token.clone() {
+            Token::Ident(a) => self.ident(a.into_iter().collect()),
+            _ => return None,
+        };
+        if !self.expect_end(false) {
+            return None;
+        }
+        Some(Statement::Import(Import { path, alias, span }))
+    }
 
-                let else_ = self.parse_statement();
+    fn parse_try(&mut self) -> Option<Statement> {
+        let span = self.current_span;
+        if !self.expect_peek(Token::LBrace) {
+            return None;
+        }
+        let body = Box::new(self.parse_statement()?);
 
-                if let None = else_ {
-                    return None;
-                }
+        let catch = if self.peek_token == Token::Catch {
+            self.next_token();
+            if !self.expect_peek(Token::LParen) || !self.expect_peek(Token::Ident(vec![])) {
+                return None;
+            }
+            let ident = match self.current_token.clone() {
+                Token::Ident(a) => self.ident(a.into_iter().collect()),
+                _ => return None,
+            };
+            if !self.expect_peek(Token::RParen) || !self.expect_peek(Token::LBrace) {
+                return None;
+            }
+            let body = Box::new(self.parse_statement()?);
+            Some(Catch { ident, body })
+        } else {
+            None
+        };
 
-                Some(Statement::IfExpression(IfExpression {
-                    condition: condition.unwrap(),
-                    then: Box::new(then.unwrap()),
-                    else_: Some(Box::new(else_.unwrap())),
-                }))
+        let finally = if self.peek_token == Token::Finally {
+            self.next_token();
+            if !self.expect_peek(Token::LBrace) {
+                return None;
             }
-            _ => {
-                if let Some(expr) = self.parse_expression(precedence::LOWEST) {
-                    if !self.expect_peek(Token::Semicolon) {
-                        return None;
-                    }
-                    Some(Statement::Expression(expr))
-                } else {
-                    None
-                }
+            Some(Box::new(self.parse_statement()?))
+        } else {
+            None
+        };
+
+        if catch.is_none() && finally.is_none() {
+            let message = format!("expected: Catch or Finally, got: {:?}", self.peek_token);
+            self.error(message, self.peek_span);
+            return None;
+        }
+        Some(Statement::Try(Try {
+            body,
+            catch,
+            finally,
+            span,
+        }))
+    }
+
+    fn parse_block(&mut self) -> Statement {
+        self.next_token();
+        let mut statements = Vec::new();
+        while Token::RBrace != self.current_token && Token::EOF != self.current_token {
+            self.take_comments(&mut statements);
+            let errors = self.errors.len();
+            match self.parse_statement() {
+                Some(statement) => statements.push(statement),
+                None if self.errors.len() > errors => self.synchronize(),
+                None => {}
             }
+            self.next_token();
+        }
+        if self.current_token == Token::RBrace {
+            self.take_comments(&mut statements);
+        }
+        Statement::BlockStatement(statements)
+    }
+
+    fn parse_if(&mut self) -> Option<Expr> {
+        let span = self.current_span;
+        if !self.expect_peek(Token::LParen) {
+            return None;
+        }
+        self.next_token();
+        let condition = self.parse_expression(precedence::LOWEST)?;
+
+        if !self.expect_peek(Token::RParen) {
+            return None;
+        }
+
+        if !self.expect_peek(Token::LBrace) {
+            return None;
         }
+
+        let then = self.parse_statement()?;
+
+        if self.peek_token != Token::Else {
+            return Some(Expr::If(Box::new(IfExpression {
+                condition,
+                then: Box::new(then),
+                else_: None,
+                span,
+            })));
+        }
+        self.next_token();
+
+        if !self.expect_peek(Token::LBrace) {
+            return None;
+        }
+
+        let else_ = self.parse_statement()?;
+
+        Some(Expr::If(Box::new(IfExpression {
+            condition,
+            then: Box::new(then),
+            else_: Some(Box::new(else_)),
+            span,
+        })))
     }
 
     pub fn parse_expression(&mut self, prec: i32) -> Option<Expr> {
+        if !self.enter() {
+            return None;
+        }
+        let res = self.parse_expression_inner(prec);
+        self.leave();
+        res
+    }
+
+    fn parse_expression_inner(&mut self, prec: i32) -> Option<Expr> {
         let mut left = self.parse_prefix();
 
         while self.peek_token != Token::Semicolon
             && prec < precedence::get_precedence(self.peek_token.clone())
+            && self.continues()
         {
             if let Some(left_ex) = left.clone() {
                 self.next_token();
                 let parsed_infix = self.parse_infix(left_ex.clone());
-                if let None = parsed_infix {
+                if parsed_infix.is_none() {
                     return Some(left_ex);
                 }
 
@@ -194,30 +504,27 @@ impl Parser {
                 return left;
             }
         }
-        return left;
+        left
     }
 
     pub fn parse_prefix(&mut self) -> Option<Expr> {
-        let res = match self.current_token.clone() {
+        match self.current_token.clone() {
             Token::Ident(a) => {
                 let name: String = a.into_iter().collect();
-                Some(Expr::Ident(Ident { name }))
-            }
-            Token::Int(a) => {
-                let num: String = a.into_iter().collect();
-                Some(Expr::Int(num.parse::<i64>().unwrap()))
+                Some(Expr::Ident(self.ident(name)))
             }
+            Token::Int(a) => self.parse_int(a),
+            Token::String(a) => Some(Expr::String(a.into_iter().collect())),
+            Token::TemplateStart(text) => self.parse_interpolated(text),
             Token::True => Some(Expr::Bool(true)),
             Token::False => Some(Expr::Bool(false)),
             Token::Minus | Token::Plus | Token::Bang => {
                 let op = self.current_token.clone();
+                let span = self.current_span;
                 self.next_token();
                 if let Some(prefix_op) = lookup_prefix(op) {
-                    if let Some(expr) = self.parse_expression(PREFIX) {
-                        Some(Expr::Prefix(prefix_op, Box::new(expr)))
-                    } else {
-                        None
-                    }
+                    self.parse_expression(PREFIX)
+                        .map(|expr| Expr::Prefix(prefix_op, Box::new(expr), span))
                 } else {
                     None
                 }
@@ -232,57 +539,391 @@ impl Parser {
                 }
                 None
             }
-            Token::Func => {
-                self.next_token();
-                if self.expect_peek(Token::LParen) {
+            Token::LBracket => {
+                let elements = self.parse_expression_list(Token::RBracket)?;
+                Some(Expr::Array(elements))
+            }
+            Token::LBrace => self.parse_hash(),
+            Token::Func => self.parse_function(),
+            Token::If => self.parse_if(),
+            Token::Match => self.parse_match(),
+            token => {
+                self.unexpected(token);
+                None
+            }
+        }
+    }
+
+    // Kept out of `parse_prefix`, whose stack frame is paid for once per
+    // level of nesting.
+    fn parse_int(&mut self, digits: Vec<char>) -> Option<Expr> {
+        let num: String = digits.into_iter().collect();
+        match num.parse::<i64>() {
+            Ok(i) => Some(Expr::Int(i)),
+            Err(_) => {
+                let message = format!("integer literal out of range: {}", num);
+                self.error(message, self.current_span);
+                None
+            }
+        }
+    }
+
+    fn parse_function(&mut self) -> Option<Expr> {
+        if !self.expect_peek(Token::LParen) {
+            return None;
+        }
+
+        let mut params = Vec::new();
+        if self.peek_token == Token::RParen {
+            self.next_token();
+        } else {
+            loop {
+                if !self.expect_peek(Token::Ident(vec![])) {
                     return None;
                 }
+                if let Token::Ident(ident) = self.current_token.clone() {
+                    let ident = self.ident(ident.into_iter().collect());
+                    let ty = self.parse_annotation(Token::Colon)?;
+                    params.push(Param { ident, ty });
+                }
+                if self.peek_token != Token::Comma {
+                    break;
+                }
+                self.next_token();
+            }
+            if !self.expect_peek(Token::RParen) {
+                return None;
+            }
+        }
+        let ret = self.parse_annotation(Token::Arrow)?;
 
-                let params = if self.peek_token == Token::RParen {
-                    self.next_token();
-                    Vec::new()
-                } else {
-                    self.next_token();
-                    let mut params = Vec::new();
+        if !self.expect_peek(Token::LBrace) {
+            return None;
+        }
+        let body = self.parse_block();
+        Some(Expr::Function(params, ret, Box::new(body)))
+    }
 
-                    if let Token::Ident(ident) = self.current_token.clone() {
-                        params.push(Ident {
-                            name: ident.into_iter().collect(),
-                        })
-                    } else {
-                        return None;
-                    }
+    // The type after `marker` if the next token is one, e.g. `: int`. The
+    // outer `None` is a parse error.
+    fn parse_annotation(&mut self, marker: Token) -> Option<Option<TypeExpr>> {
+        if self.peek_token != marker {
+            return Some(None);
+        }
+        self.next_token();
+        self.next_token();
+        self.parse_type().map(Some)
+    }
 
-                    while self.peek_token == Token::Comma {
-                        self.next_token();
+    fn parse_type(&mut self) -> Option<TypeExpr> {
+        if !self.enter() {
+            return None;
+        }
+        let res = self.parse_type_inner();
+        self.leave();
+        res
+    }
+
+    fn parse_type_inner(&mut self) -> Option<TypeExpr> {
+        match self.current_token.clone() {
+            Token::Ident(name) => {
+                let name: String = name.into_iter().collect();
+                let ty = TypeExpr::from_name(&name);
+                if ty.is_none() {
+                    self.error(format!("unknown type: {}", name), self.current_span);
+                }
+                ty
+            }
+            Token::LBracket => {
+                self.next_token();
+                let element = self.parse_type()?;
+                if !self.expect_peek(Token::RBracket) {
+                    return None;
+                }
+                Some(TypeExpr::Array(Box::new(element)))
+            }
+            Token::Func => {
+                if !self.expect_peek(Token::LParen) {
+                    return None;
+                }
+                let mut params = Vec::new();
+                if self.peek_token == Token::RParen {
+                    self.next_token();
+                } else {
+                    loop {
                         self.next_token();
-                        if let Token::Ident(ident) = self.current_token.clone() {
-                            params.push(Ident {
-                                name: ident.into_iter().collect(),
-                            })
+                        params.push(self.parse_type()?);
+                        if self.peek_token != Token::Comma {
+                            break;
                         }
+                        self.next_token();
                     }
-
                     if !self.expect_peek(Token::RParen) {
                         return None;
                     }
-                    params
-                };
+                }
+                if !self.expect_peek(Token::Arrow) {
+                    return None;
+                }
                 self.next_token();
+                let ret = self.parse_type()?;
+                Some(TypeExpr::Func(params, Box::new(ret)))
+            }
+            token => {
+                self.unexpected(token);
+                None
+            }
+        }
+    }
+
+    // `"text ${expr} text"`, from the text before the first `${`.
+    fn parse_interpolated(&mut self, text: Vec<char>) -> Option<Expr> {
+        let start = self.current_span;
+        let mut fragments = Vec::new();
+        let mut text = text;
+        loop {
+            if !text.is_empty() {
+                fragments.push(Fragment::Text(text.into_iter().collect()));
+            }
+            if let Token::TemplateMiddle(_) | Token::TemplateEnd(_) = self.peek_token {
+                self.error(String::from("empty interpolation"), self.peek_span);
+                return None;
+            }
+            self.next_token();
+            fragments.push(Fragment::Expr(self.parse_expression(precedence::LOWEST)?));
+            self.next_token();
+            match self.current_token.clone() {
+                Token::TemplateMiddle(next) => text = next,
+                Token::TemplateEnd(last) => {
+                    if !last.is_empty() {
+                        fragments.push(Fragment::Text(last.into_iter().collect()));
+                    }
+                    return Some(Expr::Interpolated(fragments, start.to(self.current_span)));
+                }
+                token => {
+                    let message = format!("expected `}}` after interpolation, got: {:?}", token);
+                    self.error(message, self.current_span);
+                    return None;
+                }
+            }
+        }
+    }
+
+    // `{key: value, ...}`, with the current token on the `{`.
+    fn parse_hash(&mut self) -> Option<Expr> {
+        let span = self.current_span;
+        let mut entries = Vec::new();
+        while self.peek_token != Token::RBrace {
+            self.next_token();
+            let key = self.parse_expression(precedence::LOWEST)?;
+            if !self.expect_peek(Token::Colon) {
+                return None;
+            }
+            self.next_token();
+            let value = self.parse_expression(precedence::LOWEST)?;
+            entries.push((key, value));
+            if self.peek_token != Token::RBrace && !self.expect_peek(Token::Comma) {
+                return None;
+            }
+        }
+        self.next_token();
+        Some(Expr::Hash(entries, span))
+    }
 
-                let body = self.parse_statement();
+    // `match (value) { pattern if guard => body, ... }`. An arm's body is a
+    // block or an expression, and a `,` only has to follow an expression.
+    fn parse_match(&mut self) -> Option<Expr> {
+        let span = self.current_span;
+        if !self.expect_peek(Token::LParen) {
+            return None;
+        }
+        self.next_token();
+        let subject = self.parse_expression(precedence::LOWEST)?;
+        if !self.expect_peek(Token::RParen) || !self.expect_peek(Token::LBrace) {
+            return None;
+        }
 
-                if let Some(Statement::BlockStatement(block)) = body {
-                    return Some(Expr::Function(
-                        params,
-                        Box::new(Statement::BlockStatement(block)),
-                    ));
+        let mut arms = Vec::new();
+        while self.peek_token != Token::RBrace {
+            self.next_token();
+            let pattern = self.parse_pattern()?;
+            let guard = if self.peek_token == Token::If {
+                self.next_token();
+                self.next_token();
+                Some(self.parse_expression(precedence::LOWEST)?)
+            } else {
+                None
+            };
+            if !self.expect_peek(Token::FatArrow) {
+                return None;
+            }
+            self.next_token();
+            let body = if self.current_token == Token::LBrace {
+                self.parse_statement()?
+            } else {
+                Statement::Expression(self.parse_expression(precedence::LOWEST)?)
+            };
+            let block = matches!(body, Statement::BlockStatement(_));
+            arms.push(Arm {
+                pattern,
+                guard,
+                body: Box::new(body),
+            });
+            if self.peek_token == Token::Comma {
+                self.next_token();
+            } else if self.peek_token != Token::RBrace && !block {
+                let message = format!("expected `,` after match arm, got: {:?}", self.peek_token);
+                self.error(message, self.peek_span);
+                return None;
+            }
+        }
+        self.next_token();
+        Some(Expr::Match(Box::new(subject), arms, span))
+    }
+
+    fn parse_pattern(&mut self) -> Option<Pattern> {
+        if !self.enter() {
+            return None;
+        }
+        let res = self.parse_pattern_inner();
+        self.leave();
+        res
+    }
+
+    fn parse_pattern_inner(&mut self) -> Option<Pattern> {
+        match self.current_token.clone() {
+            Token::Ident(name) => {
+                let name: String = name.into_iter().collect();
+                if name == "_" {
+                    Some(Pattern::Wildcard)
+                } else {
+                    Some(Pattern::Binding(self.ident(name)))
+                }
+            }
+            Token::Int(digits) => match self.parse_int(digits)? {
+                Expr::Int(i) => Some(Pattern::Int(i)),
+                _ => None,
+            },
+            Token::Minus => {
+                if !self.expect_peek(Token::Int(vec![])) {
+                    return None;
+                }
+                let Token::Int(digits) = self.current_token.clone() else {
+                    return None;
+                };
+                match self.parse_int([vec!['-'], digits].concat())? {
+                    Expr::Int(i) => Some(Pattern::Int(i)),
+                    _ => None,
+                }
+            }
+            Token::String(s) => Some(Pattern::String(s.into_iter().collect())),
+            Token::True => Some(Pattern::Bool(true)),
+            Token::False => Some(Pattern::Bool(false)),
+            Token::LBracket => {
+                let mut elements = Vec::new();
+                let mut rest = None;
+                while self.peek_token != Token::RBracket {
+                    self.next_token();
+                    if self.current_token == Token::Ellipsis {
+                        rest = Some(self.parse_rest(Token::RBracket)?);
+                        break;
+                    }
+                    elements.push(self.parse_element_pattern()?);
+                    if self.peek_token != Token::RBracket && !self.expect_peek(Token::Comma) {
+                        return None;
+                    }
                 }
+                self.next_token();
+                Some(Pattern::Array(elements, rest))
+            }
+            Token::LBrace => {
+                let mut entries = Vec::new();
+                let mut rest = None;
+                while self.peek_token != Token::RBrace {
+                    self.next_token();
+                    let entry = match self.current_token.clone() {
+                        Token::Ellipsis => {
+                            rest = Some(self.parse_rest(Token::RBrace)?);
+                            break;
+                        }
+                        // `name` is `"name": name`.
+                        Token::Ident(name) if self.peek_token != Token::Colon => {
+                            let name: String = name.into_iter().collect();
+                            let key = Expr::String(name.clone());
+                            let mut value = Pattern::Binding(self.ident(name));
+                            if self.peek_token == Token::Assign {
+                                value = self.parse_default(value)?;
+                            }
+                            (key, value)
+                        }
+                        Token::Int(_) | Token::String(_) | Token::True | Token::False => {
+                            let key = self.parse_prefix()?;
+                            if !self.expect_peek(Token::Colon) {
+                                return None;
+                            }
+                            self.next_token();
+                            (key, self.parse_element_pattern()?)
+                        }
+                        token => {
+                            let message =
+                                format!("expected a literal hash pattern key, got: {:?}", token);
+                            self.error(message, self.current_span);
+                            return None;
+                        }
+                    };
+                    entries.push(entry);
+                    if self.peek_token != Token::RBrace && !self.expect_peek(Token::Comma) {
+                        return None;
+                    }
+                }
+                self.next_token();
+                Some(Pattern::Hash(entries, rest))
+            }
+            token => {
+                self.unexpected(token);
                 None
             }
-            _ => None,
+        }
+    }
+
+    // A pattern in an array or hash pattern, which may have a default.
+    fn parse_element_pattern(&mut self) -> Option<Pattern> {
+        let pattern = self.parse_pattern()?;
+        if self.peek_token == Token::Assign {
+            return self.parse_default(pattern);
+        }
+        Some(pattern)
+    }
+
+    fn parse_default(&mut self, pattern: Pattern) -> Option<Pattern> {
+        self.next_token();
+        self.next_token();
+        let default = self.parse_expression(precedence::LOWEST)?;
+        Some(Pattern::Default(Box::new(pattern), default))
+    }
+
+    // `...name`, which ends the pattern: only a trailing comma may follow.
+    fn parse_rest(&mut self, close: Token) -> Option<Ident> {
+        if !self.expect_peek(Token::Ident(vec![])) {
+            return None;
+        }
+        let Token::Ident(name) = self.current_token.clone() else {
+            return None;
         };
-        res
+        let ident = self.ident(name.into_iter().collect());
+        if self.peek_token == Token::Comma {
+            self.next_token();
+        }
+        if self.peek_token != close {
+            let message = format!("expected the rest to come last, got: {:?}", self.peek_token);
+            self.error(message, self.peek_span);
+            return None;
+        }
+        Some(ident)
+    }
+
+    fn unexpected(&mut self, token: Token) {
+        self.error(format!("unexpected token: {:?}", token), self.current_span);
     }
 
     pub fn parse_infix(&mut self, left: Expr) -> Option<Expr> {
@@ -296,6 +937,7 @@ impl Parser {
             | Token::Eq
             | Token::NotEq => {
                 let prec = precedence::get_precedence(self.current_token.clone());
+                let span = self.current_span;
                 let parsed_infix = lookup_infix(self.current_token.clone());
 
                 self.next_token();
@@ -304,34 +946,76 @@ impl Parser {
 
                 if let Some(right) = parsed_right {
                     if let Some(infix_op) = parsed_infix {
-                        return Some(Expr::Infix(Box::new(left), infix_op, Box::new(right)));
+                        return Some(Expr::Infix(Box::new(left), infix_op, Box::new(right), span));
                     }
                 }
                 None
             }
             Token::LParen => {
-                let mut params = Vec::new();
-                if self.peek_token == Token::RParen {
-                    self.next_token();
-                    return Some(Expr::Call(Box::new(left), params));
+                let start = left.span().unwrap_or(self.current_span);
+                let params = self.parse_expression_list(Token::RParen)?;
+                let span = start.to(self.current_span);
+                Some(Expr::Call(Box::new(left), params, span))
+            }
+            Token::Question => {
+                let span = self.current_span;
+                Some(Expr::Propagate(Box::new(left), span))
+            }
+            Token::Dot => {
+                let start = left.span().unwrap_or(self.current_span);
+                if !self.expect_peek(Token::Ident(vec![])) {
+                    return None;
                 }
+                let name = match self.current_token.clone() {
+                    Token::Ident(a) => self.ident(a.into_iter().collect()),
+                    _ => return None,
+                };
+                let span = start.to(self.current_span);
+                Some(Expr::Member(Box::new(left), name, span))
+            }
+            Token::LBracket => {
+                let start = left.span().unwrap_or(self.current_span);
                 self.next_token();
-                let first_arg = self.parse_expression(precedence::LOWEST);
-                params.push(first_arg.unwrap());
-
-                while self.peek_token == Token::Comma {
-                    self.next_token();
-                    self.next_token();
-                    params.push(self.parse_expression(precedence::LOWEST).unwrap());
-                }
-
-                if !self.expect_peek(Token::RParen) {
+                let index = self.parse_expression(precedence::LOWEST)?;
+                if !self.expect_peek(Token::RBracket) {
                     return None;
                 }
-
-                return Some(Expr::Call(Box::new(left), params));
+                let span = start.to(self.current_span);
+                Some(Expr::Index(Box::new(left), Box::new(index), span))
             }
-            _ => return None,
+            _ => None,
+        }
+    }
+
+    // Parses comma separated expressions with the current token on the
+    // opening delimiter, leaving it on `end`.
+    fn parse_expression_list(&mut self, end: Token) -> Option<Vec<Expr>> {
+        let mut list = Vec::new();
+        if self.peek_token == end {
+            self.next_token();
+            return Some(list);
         }
+
+        self.next_token();
+        list.push(self.parse_expression(precedence::LOWEST)?);
+        while self.peek_token == Token::Comma {
+            self.next_token();
+            self.next_token();
+            list.push(self.parse_expression(precedence::LOWEST)?);
+        }
+
+        if !self.expect_peek(end) {
+            return None;
+        }
+        Some(list)
+    }
+}
+
+// Whether `expr` ends with the `}` of a block, after which a `;` is noise.
+fn ends_with_block(expr: &Expr) -> bool {
+    match expr {
+        Expr::Function(..) | Expr::If(_) | Expr::Match(..) => true,
+        Expr::Prefix(_, right, _) | Expr::Infix(_, _, right, _) => ends_with_block(right),
+        _ => false,
     }
 }
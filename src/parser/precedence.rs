@@ -7,6 +7,8 @@ pub const SUM: i32 = 3;
 pub const PRODUCT: i32 = 4;
 pub const PREFIX: i32 = 5;
 pub const CALL: i32 = 6;
+pub const INDEX: i32 = 7;
+pub const POSTFIX: i32 = 8;
 
 pub fn get_precedence(op: Token) -> i32 {
     match op {
@@ -19,6 +21,10 @@ pub fn get_precedence(op: Token) -> i32 {
         Token::Star => PRODUCT,
         Token::Slash => PRODUCT,
         Token::LParen => CALL,
+        Token::LBracket => INDEX,
+        // `m.f(x)` calls `m.f`, and `m.a[0]` indexes `m.a`.
+        Token::Dot => INDEX,
+        Token::Question => POSTFIX,
         _ => LOWEST,
     }
 }
@@ -1,6 +1,18 @@
 #[cfg(test)]
 mod tests {
-    use crate::{ast::Node, lexer, parser::Parser};
+    use crate::{
+        ast::{Expr, Node, Statement},
+        lexer,
+        parser::{Parser, MAX_NESTING},
+    };
+
+    fn messages(parser: &Parser) -> Vec<&str> {
+        parser
+            .errors()
+            .iter()
+            .map(|err| err.message.as_str())
+            .collect()
+    }
 
     #[test]
     fn test_parse_expression() {
@@ -82,6 +94,24 @@ mod tests {
         assert_eq!(prog.statements[4].literal(), "Return(((3 > a) == true))");
     }
 
+    #[test]
+    fn test_ident_ids() {
+        let mut parser = Parser::new(lexer::Lexer::new("let a = 1; let b = a + a;"));
+        let prog = parser.parse();
+        let [Statement::Let(a), Statement::Let(b)] = &prog.statements[..] else {
+            panic!("expected two lets");
+        };
+        let Expr::Infix(left, _, right, _) = &b.expr else {
+            panic!("expected an infix expression");
+        };
+        let [Expr::Ident(left), Expr::Ident(right)] = [left.as_ref(), right.as_ref()] else {
+            panic!("expected identifiers");
+        };
+        // In the order they are read, each use its own.
+        let ids = [a.ident(), b.ident(), Some(left), Some(right)].map(|i| i.unwrap().id);
+        assert_eq!(ids, [0, 1, 2, 3]);
+    }
+
     #[test]
     fn test_parse_let() {
         let mut parser = Parser::new(lexer::Lexer::new(
@@ -167,6 +197,108 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_parse_if_expression() {
+        let mut parser = Parser::new(lexer::Lexer::new(
+            "let x = if (c) { 1 } else { 2 };
+            f(if (a) { b }) + 1;
+            return if (a) { let y = 1; y } else { 0 }
+            if (a) { b } -1;
+            (if (a) { b } else { c }) - 1;
+            { let z = 3; z }",
+        ));
+        let prog = parser.parse();
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+        let literals: Vec<String> = prog.statements.iter().map(|s| s.literal()).collect();
+        assert_eq!(
+            literals,
+            [
+                "Let(x, If(c, { 1; }, { 2; }))",
+                "(f(If(a, { b; })) + 1)",
+                "Return(If(a, { Let(y, 1); y; }, { 0; }))",
+                // In statement position an `if` ends at its `}`.
+                "If(a, { b; })",
+                "(-1)",
+                "(If(a, { b; }, { c; }) - 1)",
+                "{ Let(z, 3); z; }",
+            ]
+        );
+
+        // Elsewhere, the `;` is still needed.
+        let cases = [
+            ("let a = 1 let b = 2;", "expected: Semicolon, got: Let"),
+            ("f(x) g(x);", "expected: Semicolon, got: Ident(['g'])"),
+            ("return 1 2;", "expected: Semicolon, got: Int(['2'])"),
+        ];
+        for (input, error) in cases {
+            let mut parser = Parser::new(lexer::Lexer::new(input));
+            parser.parse();
+            assert_eq!(messages(&parser).first(), Some(&error));
+        }
+    }
+
+    #[test]
+    fn test_parse_semicolon_insertion() {
+        let cases = [
+            // A line break ends a complete statement.
+            (
+                "let a = 1\nlet b = a\nb",
+                vec!["Let(a, 1)", "Let(b, a)", "b"],
+            ),
+            (
+                "import \"m\" as m\nreturn m\nthrow 1",
+                vec!["Import(\"m\", m)", "Return(m)", "Throw(1)"],
+            ),
+            ("f(x) // call\ng(x)", vec!["f(x)", "Comment(call)", "g(x)"]),
+            // `return` is complete on its own: its value must start on
+            // the same line.
+            ("func() {\n return\n 1\n}", vec!["Func() { Return; 1; }"]),
+            ("func() {\n return\n}", vec!["Func() { Return; }"]),
+            ("return;", vec!["Return"]),
+            // An incomplete one goes on to the next line.
+            ("let a =\n1", vec!["Let(a, 1)"]),
+            ("f(1,\n2)", vec!["f(1,2)"]),
+            ("let a = 1 +\n2", vec!["Let(a, (1 + 2))"]),
+            // So does an expression when the next line starts with an
+            // operator, even one that could start a statement.
+            ("let a = 1\n+ 2", vec!["Let(a, (1 + 2))"]),
+            ("a\n-1", vec!["(a - 1)"]),
+            ("a\n== b", vec!["(a == b)"]),
+            ("r\n?", vec!["(r?)"]),
+            ("m\n.x", vec!["(m.x)"]),
+            // But not when it starts with `(` or `[`.
+            ("let f = g\n(1)", vec!["Let(f, g)", "1"]),
+            ("a\n[1, 2]", vec!["a", "[1,2]"]),
+        ];
+        for (input, expected) in cases {
+            let mut parser = Parser::new(lexer::Lexer::new(input));
+            let prog = parser.parse();
+            let literals: Vec<String> = prog.statements.iter().map(|s| s.literal()).collect();
+            assert!(
+                parser.errors().is_empty(),
+                "{}: {:?}",
+                input,
+                parser.errors()
+            );
+            assert_eq!(literals, expected, "{}", input);
+        }
+
+        // Nor is it one inside parentheses.
+        let mut parser = Parser::new(lexer::Lexer::new("f(a\n[0])"));
+        parser.parse();
+        assert_eq!(
+            messages(&parser).first(),
+            Some(&"expected: RParen, got: LBracket")
+        );
+        // On a single line, statements still need their `;`.
+        let mut parser = Parser::new(lexer::Lexer::new("let a = 1 let b = 2"));
+        parser.parse();
+        assert_eq!(
+            messages(&parser).first(),
+            Some(&"expected: Semicolon, got: Let")
+        );
+    }
+
     #[test]
     fn test_parse_function() {
         let mut parser = Parser::new(lexer::Lexer::new(
@@ -216,4 +348,287 @@ mod tests {
         assert_eq!(prog.statements[1].literal(), "add(1,2,add(3,4,5))");
         assert_eq!(prog.statements[2].literal(), "((a + add((b * c))) + d)");
     }
+
+    #[test]
+    fn test_parse_array_and_index() {
+        let mut parser = Parser::new(lexer::Lexer::new(
+            "\
+            [1, 2 * 3, \"a\"];\
+            [];\
+            a[1 + 1] * b[0][2];\
+            f(x)[0];\
+            ",
+        ));
+
+        let prog = parser.parse();
+        assert_eq!(prog.statements[0].literal(), "[1,(2 * 3),\"a\"]");
+        assert_eq!(prog.statements[1].literal(), "[]");
+        assert_eq!(prog.statements[2].literal(), "((a[(1 + 1)]) * ((b[0])[2]))");
+        assert_eq!(prog.statements[3].literal(), "(f(x)[0])");
+    }
+
+    #[test]
+    fn test_parse_propagate() {
+        let mut parser = Parser::new(lexer::Lexer::new(
+            "\
+            f(x)?;\
+            -a? + b?;\
+            a[0]? * 2;\
+            let v = g()?;\
+            ",
+        ));
+
+        let prog = parser.parse();
+        assert!(parser.errors().is_empty());
+        assert_eq!(prog.statements[0].literal(), "(f(x)?)");
+        assert_eq!(prog.statements[1].literal(), "((-(a?)) + (b?))");
+        assert_eq!(prog.statements[2].literal(), "(((a[0])?) * 2)");
+        assert_eq!(prog.statements[3].literal(), "Let(v, (g()?))");
+    }
+
+    #[test]
+    fn test_parse_try() {
+        let mut parser = Parser::new(lexer::Lexer::new(
+            "\
+            try { f(); } catch (e) { g(e); };\
+            try { throw \"no\"; } finally { h(); };\
+            try { 1; } catch (e) { 2; } finally { 3; };\
+            ",
+        ));
+
+        let prog = parser.parse();
+        assert!(parser.errors().is_empty());
+        assert_eq!(
+            prog.statements[0].literal(),
+            "Try({ f(); }, Catch(e, { g(e); }))"
+        );
+        assert_eq!(
+            prog.statements[1].literal(),
+            "Try({ Throw(\"no\"); }, Finally({ h(); }))"
+        );
+        assert_eq!(
+            prog.statements[2].literal(),
+            "Try({ 1; }, Catch(e, { 2; }), Finally({ 3; }))"
+        );
+
+        let mut parser = Parser::new(lexer::Lexer::new("try { 1; };"));
+        parser.parse();
+        assert_eq!(
+            messages(&parser),
+            ["expected: Catch or Finally, got: Semicolon"]
+        );
+    }
+
+    #[test]
+    fn test_parse_modules() {
+        let mut parser = Parser::new(lexer::Lexer::new(
+            "\
+            import \"lib/math.cake\" as m;\
+            export let x: int = m.square(2);\
+            m.a.b[0] + -m.c;\
+            ",
+        ));
+
+        let prog = parser.parse();
+        assert!(parser.errors().is_empty());
+        assert_eq!(prog.statements[0].literal(), "Import(\"lib/math.cake\", m)");
+        assert_eq!(
+            prog.statements[1].literal(),
+            "Export(Let(x: int, (m.square)(2)))"
+        );
+        assert_eq!(prog.statements[2].literal(), "((((m.a).b)[0]) + (-(m.c)))");
+
+        let cases = [
+            (
+                "{ export let x = 1; }",
+                "export is only allowed at the top level",
+            ),
+            ("import m as m;", "expected: String([]), got: Ident(['m'])"),
+            ("import \"m\";", "expected: As, got: Semicolon"),
+            ("m.1;", "expected: Ident([]), got: Int(['1'])"),
+        ];
+        for (input, error) in cases {
+            let mut parser = Parser::new(lexer::Lexer::new(input));
+            parser.parse();
+            assert_eq!(messages(&parser).first(), Some(&error));
+        }
+    }
+
+    #[test]
+    fn test_parse_interpolation() {
+        let mut parser = Parser::new(lexer::Lexer::new(
+            r#""Hello, ${name}! You have ${count + 1} messages"; "${a}${"${b}"}";"#,
+        ));
+        let prog = parser.parse();
+        assert!(parser.errors().is_empty());
+        assert_eq!(
+            prog.statements[0].literal(),
+            "Interpolated(\"Hello, \", name, \"! You have \", (count + 1), \" messages\")"
+        );
+        assert_eq!(
+            prog.statements[1].literal(),
+            "Interpolated(a, Interpolated(b))"
+        );
+
+        let cases = [
+            ("\"a${}b\";", "empty interpolation"),
+            (
+                "\"${a b}\";",
+                "expected `}` after interpolation, got: Ident(['b'])",
+            ),
+            (
+                "\"${a;}\";",
+                "expected `}` after interpolation, got: Semicolon",
+            ),
+        ];
+        for (input, error) in cases {
+            let mut parser = Parser::new(lexer::Lexer::new(input));
+            parser.parse();
+            assert_eq!(messages(&parser).first(), Some(&error));
+        }
+    }
+
+    #[test]
+    fn test_parse_match() {
+        let mut parser = Parser::new(lexer::Lexer::new(
+            r#"let h = {"a": 1, 2: [x]}; match (f(x)) { 0 => 1, -2 => { x; } [a, _] if a > 1 => a, {"k": v} => v, }
+            match (x) {}"#,
+        ));
+        let prog = parser.parse();
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+        assert_eq!(prog.statements[0].literal(), "Let(h, {\"a\": 1,2: [x]})");
+        assert_eq!(
+            prog.statements[1].literal(),
+            "Match(f(x), [Arm(0, 1), Arm(-2, { x; }), Arm([a,_] if (a > 1), a), Arm({\"k\": v}, v)])"
+        );
+        assert_eq!(prog.statements[2].literal(), "Match(x, [])");
+
+        let cases = [
+            (
+                "match (x) { 1 => 2 3 => 4 };",
+                "expected `,` after match arm, got: Int(['3'])",
+            ),
+            ("match (x) { 1 2 };", "expected: FatArrow, got: Int(['2'])"),
+            (
+                "match (x) { {a: 1} => 2 };",
+                "expected a literal hash pattern key, got: Ident(['a'])",
+            ),
+            (
+                "match (x) { f(1) => 2 };",
+                "expected: FatArrow, got: LParen",
+            ),
+            ("let h = {1 2};", "expected: Colon, got: Int(['2'])"),
+        ];
+        for (input, error) in cases {
+            let mut parser = Parser::new(lexer::Lexer::new(input));
+            parser.parse();
+            assert_eq!(messages(&parser).first(), Some(&error));
+        }
+    }
+
+    #[test]
+    fn test_parse_destructuring() {
+        let mut parser = Parser::new(lexer::Lexer::new(
+            r#"let [a, [b, _] = x, ...rest] = y; export let {name, "k": v = 1, age = 0,}: any = z;
+            let [...all,] = y; let _ = 1;"#,
+        ));
+        let prog = parser.parse();
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+        assert_eq!(
+            prog.statements[0].literal(),
+            "Let([a,[b,_] = x,...rest], y)"
+        );
+        assert_eq!(
+            prog.statements[1].literal(),
+            "Export(Let({\"name\": name,\"k\": v = 1,\"age\": age = 0}: any, z))"
+        );
+        assert_eq!(prog.statements[2].literal(), "Let([...all], y)");
+        // A plain `_` is still a name.
+        assert_eq!(prog.statements[3].literal(), "Let(_, 1)");
+
+        let cases = [
+            (
+                "let [...rest, a] = x;",
+                "expected the rest to come last, got: Ident(['a'])",
+            ),
+            ("let {...1} = x;", "expected: Ident([]), got: Int(['1'])"),
+            ("let [a b] = x;", "expected: Comma, got: Ident(['b'])"),
+        ];
+        for (input, error) in cases {
+            let mut parser = Parser::new(lexer::Lexer::new(input));
+            parser.parse();
+            assert_eq!(messages(&parser).first(), Some(&error));
+        }
+    }
+
+    #[test]
+    fn test_parse_nesting_limit() {
+        let input = format!("{}1{};", "(".repeat(100), ")".repeat(100));
+        let mut parser = Parser::new(lexer::Lexer::new(&input));
+        let prog = parser.parse();
+        assert!(parser.errors().is_empty());
+        assert_eq!(prog.statements[0].literal(), "1");
+
+        let input = format!("{}1{};", "(".repeat(100_000), ")".repeat(100_000));
+        let mut parser = Parser::new(lexer::Lexer::new(&input));
+        let prog = parser.parse();
+        assert!(prog.statements.is_empty());
+        assert_eq!(
+            messages(&parser),
+            [format!("maximum nesting depth of {} exceeded", MAX_NESTING)]
+        );
+
+        let input = format!("{}{}", "{".repeat(100_000), "}".repeat(100_000));
+        let mut parser = Parser::new(lexer::Lexer::new(&input));
+        parser.parse();
+        assert_eq!(parser.errors().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_comments() {
+        let mut parser = Parser::new(lexer::Lexer::new(
+            "// head\nlet a = 1; // a\n{ // open\n    a;\n    // tail\n}\n// end",
+        ));
+        let prog = parser.parse();
+        assert!(parser.errors().is_empty());
+        let literals: Vec<String> = prog.statements.iter().map(|s| s.literal()).collect();
+        assert_eq!(
+            literals,
+            [
+                "Comment(head)",
+                "Let(a, 1)",
+                "Comment(a)",
+                "{ Comment(open); a; Comment(tail); }",
+                "Comment(end)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        let cases = [
+            ("let x = ;", "unexpected token: Semicolon"),
+            ("f(,);", "unexpected token: Comma"),
+            (
+                "99999999999999999999;",
+                "integer literal out of range: 99999999999999999999",
+            ),
+            ("if () { 1; }", "unexpected token: RParen"),
+            ("func(a, 1) {};", "expected: Ident([]), got: Int(['1'])"),
+            (
+                "let f = func(a) a; f;",
+                "expected: LBrace, got: Ident(['a'])",
+            ),
+        ];
+        for (input, error) in cases {
+            let mut parser = Parser::new(lexer::Lexer::new(input));
+            parser.parse();
+            assert_eq!(messages(&parser).first(), Some(&error));
+        }
+
+        // Errors point at the offending token.
+        let mut parser = Parser::new(lexer::Lexer::new("let a = 1;\nlet b = ;"));
+        parser.parse();
+        assert_eq!(parser.errors()[0].span.to_string(), "2:9");
+    }
 }
@@ -0,0 +1,172 @@
+use std::{borrow::Cow, cell::Cell};
+
+use rustyline::{
+    completion::Completer, highlight::Highlighter, hint::Hinter, validate::Validator, Context,
+    Helper,
+};
+
+use crate::{
+    evaluator::builtins,
+    lexer::{self, is_letter},
+    token::{Token, KEYWORDS},
+};
+
+const KEYWORD_COLOR: &str = "\x1b[35m";
+const BUILTIN_COLOR: &str = "\x1b[36m";
+const NUMBER_COLOR: &str = "\x1b[33m";
+const STRING_COLOR: &str = "\x1b[32m";
+const ILLEGAL_COLOR: &str = "\x1b[31m";
+const COMMENT_COLOR: &str = "\x1b[90m";
+const BRACKET_COLOR: &str = "\x1b[1;34m";
+const RESET: &str = "\x1b[0m";
+
+// Completion, highlighting and bracket matching for the REPL line editor.
+#[derive(Default)]
+pub struct CakeHelper {
+    // Names bound in the session, refreshed after every entry.
+    pub bindings: Vec<String>,
+    // Char offset of the bracket next to the cursor, if any.
+    bracket: Cell<Option<usize>>,
+}
+
+impl CakeHelper {
+    pub fn candidates(&self, prefix: &str) -> Vec<String> {
+        let keywords = KEYWORDS.iter().map(|(keyword, _)| keyword.to_string());
+        let builtins = builtins::names().map(String::from);
+        let mut candidates: Vec<String> = keywords
+            .chain(builtins)
+            .chain(self.bindings.iter().cloned())
+            .filter(|name| name.starts_with(prefix))
+            .collect();
+        candidates.sort();
+        candidates.dedup();
+        candidates
+    }
+}
+
+// Byte offset where the identifier ending at `pos` starts.
+pub fn word_start(line: &str, pos: usize) -> usize {
+    line[..pos]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_letter(*c))
+        .last()
+        .map_or(pos, |(i, _)| i)
+}
+
+// Char offset of the bracket matching the one at `at`, skipping brackets in
+// string literals.
+fn matching_bracket(line: &str, at: usize) -> Option<usize> {
+    let mut lexer = lexer::Lexer::new(line);
+    let mut open = Vec::new();
+    loop {
+        let token = lexer.next_token();
+        let start = lexer.span().start;
+        match token {
+            Token::EOF => return None,
+            Token::LParen | Token::LBrace | Token::LBracket => open.push(start),
+            Token::RParen | Token::RBrace | Token::RBracket => {
+                let opening = open.pop()?;
+                if opening == at {
+                    return Some(start);
+                }
+                if start == at {
+                    return Some(opening);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn color(token: &Token) -> Option<&'static str> {
+    match token {
+        Token::Ident(name) => {
+            let name: String = name.iter().collect();
+            builtins::names()
+                .any(|builtin| builtin == name)
+                .then_some(BUILTIN_COLOR)
+        }
+        Token::Int(_) => Some(NUMBER_COLOR),
+        Token::String(_)
+        | Token::TemplateStart(_)
+        | Token::TemplateMiddle(_)
+        | Token::TemplateEnd(_) => Some(STRING_COLOR),
+        Token::Illegal(_) => Some(ILLEGAL_COLOR),
+        Token::Comment(_) => Some(COMMENT_COLOR),
+        token if KEYWORDS.iter().any(|(_, keyword)| keyword == token) => Some(KEYWORD_COLOR),
+        _ => None,
+    }
+}
+
+impl Highlighter for CakeHelper {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        let chars: Vec<char> = line.chars().collect();
+        let matched = self
+            .bracket
+            .get()
+            .and_then(|at| matching_bracket(line, at).map(|other| (at, other)));
+
+        let mut res = String::with_capacity(line.len());
+        let mut copied = 0;
+        let mut lexer = lexer::Lexer::new(line);
+        loop {
+            let token = lexer.next_token();
+            if token == Token::EOF {
+                break;
+            }
+            let span = lexer.span();
+            res.extend(&chars[copied..span.start]);
+            let text: String = chars[span.start..span.end].iter().collect();
+            let color = match matched {
+                Some((a, b)) if span.start == a || span.start == b => Some(BRACKET_COLOR),
+                _ => color(&token),
+            };
+            match color {
+                Some(color) => res.push_str(&format!("{}{}{}", color, text, RESET)),
+                None => res.push_str(&text),
+            }
+            copied = span.end;
+        }
+        res.extend(&chars[copied..]);
+        Cow::Owned(res)
+    }
+
+    fn highlight_char(&self, line: &str, pos: usize, forced: bool) -> bool {
+        if forced {
+            self.bracket.set(None);
+            return true;
+        }
+        let at = line[..pos].chars().count();
+        let is_bracket = |c: char| "(){}[]".contains(c);
+        let bracket = match (line[pos..].chars().next(), line[..pos].chars().last()) {
+            (Some(c), _) if is_bracket(c) => Some(at),
+            (_, Some(c)) if is_bracket(c) => Some(at - 1),
+            _ => None,
+        };
+        self.bracket.set(bracket);
+        true
+    }
+}
+
+impl Completer for CakeHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = word_start(line, pos);
+        Ok((start, self.candidates(&line[start..pos])))
+    }
+}
+
+impl Hinter for CakeHelper {
+    type Hint = String;
+}
+
+impl Validator for CakeHelper {}
+
+impl Helper for CakeHelper {}
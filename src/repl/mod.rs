@@ -1,19 +1,89 @@
-use rustyline::{error::ReadlineError, DefaultEditor};
+use std::{env, path::PathBuf};
 
-use crate::{ast::Node, lexer, parser};
+use helper::CakeHelper;
+use rustyline::{error::ReadlineError, history::DefaultHistory, Editor};
+use session::Session;
 
-const PROMPT: &str = "cake-repl > ";
+use crate::{evaluator::Config, lexer, token::Token};
 
-pub fn repl() {
-    let mut rl = DefaultEditor::new().unwrap();
+pub mod helper;
+pub mod session;
+mod test;
+
+pub const PROMPT: &str = "cake-repl > ";
+const HISTORY_FILE: &str = ".cake_history";
+
+#[derive(Debug, Clone)]
+pub struct ReplConfig {
+    pub prompt: String,
+    // Where entries are saved between sessions, `None` keeps no history.
+    pub history_file: Option<PathBuf>,
+}
+
+impl Default for ReplConfig {
+    fn default() -> Self {
+        Self {
+            prompt: String::from(PROMPT),
+            history_file: env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE)),
+        }
+    }
+}
+
+// Right-aligns `...` under the prompt so continuation lines line up with the
+// first one.
+fn continuation_prompt(prompt: &str) -> String {
+    let width = prompt.chars().count().max(4);
+    format!("{:>width$}", "... ", width = width)
+}
+
+pub fn repl(config: Config, repl_config: ReplConfig) {
+    let mut rl: Editor<CakeHelper, DefaultHistory> = Editor::new().unwrap();
+    rl.set_helper(Some(CakeHelper::default()));
+    if let Some(history) = &repl_config.history_file {
+        // A missing file just means there is no history yet.
+        let _ = rl.load_history(history);
+    }
+
+    let continuation = continuation_prompt(&repl_config.prompt);
+    let mut session = Session::new(config);
+    let mut buffer = String::new();
     loop {
-        let readline = rl.readline("cake-repl > ");
-        match readline {
+        if let Some(helper) = rl.helper_mut() {
+            helper.bindings = session.names();
+        }
+        let prompt = if buffer.is_empty() {
+            repl_config.prompt.as_str()
+        } else {
+            continuation.as_str()
+        };
+        match rl.readline(prompt) {
+            Ok(line) if buffer.is_empty() && line.trim_start().starts_with(':') => {
+                let _ = rl.add_history_entry(line.as_str());
+                if let Some(output) = session.command(&line) {
+                    println!("{}", output);
+                }
+            }
             Ok(line) => {
-                let lex = lexer::Lexer::new(&line);
-                let mut parser = parser::Parser::new(lex);
-                let res = parser.parse();
-                println!("{}", res.literal());
+                if !buffer.is_empty() {
+                    buffer.push('\n');
+                }
+                buffer.push_str(&line);
+                if !is_complete(&buffer) {
+                    continue;
+                }
+                let input = std::mem::take(&mut buffer);
+                if input.trim().is_empty() {
+                    continue;
+                }
+                let _ = rl.add_history_entry(input.as_str());
+                if let Some(output) = session.eval(&input) {
+                    println!("{}", output);
+                }
+            }
+            // Ctrl-C drops a pending multi-line entry, or exits at an empty
+            // prompt.
+            Err(ReadlineError::Interrupted) if !buffer.is_empty() => {
+                buffer.clear();
             }
             Err(ReadlineError::Interrupted) => {
                 break;
@@ -27,4 +97,58 @@ pub fn repl() {
             }
         }
     }
+
+    if let Some(history) = &repl_config.history_file {
+        if let Err(err) = rl.save_history(history) {
+            println!(
+                "Error: could not save history to {}: {}",
+                history.display(),
+                err
+            );
+        }
+    }
+}
+
+// An entry is complete once every `(`, `{` and `[` is closed, no string is
+// left open and its last token can end a statement, as the end of the input
+// then does. Anything else, like `let a =` or `1 +`, asks for a continuation
+// line.
+pub fn is_complete(input: &str) -> bool {
+    let mut lexer = lexer::Lexer::new(input);
+    let mut depth: i32 = 0;
+    let mut last: Option<Token> = None;
+    loop {
+        let token = lexer.next_token();
+        match token {
+            Token::EOF => break,
+            // An unterminated string literal.
+            Token::Illegal(ref c) if c == &['"'] => return false,
+            Token::Comment(_) => continue,
+            Token::LParen | Token::LBrace | Token::LBracket => depth += 1,
+            Token::RParen | Token::RBrace | Token::RBracket => depth -= 1,
+            _ => {}
+        }
+        last = Some(token);
+    }
+    // Extra closing delimiters can't be fixed by reading more input, let the
+    // parser report them.
+    depth < 0 || (depth == 0 && last.as_ref().is_none_or(ends_statement))
+}
+
+fn ends_statement(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Ident(_)
+            | Token::Int(_)
+            | Token::String(_)
+            | Token::TemplateEnd(_)
+            | Token::True
+            | Token::False
+            | Token::RParen
+            | Token::RBracket
+            | Token::RBrace
+            | Token::Question
+            | Token::Semicolon
+            | Token::Return
+    )
 }
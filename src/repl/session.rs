@@ -0,0 +1,173 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::{
+    ast::{Node, Program},
+    checker::{self, Type},
+    evaluator::{Config, Evaluator},
+    lexer,
+    object::{
+        environment::{Env, Environment},
+        Object,
+    },
+    parser,
+};
+
+const HELP: &str = "\
+:help            show this message
+:tokens <code>   show the tokens of <code>
+:ast <code>      show the syntax tree of <code>
+:type <expr>     show the type of <expr>, without evaluating it
+:load <file>     run a file in the current session
+:env             list the bindings of the session
+:reset           drop every binding and start over";
+
+// State kept between REPL entries: the global environment and the evaluator
+// running in it.
+pub struct Session {
+    config: Config,
+    evaluator: Evaluator,
+    env: Env,
+}
+
+impl Session {
+    pub fn new(config: Config) -> Self {
+        Self {
+            evaluator: Evaluator::with_config(config.clone()),
+            env: Environment::new(),
+            config,
+        }
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let bindings = self.env.borrow().bindings();
+        bindings.into_iter().map(|(name, _)| name).collect()
+    }
+
+    // Runs one entry and returns what should be printed, if anything.
+    pub fn eval(&mut self, input: &str) -> Option<String> {
+        let program = match parse(input) {
+            Ok(program) => program,
+            Err(errors) => return Some(errors),
+        };
+        shown(self.evaluator.eval(&program, &self.env))
+    }
+
+    // Runs a `:command` line.
+    pub fn command(&mut self, line: &str) -> Option<String> {
+        let line = line.trim();
+        let (command, arg) = match line.split_once(char::is_whitespace) {
+            Some((command, arg)) => (command, arg.trim()),
+            None => (line, ""),
+        };
+        match command {
+            ":help" => Some(String::from(HELP)),
+            ":tokens" => {
+                let tokens = lexer::Lexer::new(arg).read_all();
+                Some(format!("{:?}", tokens))
+            }
+            ":ast" => match parse(arg) {
+                Ok(program) => Some(program.literal()),
+                Err(errors) => Some(errors),
+            },
+            ":type" => match parse(arg) {
+                Ok(program) => {
+                    let globals = self
+                        .env
+                        .borrow()
+                        .bindings()
+                        .into_iter()
+                        .map(|(name, value)| (name, value_type(&value)))
+                        .collect::<HashMap<String, Type>>();
+                    let inference = checker::infer(&program, globals);
+                    match inference.errors {
+                        errors if !errors.is_empty() => {
+                            let errors = errors
+                                .iter()
+                                .map(|err| format!("Error: {} at {}", err, err.span))
+                                .collect::<Vec<String>>();
+                            Some(errors.join("\n"))
+                        }
+                        _ => Some(inference.ty.to_string()),
+                    }
+                }
+                Err(errors) => Some(errors),
+            },
+            // Run as a file, so that its imports are found next to it.
+            ":load" => match fs::read_to_string(arg).map(|source| parse(&source)) {
+                Ok(Ok(program)) => shown(self.evaluator.eval_file(
+                    &program,
+                    &self.env,
+                    Path::new(arg),
+                )),
+                Ok(Err(errors)) => Some(errors),
+                Err(err) => Some(format!("Error: {}: {}", arg, err)),
+            },
+            ":env" => {
+                let bindings = self.env.borrow().bindings();
+                let lines = bindings
+                    .iter()
+                    .map(|(name, value)| format!("{} = {}", name, value.repr()))
+                    .collect::<Vec<String>>();
+                (!lines.is_empty()).then(|| lines.join("\n"))
+            }
+            ":reset" => {
+                *self = Session::new(self.config.clone());
+                None
+            }
+            _ => Some(format!("unknown command {}, try :help", command)),
+        }
+    }
+}
+
+// What the checker knows of a value bound in the session.
+fn value_type(value: &Object) -> Type {
+    match value {
+        Object::Int(_) => Type::Int,
+        Object::Bool(_) => Type::Bool,
+        Object::String(_) => Type::Str,
+        Object::Null => Type::Null,
+        Object::Ok(_) | Object::Err(_) => Type::Result,
+        Object::ErrorValue(_) => Type::Error,
+        Object::Array(elements) => {
+            let types: Vec<Type> = elements.iter().map(value_type).collect();
+            match types.first() {
+                Some(first) if types.iter().all(|ty| ty == first) => {
+                    Type::Array(Box::new(first.clone()))
+                }
+                _ => Type::Array(Box::new(Type::Any)),
+            }
+        }
+        Object::Function(func) => {
+            let params = func
+                .params
+                .iter()
+                .map(|param| param.ty.as_ref().map_or(Type::Any, Type::from_annotation))
+                .collect();
+            Type::Func(params, Box::new(Type::Any))
+        }
+        _ => Type::Any,
+    }
+}
+
+// What the REPL prints for the value of an entry.
+fn shown(value: Object) -> Option<String> {
+    match value {
+        Object::Null => None,
+        value => Some(value.repr()),
+    }
+}
+
+fn parse(input: &str) -> Result<Program, String> {
+    let mut parser = parser::Parser::new(lexer::Lexer::new(input));
+    let program = parser.parse();
+    if parser.errors().is_empty() {
+        Ok(program)
+    } else {
+        let errors = parser
+            .errors()
+            .iter()
+            .map(|err| format!("Error: {}", err))
+            .collect::<Vec<String>>();
+        Err(errors.join("\n"))
+    }
+}
@@ -0,0 +1,155 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        evaluator::Config,
+        repl::{
+            helper::{word_start, CakeHelper},
+            is_complete,
+            session::Session,
+        },
+    };
+    use rustyline::highlight::Highlighter;
+
+    #[test]
+    fn test_is_complete() {
+        assert!(is_complete(""));
+        assert!(is_complete("let a = 1;"));
+        assert!(is_complete("if (a) { 1; }"));
+        assert!(is_complete("let f = func(x) {\n    x + 1;\n};"));
+        assert!(is_complete("1 + 2);"));
+        assert!(is_complete("try { f(); } catch (e) {\n    g();\n}"));
+        assert!(is_complete("if (a) { 1; } let b = 2;"));
+        assert!(is_complete("let a = 1; // done"));
+
+        // No `;` is needed at the end of an entry.
+        assert!(is_complete("let a = 1"));
+        assert!(is_complete("let f = func(x) {\n    x + 1\n}"));
+        assert!(is_complete("if (a) { 1; } let b = 2"));
+        assert!(is_complete("f(x)?"));
+        assert!(is_complete("return"));
+
+        assert!(!is_complete("let f = func(x) {"));
+        assert!(!is_complete("add(1,\n2"));
+        assert!(!is_complete("[1, 2"));
+        assert!(!is_complete("let s = \"abc;"));
+        assert!(!is_complete("let a = // later\n"));
+        assert!(!is_complete("1 +"));
+    }
+
+    #[test]
+    fn test_session_keeps_bindings() {
+        let mut session = Session::new(Config::default());
+        assert_eq!(session.eval("let a = 2;"), None);
+        assert_eq!(session.eval("let f = func(x) { x * a; };"), None);
+        assert_eq!(session.eval("f(21);"), Some(String::from("42")));
+        assert_eq!(session.eval("\"s\";"), Some(String::from("\"s\"")));
+        assert_eq!(
+            session.eval("let = 1;"),
+            Some(String::from("Error: expected: Ident([]), got: Assign"))
+        );
+    }
+
+    #[test]
+    fn test_session_commands() {
+        let mut session = Session::new(Config::default());
+        assert_eq!(
+            session.command(":tokens let a = 1;"),
+            Some(String::from(
+                "[Let, Ident(['a']), Assign, Int(['1']), Semicolon]"
+            ))
+        );
+        assert_eq!(
+            session.command(":ast 1 + 2 * 3"),
+            Some(String::from("Program {\n    (1 + (2 * 3));\n}"))
+        );
+        assert_eq!(session.command(":type 1 < 2"), Some(String::from("bool")));
+        assert_eq!(
+            session.command(":type 1 + true"),
+            Some(String::from(
+                "Error: type mismatch: expected int, got bool at 1:3"
+            ))
+        );
+        assert_eq!(session.command(":env"), None);
+
+        session.eval("let b = [1, \"x\"];");
+        session.eval("let a = 1;");
+        assert_eq!(
+            session.command(":env"),
+            Some(String::from("a = 1\nb = [1, \"x\"]"))
+        );
+        assert_eq!(session.command(":reset"), None);
+        assert_eq!(session.command(":env"), None);
+
+        // Imports are found next to the loaded file, not in the working
+        // directory.
+        let root = std::env::temp_dir().join(format!("cake-repl-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("five.cake"), "export let five = 5;").unwrap();
+        let file = root.join("main.cake");
+        std::fs::write(
+            &file,
+            "import \"five.cake\" as m;\nlet double = func(x) { x * 2; };\nlet ten = double(m.five);\n",
+        )
+        .unwrap();
+        assert_eq!(session.command(&format!(":load {}", file.display())), None);
+        assert_eq!(session.eval("ten;"), Some(String::from("10")));
+        std::fs::remove_dir_all(root).unwrap();
+
+        // `:type` uses the session's bindings but runs nothing.
+        assert_eq!(session.command(":type ten"), Some(String::from("int")));
+        assert_eq!(
+            session.command(":type double"),
+            Some(String::from("func(any) -> any"))
+        );
+        session.eval("let boom = func() { throw 1; };");
+        assert_eq!(
+            session.command(":type let x = boom()"),
+            Some(String::from("null"))
+        );
+        assert_eq!(
+            session.eval("x;"),
+            Some(String::from(
+                "UnknownIdentifier: identifier not found: x at 1:1"
+            ))
+        );
+
+        assert_eq!(
+            session.command(":nope"),
+            Some(String::from("unknown command :nope, try :help"))
+        );
+    }
+
+    #[test]
+    fn test_completion_candidates() {
+        let mut helper = CakeHelper::default();
+        helper.bindings = vec![String::from("total"), String::from("unwrap")];
+        assert_eq!(helper.candidates("t"), ["throw", "total", "true", "try"]);
+        assert_eq!(
+            helper.candidates("unw"),
+            ["unwrap", "unwrap_err", "unwrap_or"]
+        );
+        assert_eq!(helper.candidates("fu"), ["func"]);
+
+        assert_eq!(word_start("let x = unw", 11), 8);
+        assert_eq!(word_start("f(is_o", 6), 2);
+        assert_eq!(word_start("1 + ", 4), 4);
+    }
+
+    #[test]
+    fn test_highlight() {
+        let helper = CakeHelper::default();
+        assert_eq!(
+            helper.highlight("let s = ok(\"a(\") + 1;", 0),
+            "\x1b[35mlet\x1b[0m s = \x1b[36mok\x1b[0m(\x1b[32m\"a(\"\x1b[0m) + \x1b[33m1\x1b[0m;"
+        );
+
+        // With the cursor after the closing parenthesis, both ends of the
+        // pair are highlighted; the one inside the string is ignored.
+        let line = "f(\"(\", [1]);";
+        helper.highlight_char(line, 11, false);
+        assert_eq!(
+            helper.highlight(line, 11),
+            "f\x1b[1;34m(\x1b[0m\x1b[32m\"(\"\x1b[0m, [\x1b[33m1\x1b[0m]\x1b[1;34m)\x1b[0m;"
+        );
+    }
+}
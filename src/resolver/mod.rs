@@ -0,0 +1,530 @@
+use std::fmt::Display;
+
+use crate::{
+    ast::{ident::Ident, interpolated::Fragment, pattern::Pattern, Expr, Program, Statement},
+    evaluator::builtins,
+    token::Span,
+};
+
+mod test;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeKind {
+    // The top level of a program. Its bindings are globals, kept by name so
+    // that later REPL entries and functions defined earlier can see them.
+    Global,
+    // A function body, with the parameters. Each call gets a frame with a
+    // slot for each binding. Blocks share the scope around them, as they do
+    // when evaluated.
+    Function,
+    // A `catch` block with the error it binds, or a `match` arm with the
+    // names of its pattern. It runs in an environment of its own, enclosed
+    // by the one around it, and so gets a frame like a function.
+    Block,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingKind {
+    Let,
+    Param,
+    Catch,
+    Import,
+    // A name in a `match` pattern.
+    Pattern,
+}
+
+// A local binding: `index` in the frame `depth` functions out from the use.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Slot {
+    pub depth: usize,
+    pub index: usize,
+}
+
+// Where the local names of a program live at runtime, by the id of their
+// identifier. Globals are looked up by name and have no slot.
+pub type Slots = Vec<Option<Slot>>;
+
+#[derive(Debug, Clone)]
+pub struct Binding {
+    pub name: String,
+    pub span: Span,
+    pub kind: BindingKind,
+    pub scope: usize,
+    // Index in the frame of the function, `None` for globals.
+    pub slot: Option<usize>,
+    pub uses: usize,
+    // The scope of the function a `let` binds, if it binds a literal one.
+    pub body: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Use {
+    pub name: String,
+    pub span: Span,
+    pub binding: Option<usize>,
+    pub scope: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Scope {
+    pub kind: ScopeKind,
+    pub parent: Option<usize>,
+    pub bindings: Vec<usize>,
+    // Char offsets covered by the names in the scope, a stand-in for its
+    // extent.
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    Undefined,
+    Shadowed,
+    Unused,
+    Unreachable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub message: String,
+    pub span: Span,
+}
+
+impl Diagnostic {
+    // Undefined names fail when they are evaluated, the rest is only suspicious.
+    pub fn is_error(&self) -> bool {
+        self.kind == DiagnosticKind::Undefined
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+// The names of a program: their scopes, where each one is bound and what
+// refers to it.
+#[derive(Debug, Clone, Default)]
+pub struct Resolution {
+    pub scopes: Vec<Scope>,
+    pub bindings: Vec<Binding>,
+    pub uses: Vec<Use>,
+    pub slots: Slots,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+pub fn resolve(program: &Program) -> Resolution {
+    resolve_in(program, &[])
+}
+
+// Resolves `program` to run after `globals` are bound, e.g. by earlier REPL
+// entries.
+pub fn resolve_in(program: &Program, globals: &[String]) -> Resolution {
+    let mut resolver = Resolver {
+        globals,
+        res: Resolution::default(),
+        frames: vec![],
+        pending: vec![],
+    };
+    resolver.res.scopes.push(Scope {
+        kind: ScopeKind::Global,
+        parent: None,
+        bindings: vec![],
+        start: 0,
+        end: usize::MAX,
+    });
+    resolver.statements(&program.statements, 0);
+    resolver.finish()
+}
+
+struct Resolver<'a> {
+    globals: &'a [String],
+    res: Resolution,
+    // Next free slot of each function frame being resolved, innermost last.
+    frames: Vec<usize>,
+    // Uses not bound when they were reached, with their identifier.
+    pending: Vec<(usize, &'a Ident)>,
+}
+
+impl<'a> Resolver<'a> {
+    fn finish(mut self) -> Resolution {
+        // A function can refer to names bound after it, such as a mutually
+        // recursive one: they are bound by the time its body runs.
+        for (id, ident) in std::mem::take(&mut self.pending) {
+            let found = self.lookup_later(&ident.name, self.res.uses[id].scope);
+            match found {
+                Some((binding, depth)) => {
+                    self.bind_use(id, ident, binding, depth);
+                }
+                None if self.is_known(&ident.name) => {}
+                None => self.report(
+                    DiagnosticKind::Undefined,
+                    format!("undefined identifier: {}", ident.name),
+                    ident.span,
+                ),
+            }
+        }
+        for i in 0..self.res.bindings.len() {
+            let binding = &self.res.bindings[i];
+            if binding.uses == 0 && !binding.name.starts_with('_') {
+                let message = format!("unused binding: {}", binding.name);
+                let span = binding.span;
+                self.report(DiagnosticKind::Unused, message, span);
+            }
+        }
+        self.res.diagnostics.sort_by_key(|d| d.span.start);
+        self.res
+    }
+
+    fn report(&mut self, kind: DiagnosticKind, message: String, span: Span) {
+        self.res.diagnostics.push(Diagnostic {
+            kind,
+            message,
+            span,
+        });
+    }
+
+    fn is_known(&self, name: &str) -> bool {
+        builtins::lookup(name).is_some() || self.globals.iter().any(|g| g == name)
+    }
+
+    fn push_scope(&mut self, kind: ScopeKind, parent: usize) -> usize {
+        self.res.scopes.push(Scope {
+            kind,
+            parent: Some(parent),
+            bindings: vec![],
+            start: usize::MAX,
+            end: 0,
+        });
+        self.res.scopes.len() - 1
+    }
+
+    // Resolves a list of statements, reporting the first one that follows a
+    // statement which never completes.
+    fn statements(&mut self, statements: &'a [Statement], scope: usize) {
+        let mut exit: Option<Span> = None;
+        let mut reported = false;
+        for statement in statements {
+            if let Statement::Comment(_) = statement {
+                continue;
+            }
+            if let (Some(exit), false) = (exit, reported) {
+                let span = first_span(statement).unwrap_or(exit);
+                self.report(
+                    DiagnosticKind::Unreachable,
+                    String::from("unreachable code"),
+                    span,
+                );
+                reported = true;
+            }
+            self.statement(statement, scope);
+            if exit.is_none() && statement.always_exits() {
+                exit = Some(first_span(statement).unwrap_or_default());
+            }
+        }
+    }
+
+    fn statement(&mut self, statement: &'a Statement, scope: usize) {
+        match statement {
+            Statement::Let(let_stat) => {
+                let body = self.res.scopes.len();
+                self.expr(&let_stat.expr, scope);
+                // Defaults run before any of the names are bound.
+                for default in let_stat.pattern.defaults() {
+                    self.expr(default, scope);
+                }
+                for ident in let_stat.pattern.bindings() {
+                    let binding = self.define(ident, BindingKind::Let, scope);
+                    if let (Pattern::Binding(_), Expr::Function(..)) =
+                        (&let_stat.pattern, &let_stat.expr)
+                    {
+                        self.res.bindings[binding].body = Some(body);
+                    }
+                    // Modules that import an exported binding use it.
+                    if let_stat.export {
+                        self.res.bindings[binding].uses += 1;
+                    }
+                }
+            }
+            Statement::Import(import) => {
+                self.define(&import.alias, BindingKind::Import, scope);
+            }
+            Statement::Return(return_stat) => {
+                if let Some(expr) = &return_stat.return_expr {
+                    self.expr(expr, scope);
+                }
+            }
+            Statement::Throw(throw_stat) => self.expr(&throw_stat.expr, scope),
+            Statement::Expression(expr) => self.expr(expr, scope),
+            Statement::BlockStatement(block) => self.statements(block, scope),
+            Statement::Try(try_stat) => {
+                self.statement(&try_stat.body, scope);
+                if let Some(catch) = &try_stat.catch {
+                    let inner = self.push_scope(ScopeKind::Block, scope);
+                    self.frames.push(0);
+                    self.define(&catch.ident, BindingKind::Catch, inner);
+                    self.statement(&catch.body, inner);
+                    self.frames.pop();
+                }
+                if let Some(finally) = &try_stat.finally {
+                    self.statement(finally, scope);
+                }
+            }
+            Statement::Comment(_) | Statement::Illegal(_) => {}
+        }
+    }
+
+    fn expr(&mut self, expr: &'a Expr, scope: usize) {
+        match expr {
+            Expr::Ident(ident) => self.use_name(ident, scope),
+            Expr::Int(_) | Expr::Bool(_) | Expr::String(_) | Expr::Illegal(_) => {}
+            Expr::Prefix(_, right, _) => self.expr(right, scope),
+            Expr::Infix(left, _, right, _) => {
+                self.expr(left, scope);
+                self.expr(right, scope);
+            }
+            Expr::Function(params, _, body) => {
+                let inner = self.push_scope(ScopeKind::Function, scope);
+                self.frames.push(0);
+                for param in params {
+                    self.define(&param.ident, BindingKind::Param, inner);
+                }
+                self.statement(body, inner);
+                self.frames.pop();
+            }
+            Expr::Call(func, args, _) => {
+                self.expr(func, scope);
+                for arg in args {
+                    self.expr(arg, scope);
+                }
+            }
+            Expr::Array(elements) => {
+                for element in elements {
+                    self.expr(element, scope);
+                }
+            }
+            Expr::Index(left, index, _) => {
+                self.expr(left, scope);
+                self.expr(index, scope);
+            }
+            // The name after the dot is looked up in the value, not in scope.
+            Expr::Propagate(expr, _) | Expr::Member(expr, _, _) => self.expr(expr, scope),
+            Expr::Interpolated(fragments, _) => {
+                for fragment in fragments {
+                    if let Fragment::Expr(expr) = fragment {
+                        self.expr(expr, scope);
+                    }
+                }
+            }
+            Expr::Hash(entries, _) => {
+                for (key, value) in entries {
+                    self.expr(key, scope);
+                    self.expr(value, scope);
+                }
+            }
+            Expr::If(if_expr) => {
+                self.expr(&if_expr.condition, scope);
+                self.statement(&if_expr.then, scope);
+                if let Some(else_) = &if_expr.else_ {
+                    self.statement(else_, scope);
+                }
+            }
+            // Defaults run before the arm's own scope is entered.
+            Expr::Match(subject, arms, _) => {
+                self.expr(subject, scope);
+                for arm in arms {
+                    for default in arm.pattern.defaults() {
+                        self.expr(default, scope);
+                    }
+                    let inner = self.push_scope(ScopeKind::Block, scope);
+                    self.frames.push(0);
+                    for ident in arm.pattern.bindings() {
+                        self.define(ident, BindingKind::Pattern, inner);
+                    }
+                    if let Some(guard) = &arm.guard {
+                        self.expr(guard, inner);
+                    }
+                    self.statement(&arm.body, inner);
+                    self.frames.pop();
+                }
+            }
+        }
+    }
+
+    fn define(&mut self, ident: &Ident, kind: BindingKind, scope: usize) -> usize {
+        let same_scope = self.res.scopes[scope]
+            .bindings
+            .iter()
+            .rev()
+            .find(|b| self.res.bindings[**b].name == ident.name)
+            .copied();
+        let slot = match same_scope {
+            // Binding a name again in the same scope replaces its value, so
+            // closures made in between see the new one.
+            Some(previous) => self.res.bindings[previous].slot,
+            None => {
+                if let Some(parent) = self.res.scopes[scope].parent {
+                    if let Some((outer, _)) = self.lookup(&ident.name, parent) {
+                        let message = format!(
+                            "{} shadows the binding at {}",
+                            ident.name, self.res.bindings[outer].span
+                        );
+                        self.report(DiagnosticKind::Shadowed, message, ident.span);
+                    }
+                }
+                match self.res.scopes[scope].kind {
+                    ScopeKind::Global => None,
+                    ScopeKind::Function | ScopeKind::Block => {
+                        let next = self
+                            .frames
+                            .last_mut()
+                            .expect("scopes with slots open a frame");
+                        *next += 1;
+                        Some(*next - 1)
+                    }
+                }
+            }
+        };
+        if let Some(index) = slot {
+            self.set_slot(ident, Slot { depth: 0, index });
+        }
+        let binding = self.res.bindings.len();
+        self.res.bindings.push(Binding {
+            name: ident.name.clone(),
+            span: ident.span,
+            kind,
+            scope,
+            slot,
+            uses: 0,
+            body: None,
+        });
+        self.res.scopes[scope].bindings.push(binding);
+        self.cover(scope, ident.span);
+        binding
+    }
+
+    fn use_name(&mut self, ident: &'a Ident, scope: usize) {
+        let id = self.res.uses.len();
+        self.res.uses.push(Use {
+            name: ident.name.clone(),
+            span: ident.span,
+            binding: None,
+            scope,
+        });
+        self.cover(scope, ident.span);
+        match self.lookup(&ident.name, scope) {
+            Some((binding, depth)) => self.bind_use(id, ident, binding, depth),
+            None => self.pending.push((id, ident)),
+        }
+    }
+
+    fn bind_use(&mut self, id: usize, ident: &Ident, binding: usize, depth: usize) {
+        self.res.uses[id].binding = Some(binding);
+        let binding = &mut self.res.bindings[binding];
+        binding.uses += 1;
+        if let Some(index) = binding.slot {
+            self.set_slot(ident, Slot { depth, index });
+        }
+    }
+
+    fn set_slot(&mut self, ident: &Ident, slot: Slot) {
+        if ident.id >= self.res.slots.len() {
+            self.res.slots.resize(ident.id + 1, None);
+        }
+        self.res.slots[ident.id] = Some(slot);
+    }
+
+    // The latest binding of `name` visible from `scope`, and how many
+    // function frames out it lives.
+    fn lookup(&self, name: &str, scope: usize) -> Option<(usize, usize)> {
+        let mut depth = 0;
+        let mut current = Some(scope);
+        while let Some(scope) = current {
+            let scope = &self.res.scopes[scope];
+            let found = scope
+                .bindings
+                .iter()
+                .rev()
+                .find(|b| self.res.bindings[**b].name == name);
+            if let Some(binding) = found {
+                return Some((*binding, depth));
+            }
+            if scope.kind != ScopeKind::Global {
+                depth += 1;
+            }
+            current = scope.parent;
+        }
+        None
+    }
+
+    // The first binding of `name` made, after the use in `scope`, in a scope
+    // around the function the use is in.
+    fn lookup_later(&self, name: &str, scope: usize) -> Option<(usize, usize)> {
+        let mut depth = 0;
+        let mut outside = false;
+        let mut current = Some(scope);
+        while let Some(scope) = current {
+            let scope = &self.res.scopes[scope];
+            if outside {
+                let found = scope
+                    .bindings
+                    .iter()
+                    .find(|b| self.res.bindings[**b].name == name);
+                if let Some(binding) = found {
+                    return Some((*binding, depth));
+                }
+            }
+            match scope.kind {
+                ScopeKind::Global => {}
+                ScopeKind::Function => {
+                    depth += 1;
+                    outside = true;
+                }
+                ScopeKind::Block => depth += 1,
+            }
+            current = scope.parent;
+        }
+        None
+    }
+
+    // Grows `scope` and the scopes around it to include `span`.
+    fn cover(&mut self, scope: usize, span: Span) {
+        let mut current = Some(scope);
+        while let Some(scope) = current {
+            let scope = &mut self.res.scopes[scope];
+            scope.start = scope.start.min(span.start);
+            scope.end = scope.end.max(span.end);
+            current = scope.parent;
+        }
+    }
+}
+
+// The first location found in a statement, if any part of it has one.
+fn first_span(statement: &Statement) -> Option<Span> {
+    match statement {
+        Statement::Let(let_stat) => Some(let_stat.ident().map_or(let_stat.span, |i| i.span)),
+        Statement::Return(return_stat) => Some(return_stat.span),
+        Statement::Throw(throw_stat) => Some(throw_stat.span),
+        Statement::Expression(expr) => expr_span(expr),
+        Statement::BlockStatement(block) => block.iter().find_map(first_span),
+        Statement::Try(try_stat) => Some(try_stat.span),
+        Statement::Import(import) => Some(import.span),
+        Statement::Comment(_) | Statement::Illegal(_) => None,
+    }
+}
+
+fn expr_span(expr: &Expr) -> Option<Span> {
+    match expr {
+        Expr::Function(params, _, body) => params
+            .first()
+            .map(|param| param.ident.span)
+            .or_else(|| first_span(body)),
+        Expr::Array(elements) => elements.iter().find_map(expr_span),
+        Expr::Hash(entries, _) => entries
+            .iter()
+            .find_map(|(key, value)| expr_span(key).or_else(|| expr_span(value))),
+        expr => expr.span(),
+    }
+}
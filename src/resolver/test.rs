@@ -0,0 +1,144 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ast::{ident::Ident, Expr, Program, Statement},
+        lexer,
+        parser::Parser,
+        resolver::{resolve, resolve_in, DiagnosticKind, Slot},
+    };
+
+    fn parse(input: &str) -> Program {
+        let mut parser = Parser::new(lexer::Lexer::new(input));
+        let program = parser.parse();
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+        program
+    }
+
+    // The diagnostics of `input`, as `line:column message`.
+    fn diagnostics(input: &str) -> Vec<String> {
+        resolve(&parse(input))
+            .diagnostics
+            .iter()
+            .map(|d| format!("{} {}", d.span, d))
+            .collect()
+    }
+
+    #[test]
+    fn test_resolve_slots() {
+        let program = parse("let g = 1; let f = func(a, b) { let c = a; func() { c + b + g; }; };");
+        let resolution = resolve(&program);
+        let Statement::Let(f) = &program.statements[1] else {
+            panic!("expected a let");
+        };
+        let Expr::Function(params, _, body) = &f.expr else {
+            panic!("expected a function");
+        };
+        let slot = |depth, index| Some(Slot { depth, index });
+        let slot_of_ident = |ident: &Ident| resolution.slots.get(ident.id).copied().flatten();
+        let slot_of = |expr: &Expr| match expr {
+            Expr::Ident(ident) => slot_of_ident(ident),
+            _ => panic!("expected an identifier"),
+        };
+        assert_eq!(slot_of_ident(&params[0].ident), slot(0, 0));
+        assert_eq!(slot_of_ident(&params[1].ident), slot(0, 1));
+        let Statement::BlockStatement(block) = body.as_ref() else {
+            panic!("expected a block");
+        };
+        let Statement::Let(c) = &block[0] else {
+            panic!("expected a let");
+        };
+        assert_eq!(slot_of_ident(c.ident().unwrap()), slot(0, 2));
+        assert_eq!(slot_of(&c.expr), slot(0, 0));
+        // Uses in the inner function reach one frame out; globals have no slot.
+        let Statement::Expression(Expr::Function(_, _, inner)) = &block[1] else {
+            panic!("expected a function");
+        };
+        let Statement::BlockStatement(inner) = inner.as_ref() else {
+            panic!("expected a block");
+        };
+        let Statement::Expression(Expr::Infix(left, _, g, _)) = &inner[0] else {
+            panic!("expected an infix expression");
+        };
+        let Expr::Infix(c, _, b, _) = left.as_ref() else {
+            panic!("expected an infix expression");
+        };
+        assert_eq!(slot_of(c), slot(1, 2));
+        assert_eq!(slot_of(b), slot(1, 1));
+        assert_eq!(slot_of(g), None);
+    }
+
+    #[test]
+    fn test_resolve_undefined() {
+        assert_eq!(
+            diagnostics("let a = b; a; ok(a); c;"),
+            [
+                "1:9 undefined identifier: b",
+                "1:22 undefined identifier: c"
+            ]
+        );
+        // Functions may use names bound after them, the top level may not.
+        assert_eq!(
+            diagnostics(
+                "let even = func(n) { if (n == 0) { true; } else { odd(n - 1); } };
+                let odd = func(n) { if (n == 0) { false; } else { even(n - 1); } };
+                even(4); x; let x = 1;"
+            ),
+            ["3:26 undefined identifier: x", "3:33 unused binding: x"]
+        );
+        // Names bound by earlier REPL entries are known.
+        let program = parse("total + 1;");
+        assert!(resolve_in(&program, &[String::from("total")])
+            .diagnostics
+            .is_empty());
+    }
+
+    #[test]
+    fn test_resolve_shadowing_and_unused() {
+        assert_eq!(
+            diagnostics("let x = 1; let f = func(x) { let y = 2; x; }; f(x);"),
+            [
+                "1:25 x shadows the binding at 1:5",
+                "1:34 unused binding: y",
+            ]
+        );
+        // Binding a name again in the same scope is not shadowing, and `_`
+        // marks a binding as unused on purpose.
+        assert_eq!(
+            diagnostics("let a = 1; let a = a + 1; let f = func(_b) { a; }; f(1);"),
+            Vec::<String>::new()
+        );
+        let resolution = resolve(&parse("try { 1; } catch (e) { 2; };"));
+        assert_eq!(resolution.diagnostics[0].kind, DiagnosticKind::Unused);
+        // The error is bound in a scope of the handler's own.
+        assert_eq!(
+            diagnostics("let e = 1; try { 1; } catch (e) { e; }; e;"),
+            ["1:30 e shadows the binding at 1:5"]
+        );
+        // So are the names of a `match` arm.
+        assert_eq!(
+            diagnostics("let x = 1; match (5) { x => x }; x;"),
+            ["1:24 x shadows the binding at 1:5"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_unreachable() {
+        assert_eq!(
+            diagnostics("let f = func() { return 1; f(); f(); }; f();"),
+            ["1:28 unreachable code"]
+        );
+        // Only when every branch leaves.
+        assert_eq!(
+            diagnostics(
+                "let f = func(a) { if (a) { return 1; } else { throw 2; } a; }; f(1);
+                let g = func(a) { if (a) { return 1; } 3; }; g(1);"
+            ),
+            ["1:58 unreachable code"]
+        );
+        // Nothing after the `return` has a location: point at the `return`.
+        assert_eq!(
+            diagnostics("let f = func() { return 1; 2; }; f();"),
+            ["1:18 unreachable code"]
+        );
+    }
+}
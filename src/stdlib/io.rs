@@ -0,0 +1,65 @@
+//! `import "io" as io;`: files and standard input. Failures are returned as
+//! `err(message)` rather than raised, so callers can handle them or pass them
+//! on with `?`.
+
+use std::{fs, io::BufRead};
+
+use crate::{
+    evaluator::{builtins::check_arity, Evaluator},
+    object::{BuiltinFn, Object},
+};
+
+use super::{strings, value};
+
+pub const FUNCTIONS: &[(&str, BuiltinFn)] = &[
+    ("read_file", read_file),
+    ("write_file", write_file),
+    ("read_line", read_line),
+];
+
+fn ok(value: Object) -> Object {
+    Object::Ok(Box::new(value))
+}
+
+fn err(message: String) -> Object {
+    Object::Err(Box::new(Object::String(message)))
+}
+
+/// `read_file(path: str) -> result`: `ok` with the contents of the file at
+/// `path`, or `err` with why it could not be read.
+fn read_file(_: &mut Evaluator, args: Vec<Object>) -> Object {
+    value(
+        strings("io.read_file", &args, 1).map(|s| match fs::read_to_string(s[0]) {
+            Ok(contents) => ok(Object::String(contents)),
+            Err(e) => err(format!("cannot read {}: {}", s[0], e)),
+        }),
+    )
+}
+
+/// `write_file(path: str, contents: str) -> result`: `ok(null)` once the file
+/// at `path` holds `contents`, or `err` with why it could not be written.
+fn write_file(_: &mut Evaluator, args: Vec<Object>) -> Object {
+    value(
+        strings("io.write_file", &args, 2).map(|s| match fs::write(s[0], s[1]) {
+            Ok(()) => ok(Object::Null),
+            Err(e) => err(format!("cannot write {}: {}", s[0], e)),
+        }),
+    )
+}
+
+/// `read_line() -> result`: `ok` with the next line of standard input without
+/// its line ending, `ok(null)` at the end of the input, or `err`.
+fn read_line(_: &mut Evaluator, args: Vec<Object>) -> Object {
+    if let Err(e) = check_arity("io.read_line", &args, 0) {
+        return e;
+    }
+    let mut line = String::new();
+    match std::io::stdin().lock().read_line(&mut line) {
+        Ok(0) => ok(Object::Null),
+        Ok(_) => {
+            let trimmed = line.trim_end_matches(['\n', '\r']);
+            ok(Object::String(trimmed.to_string()))
+        }
+        Err(e) => err(format!("cannot read standard input: {}", e)),
+    }
+}
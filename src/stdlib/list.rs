@@ -0,0 +1,206 @@
+//! `import "list" as list;`: building and transforming arrays. Arrays are
+//! values: these functions return new ones and leave their arguments alone.
+
+use std::{cmp::Ordering, rc::Rc};
+
+use crate::{
+    evaluator::{
+        builtins::{check_arity, error},
+        Evaluator,
+    },
+    object::{error::ErrorKind, BuiltinFn, Object},
+};
+
+use super::{array, int, value};
+
+pub const FUNCTIONS: &[(&str, BuiltinFn)] = &[
+    ("map", map),
+    ("filter", filter),
+    ("reduce", reduce),
+    ("sort", sort),
+    ("reverse", reverse),
+    ("zip", zip),
+    ("range", range),
+];
+
+// Calls back into Cake; an error or a `return` out of the callback stops the
+// builtin.
+fn call(evaluator: &mut Evaluator, func: &Object, args: Vec<Object>) -> Result<Object, Object> {
+    match evaluator.call(func.clone(), args) {
+        value if value.is_abrupt() => Err(value),
+        value => Ok(value),
+    }
+}
+
+/// `map(items: [any], f: func(any) -> any) -> [any]`: `f` applied to each
+/// element of `items`.
+fn map(evaluator: &mut Evaluator, args: Vec<Object>) -> Object {
+    let res = check_arity("list.map", &args, 2).and_then(|_| {
+        let items = array("list.map", &args[0])?;
+        let mapped = items
+            .iter()
+            .map(|item| call(evaluator, &args[1], vec![item.clone()]))
+            .collect::<Result<Vec<Object>, Object>>()?;
+        Ok(Object::Array(Rc::new(mapped)))
+    });
+    value(res)
+}
+
+/// `filter(items: [any], keep: func(any) -> bool) -> [any]`: the elements of
+/// `items` for which `keep` is truthy, in order.
+fn filter(evaluator: &mut Evaluator, args: Vec<Object>) -> Object {
+    let res = check_arity("list.filter", &args, 2).and_then(|_| {
+        let items = array("list.filter", &args[0])?;
+        let mut kept = Vec::new();
+        for item in items.iter() {
+            if call(evaluator, &args[1], vec![item.clone()])?.is_truthy() {
+                kept.push(item.clone());
+            }
+        }
+        Ok(Object::Array(Rc::new(kept)))
+    });
+    value(res)
+}
+
+/// `reduce(items: [any], initial: any, f: func(any, any) -> any) -> any`:
+/// `f(acc, item)` for each element in turn, where `acc` is `initial` and then
+/// the previous result.
+fn reduce(evaluator: &mut Evaluator, args: Vec<Object>) -> Object {
+    let res = check_arity("list.reduce", &args, 3).and_then(|_| {
+        let items = array("list.reduce", &args[0])?;
+        let mut acc = args[1].clone();
+        for item in items.iter() {
+            acc = call(evaluator, &args[2], vec![acc, item.clone()])?;
+        }
+        Ok(acc)
+    });
+    value(res)
+}
+
+/// `sort(items: [any]) -> [any]` sorts integers or strings in increasing
+/// order. `sort(items: [any], compare: func(any, any) -> int) -> [any]`
+/// sorts anything: `compare(a, b)` is negative when `a` comes first,
+/// positive when `b` does and zero when either can. The sort is stable.
+fn sort(evaluator: &mut Evaluator, args: Vec<Object>) -> Object {
+    if args.len() != 2 {
+        if let Err(err) = check_arity("list.sort", &args, 1) {
+            return err;
+        }
+    }
+    let items = match array("list.sort", &args[0]) {
+        Ok(items) => items,
+        Err(err) => return err,
+    };
+    let mut sorted = items.as_ref().clone();
+    // The first error stops the comparisons that count; the sort itself
+    // cannot be interrupted.
+    let mut failed = None;
+    let mut compare = |a: &Object, b: &Object| -> Ordering {
+        if failed.is_some() {
+            return Ordering::Equal;
+        }
+        let res = match args.get(1) {
+            Some(compare) => call(evaluator, compare, vec![a.clone(), b.clone()])
+                .and_then(|order| int("list.sort", &order))
+                .map(|order| order.cmp(&0)),
+            None => natural_order(a, b),
+        };
+        res.unwrap_or_else(|err| {
+            failed = Some(err);
+            Ordering::Equal
+        })
+    };
+    sorted.sort_by(|a, b| compare(a, b));
+    match failed {
+        Some(err) => err,
+        None => Object::Array(Rc::new(sorted)),
+    }
+}
+
+fn natural_order(a: &Object, b: &Object) -> Result<Ordering, Object> {
+    match (a, b) {
+        (Object::Int(a), Object::Int(b)) => Ok(a.cmp(b)),
+        (Object::String(a), Object::String(b)) => Ok(a.cmp(b)),
+        (a, b) => Err(error(
+            ErrorKind::TypeMismatch,
+            format!(
+                "cannot sort {} and {} without a comparison function",
+                a.type_name(),
+                b.type_name()
+            ),
+        )),
+    }
+}
+
+/// `reverse(items: [any]) -> [any]`: the elements of `items`, last first.
+fn reverse(_: &mut Evaluator, args: Vec<Object>) -> Object {
+    let res = check_arity("list.reverse", &args, 1).and_then(|_| {
+        let items = array("list.reverse", &args[0])?;
+        Ok(Object::Array(Rc::new(
+            items.iter().rev().cloned().collect(),
+        )))
+    });
+    value(res)
+}
+
+/// `zip(a: [any], b: [any]) -> [[any]]`: pairs of the elements of `a` and
+/// `b` at the same index, as long as the shorter of the two.
+fn zip(_: &mut Evaluator, args: Vec<Object>) -> Object {
+    let res = check_arity("list.zip", &args, 2).and_then(|_| {
+        let a = array("list.zip", &args[0])?;
+        let b = array("list.zip", &args[1])?;
+        let pairs = a
+            .iter()
+            .zip(b.iter())
+            .map(|(a, b)| Object::Array(Rc::new(vec![a.clone(), b.clone()])))
+            .collect();
+        Ok(Object::Array(Rc::new(pairs)))
+    });
+    value(res)
+}
+
+/// `range(end: int) -> [int]`, `range(start: int, end: int) -> [int]` and
+/// `range(start: int, end: int, step: int) -> [int]`: the integers from
+/// `start`, 0 by default, up to but not including `end`, `step` apart. A
+/// negative `step` counts down.
+fn range(evaluator: &mut Evaluator, args: Vec<Object>) -> Object {
+    let bounds: Result<Vec<i64>, Object> = args.iter().map(|arg| int("list.range", arg)).collect();
+    let (start, end, step) = match bounds.as_deref() {
+        Ok([end]) => (0, *end, 1),
+        Ok([start, end]) => (*start, *end, 1),
+        Ok([start, end, step]) => (*start, *end, *step),
+        Ok(_) => {
+            return error(
+                ErrorKind::WrongArity,
+                format!(
+                    "wrong number of arguments to `list.range`: expected 1 to 3, got {}",
+                    args.len()
+                ),
+            )
+        }
+        Err(err) => return err.clone(),
+    };
+    if step == 0 {
+        return error(
+            ErrorKind::TypeMismatch,
+            String::from("`list.range` step cannot be zero"),
+        );
+    }
+    // Checked before anything is allocated: ranges can be much larger than
+    // the memory there is.
+    let distance = (i128::from(end) - i128::from(start)) * i128::from(step.signum());
+    let len = (distance.max(0) + i128::from(step).abs() - 1) / i128::from(step).abs();
+    if let Err(err) = evaluator.reserve(usize::try_from(len).unwrap_or(usize::MAX)) {
+        return err;
+    }
+    let mut res = Vec::new();
+    let mut i = start;
+    while (step > 0 && i < end) || (step < 0 && i > end) {
+        res.push(Object::Int(i));
+        match i.checked_add(step) {
+            Some(next) => i = next,
+            None => break,
+        }
+    }
+    Object::Array(Rc::new(res))
+}
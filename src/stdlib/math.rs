@@ -0,0 +1,88 @@
+//! `import "math" as math;`: arithmetic on integers, the only numbers Cake
+//! has. Like the operators, results wrap around on overflow.
+
+use crate::{
+    evaluator::{builtins::error, Evaluator},
+    object::{error::ErrorKind, BuiltinFn, Object},
+};
+
+use super::{int, ints, value};
+
+pub const FUNCTIONS: &[(&str, BuiltinFn)] = &[
+    ("abs", abs),
+    ("min", min),
+    ("max", max),
+    ("pow", pow),
+    ("sqrt", sqrt),
+    ("floor", floor),
+];
+
+/// `abs(n: int) -> int`: `n` without its sign.
+fn abs(_: &mut Evaluator, args: Vec<Object>) -> Object {
+    value(ints("math.abs", &args, 1).map(|n| Object::Int(n[0].wrapping_abs())))
+}
+
+/// `min(a: int, ...) -> int`: the smallest of one or more integers.
+fn min(_: &mut Evaluator, args: Vec<Object>) -> Object {
+    value(extreme("math.min", &args).map(|ints| Object::Int(ints.into_iter().min().unwrap_or(0))))
+}
+
+/// `max(a: int, ...) -> int`: the largest of one or more integers.
+fn max(_: &mut Evaluator, args: Vec<Object>) -> Object {
+    value(extreme("math.max", &args).map(|ints| Object::Int(ints.into_iter().max().unwrap_or(0))))
+}
+
+fn extreme(name: &str, args: &[Object]) -> Result<Vec<i64>, Object> {
+    if args.is_empty() {
+        return Err(error(
+            ErrorKind::WrongArity,
+            format!("`{}` expects at least one argument", name),
+        ));
+    }
+    args.iter().map(|arg| int(name, arg)).collect()
+}
+
+/// `pow(base: int, exp: int) -> int`: `base` multiplied by itself `exp`
+/// times. `exp` cannot be negative.
+fn pow(_: &mut Evaluator, args: Vec<Object>) -> Object {
+    value(ints("math.pow", &args, 2).and_then(|n| {
+        let exp = u32::try_from(n[1]).map_err(|_| {
+            error(
+                ErrorKind::TypeMismatch,
+                format!("exponent out of range: {}", n[1]),
+            )
+        })?;
+        Ok(Object::Int(n[0].wrapping_pow(exp)))
+    }))
+}
+
+/// `sqrt(n: int) -> int`: the square root of `n`, rounded down. `n` cannot
+/// be negative.
+fn sqrt(_: &mut Evaluator, args: Vec<Object>) -> Object {
+    value(ints("math.sqrt", &args, 1).and_then(|n| {
+        if n[0] < 0 {
+            return Err(error(
+                ErrorKind::TypeMismatch,
+                format!("square root of a negative number: {}", n[0]),
+            ));
+        }
+        Ok(Object::Int(n[0].isqrt()))
+    }))
+}
+
+/// `floor(a: int, b: int) -> int`: `a` divided by `b`, rounded down, where
+/// `a / b` rounds toward zero: `floor(-7, 2)` is `-4` and `-7 / 2` is `-3`.
+fn floor(_: &mut Evaluator, args: Vec<Object>) -> Object {
+    value(ints("math.floor", &args, 2).and_then(|n| {
+        let (a, b) = (n[0], n[1]);
+        if b == 0 {
+            return Err(error(
+                ErrorKind::DivisionByZero,
+                String::from("division by zero"),
+            ));
+        }
+        let quotient = a.wrapping_div(b);
+        let rounded = a.wrapping_rem(b) != 0 && (a < 0) != (b < 0);
+        Ok(Object::Int(quotient - i64::from(rounded)))
+    }))
+}
@@ -0,0 +1,101 @@
+//! The standard library: modules written in Rust, imported by name rather
+//! than by path.
+//!
+//! ```text
+//! import "math" as math;
+//! math.max(1, 2);
+//! ```
+//!
+//! Each module documents its functions with their Cake signatures.
+
+use std::rc::Rc;
+
+use crate::{
+    evaluator::builtins::{check_arity, error},
+    object::{error::ErrorKind, Builtin, BuiltinFn, Module, Object},
+};
+
+pub mod io;
+pub mod list;
+pub mod math;
+pub mod string;
+mod test;
+
+pub const MODULES: &[(&str, &[(&str, BuiltinFn)])] = &[
+    ("io", io::FUNCTIONS),
+    ("list", list::FUNCTIONS),
+    ("math", math::FUNCTIONS),
+    ("string", string::FUNCTIONS),
+];
+
+/// The module `import` gives for `name`, if it is one of the standard library.
+pub fn module(name: &str) -> Option<Rc<Module>> {
+    let (name, functions) = MODULES.iter().find(|(module, _)| *module == name)?;
+    let exports = functions
+        .iter()
+        .map(|(function, func)| {
+            let builtin = Builtin {
+                name: function,
+                func: *func,
+            };
+            (function.to_string(), Object::Builtin(builtin))
+        })
+        .collect();
+    Some(Rc::new(Module {
+        path: name.to_string(),
+        exports,
+    }))
+}
+
+// Argument checks shared by the modules. `name` is how the function is called
+// in messages, e.g. `math.abs`.
+
+fn type_error(name: &str, expected: &str, value: &Object) -> Object {
+    error(
+        ErrorKind::TypeMismatch,
+        format!(
+            "argument to `{}` must be {}, got {}",
+            name,
+            expected,
+            value.type_name()
+        ),
+    )
+}
+
+fn int(name: &str, value: &Object) -> Result<i64, Object> {
+    match value {
+        Object::Int(i) => Ok(*i),
+        other => Err(type_error(name, "INTEGER", other)),
+    }
+}
+
+fn string<'a>(name: &str, value: &'a Object) -> Result<&'a str, Object> {
+    match value {
+        Object::String(s) => Ok(s),
+        other => Err(type_error(name, "STRING", other)),
+    }
+}
+
+fn array(name: &str, value: &Object) -> Result<Rc<Vec<Object>>, Object> {
+    match value {
+        Object::Array(elements) => Ok(elements.clone()),
+        other => Err(type_error(name, "ARRAY", other)),
+    }
+}
+
+// `expected` integers.
+fn ints(name: &str, args: &[Object], expected: usize) -> Result<Vec<i64>, Object> {
+    check_arity(name, args, expected)?;
+    args.iter().map(|arg| int(name, arg)).collect()
+}
+
+// `expected` strings.
+fn strings<'a>(name: &str, args: &'a [Object], expected: usize) -> Result<Vec<&'a str>, Object> {
+    check_arity(name, args, expected)?;
+    args.iter().map(|arg| string(name, arg)).collect()
+}
+
+// Builtins return their errors as values.
+fn value(res: Result<Object, Object>) -> Object {
+    res.unwrap_or_else(|err| err)
+}
@@ -0,0 +1,163 @@
+//! `import "string" as string;`: working with text. Positions and lengths
+//! count characters, as indexing does.
+
+use std::rc::Rc;
+
+use crate::{
+    evaluator::{
+        builtins::{check_arity, error},
+        Evaluator,
+    },
+    object::{error::ErrorKind, BuiltinFn, Object},
+};
+
+use super::{array, string, strings, value};
+
+pub const FUNCTIONS: &[(&str, BuiltinFn)] = &[
+    ("split", split),
+    ("join", join),
+    ("trim", trim),
+    ("replace", replace),
+    ("upper", upper),
+    ("lower", lower),
+    ("contains", contains),
+    ("format", format),
+];
+
+/// `split(s: str, sep: str) -> [str]`: the parts of `s` between the
+/// occurrences of `sep`, or its characters when `sep` is empty.
+fn split(evaluator: &mut Evaluator, args: Vec<Object>) -> Object {
+    value(strings("string.split", &args, 2).and_then(|s| {
+        let len = if s[1].is_empty() {
+            s[0].chars().count()
+        } else {
+            s[0].matches(s[1]).count() + 1
+        };
+        evaluator.reserve(len)?;
+        let parts: Vec<Object> = if s[1].is_empty() {
+            s[0].chars()
+                .map(|c| Object::String(c.to_string()))
+                .collect()
+        } else {
+            s[0].split(s[1])
+                .map(|part| Object::String(part.to_string()))
+                .collect()
+        };
+        Ok(Object::Array(Rc::new(parts)))
+    }))
+}
+
+/// `join(parts: [any], sep: str) -> str`: the elements of `parts`, as
+/// printed, with `sep` between them.
+fn join(evaluator: &mut Evaluator, args: Vec<Object>) -> Object {
+    let res = check_arity("string.join", &args, 2).and_then(|_| {
+        let parts = array("string.join", &args[0])?;
+        let sep = string("string.join", &args[1])?;
+        let mut res = String::new();
+        for (i, part) in parts.iter().enumerate() {
+            let part = part.to_string();
+            let sep = if i == 0 { "" } else { sep };
+            evaluator.reserve_string(res.len() + sep.len() + part.len())?;
+            res.push_str(sep);
+            res.push_str(&part);
+        }
+        Ok(Object::String(res))
+    });
+    value(res)
+}
+
+/// `trim(s: str) -> str`: `s` without whitespace at either end.
+fn trim(_: &mut Evaluator, args: Vec<Object>) -> Object {
+    value(strings("string.trim", &args, 1).map(|s| Object::String(s[0].trim().to_string())))
+}
+
+/// `replace(s: str, from: str, to: str) -> str`: `s` with every occurrence
+/// of `from` replaced by `to`.
+fn replace(evaluator: &mut Evaluator, args: Vec<Object>) -> Object {
+    value(strings("string.replace", &args, 3).and_then(|s| {
+        if s[1].is_empty() {
+            return Err(error(
+                ErrorKind::TypeMismatch,
+                String::from("cannot replace an empty string"),
+            ));
+        }
+        let count = s[0].matches(s[1]).count();
+        let len =
+            (s[0].len() - count * s[1].len()).saturating_add(count.saturating_mul(s[2].len()));
+        evaluator.reserve_string(len)?;
+        Ok(Object::String(s[0].replace(s[1], s[2])))
+    }))
+}
+
+/// `upper(s: str) -> str`: `s` in upper case.
+fn upper(_: &mut Evaluator, args: Vec<Object>) -> Object {
+    value(strings("string.upper", &args, 1).map(|s| Object::String(s[0].to_uppercase())))
+}
+
+/// `lower(s: str) -> str`: `s` in lower case.
+fn lower(_: &mut Evaluator, args: Vec<Object>) -> Object {
+    value(strings("string.lower", &args, 1).map(|s| Object::String(s[0].to_lowercase())))
+}
+
+/// `contains(s: str, part: str) -> bool`: whether `part` occurs in `s`.
+fn contains(_: &mut Evaluator, args: Vec<Object>) -> Object {
+    value(strings("string.contains", &args, 2).map(|s| Object::Bool(s[0].contains(s[1]))))
+}
+
+/// `format(template: str, values: any...) -> str`: `template` with each `{}`
+/// replaced by the next value, as printed. `{{` and `}}` stand for braces.
+fn format(evaluator: &mut Evaluator, args: Vec<Object>) -> Object {
+    let Some(template) = args.first() else {
+        return error(
+            ErrorKind::WrongArity,
+            String::from("`string.format` expects a template"),
+        );
+    };
+    let template = match string("string.format", template) {
+        Ok(template) => template,
+        Err(err) => return err,
+    };
+    let mut values = args[1..].iter();
+    let mut res = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                res.push(c);
+            }
+            ('{', Some('}')) => {
+                chars.next();
+                match values.next() {
+                    Some(value) => {
+                        let value = value.to_string();
+                        if let Err(err) = evaluator.reserve_string(res.len() + value.len()) {
+                            return err;
+                        }
+                        res.push_str(&value);
+                    }
+                    None => {
+                        return error(
+                            ErrorKind::WrongArity,
+                            format!(
+                                "`string.format` has more placeholders than the {} values given",
+                                args.len() - 1
+                            ),
+                        )
+                    }
+                }
+            }
+            (c, _) => res.push(c),
+        }
+    }
+    if values.next().is_some() {
+        return error(
+            ErrorKind::WrongArity,
+            format!(
+                "`string.format` was given {} values for fewer placeholders",
+                args.len() - 1
+            ),
+        );
+    }
+    Object::String(res)
+}
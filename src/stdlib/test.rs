@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        evaluator::Evaluator, lexer::Lexer, object::environment::Environment, parser::Parser,
+    };
+
+    fn eval(input: &str) -> String {
+        let mut parser = Parser::new(Lexer::new(input));
+        let prog = parser.parse();
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+        let env = Environment::new();
+        Evaluator::default().eval(&prog, &env).to_string()
+    }
+
+    #[test]
+    fn test_math() {
+        let eval = |input: &str| eval(&format!("import \"math\" as m; {}", input));
+        assert_eq!(eval("m.abs(-3);"), "3");
+        assert_eq!(eval("m.min(4, 2, 8);"), "2");
+        assert_eq!(eval("m.max(4, 2, 8);"), "8");
+        assert_eq!(eval("m.pow(2, 10);"), "1024");
+        assert_eq!(eval("m.sqrt(17);"), "4");
+        assert_eq!(eval("m.floor(-7, 2);"), "-4");
+        assert_eq!(eval("m.floor(7, 2);"), "3");
+        assert_eq!(
+            eval("m.sqrt(-1);"),
+            "TypeMismatch: square root of a negative number: -1 at 1:21"
+        );
+        assert_eq!(
+            eval("m.abs(true);"),
+            "TypeMismatch: argument to `math.abs` must be INTEGER, got BOOLEAN at 1:21"
+        );
+        assert_eq!(
+            eval("m.floor(1, 0);"),
+            "DivisionByZero: division by zero at 1:21"
+        );
+    }
+
+    #[test]
+    fn test_string() {
+        let eval = |input: &str| eval(&format!("import \"string\" as s; {}", input));
+        assert_eq!(eval("s.split(\"a,b,c\", \",\");"), "[\"a\", \"b\", \"c\"]");
+        assert_eq!(eval("s.split(\"ab\", \"\");"), "[\"a\", \"b\"]");
+        assert_eq!(eval("s.join([1, 2, 3], \"-\");"), "1-2-3");
+        assert_eq!(eval("s.trim(\"  x \");"), "x");
+        assert_eq!(eval("s.replace(\"aXbX\", \"X\", \"-\");"), "a-b-");
+        assert_eq!(eval("s.upper(\"abc\");"), "ABC");
+        assert_eq!(eval("s.lower(\"ABC\");"), "abc");
+        assert_eq!(eval("s.contains(\"cake\", \"ak\");"), "true");
+        assert_eq!(
+            eval("s.format(\"{} + {} = {{{}}}\", 1, 2, 3);"),
+            "1 + 2 = {3}"
+        );
+        assert_eq!(
+            eval("s.format(\"{}\");"),
+            "WrongArity: `string.format` has more placeholders than the 0 values given at 1:23"
+        );
+    }
+
+    #[test]
+    fn test_list() {
+        let eval = |input: &str| eval(&format!("import \"list\" as l; {}", input));
+        assert_eq!(eval("l.map([1, 2, 3], func(x) { x * 2; });"), "[2, 4, 6]");
+        assert_eq!(
+            eval("l.filter(l.range(10), func(x) { x / 3 * 3 == x; });"),
+            "[0, 3, 6, 9]"
+        );
+        assert_eq!(
+            eval("l.reduce([1, 2, 3, 4], 0, func(acc, x) { acc + x; });"),
+            "10"
+        );
+        assert_eq!(eval("l.sort([3, 1, 2]);"), "[1, 2, 3]");
+        assert_eq!(
+            eval("l.sort([1, 3, 2], func(a, b) { b - a; });"),
+            "[3, 2, 1]"
+        );
+        assert_eq!(eval("l.reverse([1, 2, 3]);"), "[3, 2, 1]");
+        assert_eq!(eval("l.zip([1, 2, 3], [4, 5]);"), "[[1, 4], [2, 5]]");
+        assert_eq!(eval("l.range(2, 5);"), "[2, 3, 4]");
+        assert_eq!(eval("l.range(5, 0, -2);"), "[5, 3, 1]");
+        // Errors raised by the callback stop the builtin.
+        assert_eq!(
+            eval("l.map([1, 0], func(x) { 1 / x; });"),
+            "DivisionByZero: division by zero at 1:47\n    in <anonymous> (called at 1:21)"
+        );
+        assert_eq!(
+            eval("l.sort([1, \"a\"]);"),
+            "TypeMismatch: cannot sort STRING and INTEGER without a comparison function at 1:21"
+        );
+        assert_eq!(
+            eval("l.range(0, 1, 0);"),
+            "TypeMismatch: `list.range` step cannot be zero at 1:21"
+        );
+    }
+
+    #[test]
+    fn test_io() {
+        let file = std::env::temp_dir().join(format!("cake-io-{}.txt", std::process::id()));
+        let file = file.display();
+        assert_eq!(
+            eval(&format!(
+                "import \"io\" as io; io.write_file(\"{}\", \"cake\")?; io.read_file(\"{}\");",
+                file, file
+            )),
+            "ok(\"cake\")"
+        );
+        assert_eq!(
+            eval("import \"io\" as io; is_err(io.read_file(\"/nonexistent/cake\"));"),
+            "true"
+        );
+        assert_eq!(
+            eval("import \"nope\" as n;"),
+            "Import: module not found: nope at 1:1"
+        );
+    }
+}
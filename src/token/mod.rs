@@ -1,15 +1,24 @@
 #![allow(dead_code)]
 
-use std::fmt::Debug;
+use std::fmt::{Debug, Display};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Token {
     Illegal(Vec<char>),
+    #[allow(clippy::upper_case_acronyms)]
     EOF,
 
     // Identifiers + literals
     Ident(Vec<char>),
     Int(Vec<char>),
+    String(Vec<char>),
+    // The text of a string with embedded `${expr}`s: up to the first one,
+    // between two of them, and after the last one.
+    TemplateStart(Vec<char>),
+    TemplateMiddle(Vec<char>),
+    TemplateEnd(Vec<char>),
+    // Text of a `//` comment, without the slashes.
+    Comment(Vec<char>),
 
     // Operators
     Assign,
@@ -20,17 +29,29 @@ pub enum Token {
     Bang,
     LessThan,
     GreaterThan,
+    Question,
+    // `->`, before the return type of a function.
+    Arrow,
+    // `=>`, between a pattern and its arm in a `match`.
+    FatArrow,
+    // Member access, `m.name`.
+    Dot,
+    // `...`, before the name taking the rest of a destructured value.
+    Ellipsis,
 
     Eq,
     NotEq,
 
     // Delimiters
     Comma,
+    Colon,
     Semicolon,
     LParen,
     RParen,
     LBrace,
     RBrace,
+    LBracket,
+    RBracket,
 
     // Keywords
     Func,
@@ -40,17 +61,69 @@ pub enum Token {
     If,
     Else,
     Return,
+    Try,
+    Catch,
+    Finally,
+    Throw,
+    Import,
+    As,
+    Export,
+    Match,
 }
 
+pub const KEYWORDS: &[(&str, Token)] = &[
+    ("func", Token::Func),
+    ("let", Token::Let),
+    ("true", Token::True),
+    ("false", Token::False),
+    ("if", Token::If),
+    ("else", Token::Else),
+    ("return", Token::Return),
+    ("try", Token::Try),
+    ("catch", Token::Catch),
+    ("finally", Token::Finally),
+    ("throw", Token::Throw),
+    ("import", Token::Import),
+    ("as", Token::As),
+    ("export", Token::Export),
+    ("match", Token::Match),
+];
+
 pub fn lookup_ident(ident: &str) -> Token {
-    match ident {
-        "func" => Token::Func,
-        "let" => Token::Let,
-        "true" => Token::True,
-        "false" => Token::False,
-        "if" => Token::If,
-        "else" => Token::Else,
-        "return" => Token::Return,
-        _ => Token::Ident(ident.chars().collect()),
+    match KEYWORDS.iter().find(|(keyword, _)| *keyword == ident) {
+        Some((_, token)) => token.clone(),
+        None => Token::Ident(ident.chars().collect()),
+    }
+}
+
+// Location of a piece of source: `start..end` are char offsets, `line` and
+// `column` (both 1-based) locate `start`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn to(self, other: Span) -> Span {
+        let (first, last) = if self.start <= other.start {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        Span {
+            start: first.start,
+            end: first.end.max(last.end),
+            line: first.line,
+            column: first.column,
+        }
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
     }
 }